@@ -6,5 +6,9 @@ public class RustTranspilerModule {
     }
 
     public static native String nativeTranspile(String code, String filename, boolean isTypescript);
+    /** Typed AST of a hook (imports, exports and JSX with spans) as JSON */
+    public static native String nativeParseModule(String code, String filename, boolean isTypescript);
+    /** Element trees of a hook's JSX roots, with placeholders for dynamic expressions, as JSON */
+    public static native String nativeCompileUiTree(String code, String filename, boolean isTypescript);
     public static native String nativeGetVersion();
 }
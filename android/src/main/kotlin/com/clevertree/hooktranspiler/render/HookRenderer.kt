@@ -1344,6 +1344,13 @@ class HookRenderer @JvmOverloads constructor(
                             console.warn('[JSX] jsxs() not yet initialized');
                             return null;
                         },
+                        // Elements with a key after a spread, or spread children
+                        createElement: function() {
+                            if (globalObj.Act && globalObj.Act.createElement) return globalObj.Act.createElement.apply(globalObj.Act, arguments);
+                            if (globalObj.React && globalObj.React.createElement) return globalObj.React.createElement.apply(globalObj.React, arguments);
+                            console.warn('[JSX] createElement() not yet initialized');
+                            return null;
+                        },
                         Fragment: 'Fragment'
                     };
                     
@@ -1399,6 +1406,9 @@ class HookRenderer @JvmOverloads constructor(
                     globalThis.__hook_jsx_runtime = {
                         jsx: jsxWrapper,
                         jsxs: jsxWrapper,
+                        createElement: function() {
+                            return ActRuntime.createElement.apply(ActRuntime, arguments);
+                        },
                         Fragment: ActRuntime.Fragment
                     };
                     globalThis.__jsx = jsxWrapper;
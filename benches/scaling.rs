@@ -0,0 +1,98 @@
+//! Transpile time against input size for generated hooks of 0.5 to 4 MB.
+//!
+//! Run with `cargo bench --bench scaling`. Every pass is meant to be linear, so the time
+//! per megabyte should stay roughly flat as the input grows; a pass that rescans or
+//! re-allocates per nested expression shows up as time per megabyte growing with size,
+//! and the run fails once it doubles.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions, TranspileTarget};
+
+/// One component exercising the constructs the passes handle: nested `{expr}` with JSX
+/// inside maps and conditionals, template literals, regex literals, dynamic imports,
+/// destructuring that needs temporaries on Android and (in TypeScript mode) type
+/// annotations and generics
+fn component(i: usize, typescript: bool) -> String {
+    let (props, state, item) = if typescript {
+        (": { items: Item[]; title?: string }", "<number | null>", ": Item")
+    } else {
+        ("", "", "")
+    };
+    format!(
+        r#"
+export function List{i}({{ items, title = "List {i}" }}{props}) {{
+  const [selected, setSelected] = useState{state}(null);
+  const {{ id, tags: [first] }} = items[0] || {{}};
+  const load = async () => (await import('./detail-{i}.jsx')).default;
+  const label = `${{title}}: ${{items.length}} ${{items.length === 1 ? "item" : "items"}}`;
+  const valid = /^[a-z]+\/\d+$/i.test(title) && items.length < 100;
+  return (
+    <section className="list" data-index={{{i}}}>
+      <h2 title={{label}}>{{title}} &amp; more</h2>
+      {{valid ? <p>{{label}}</p> : null}}
+      <ul>
+        {{items.map((item{item}) => (
+          <li key={{item.id}} onClick={{() => setSelected(item.id)}}>
+            {{item.tags.map(tag => <span className={{tag === selected ? "on" : "off"}}>{{tag}}</span>)}}
+            {{selected === item.id && <Detail load={{load}} item={{{{ ...item, index: {i} }}}} />}}
+          </li>
+        ))}}
+      </ul>
+    </section>
+  );
+}}
+"#
+    )
+}
+
+/// A hook of at least `bytes` bytes
+fn corpus(bytes: usize, typescript: bool) -> String {
+    let mut source = String::from("import { useState } from 'react';\n");
+    if typescript {
+        source.push_str("interface Item { id: number; tags: string[] }\n");
+    }
+    let mut i = 0;
+    while source.len() < bytes {
+        source.push_str(&component(i, typescript));
+        i += 1;
+    }
+    source
+}
+
+/// Largest growth of time per megabyte over the smallest input that still counts as linear
+const MAX_GROWTH: f64 = 2.0;
+
+/// Best of three runs
+fn time(source: &str, opts: &TranspileOptions) -> Duration {
+    (0..3)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(transpile_jsx_with_options(black_box(source), opts).expect("corpus transpiles"));
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let configs = [
+        ("web jsx", false, TranspileTarget::Web),
+        ("web tsx", true, TranspileTarget::Web),
+        ("android jsx", false, TranspileTarget::Android),
+    ];
+    for (name, typescript, target) in configs {
+        let opts = TranspileOptions { is_typescript: typescript, target, ..Default::default() };
+        println!("{name}");
+        let mut first = None;
+        for megabytes in [0.5, 1.0, 2.0, 4.0] {
+            let source = corpus((megabytes * 1024.0 * 1024.0) as usize, typescript);
+            let elapsed = time(&source, &opts);
+            let per_mb = elapsed.as_secs_f64() * 1000.0 / megabytes;
+            let ratio = per_mb / *first.get_or_insert(per_mb);
+            println!("  {megabytes:>4} MB  {:>9.1} ms  {per_mb:>7.1} ms/MB  x{ratio:.2}", elapsed.as_secs_f64() * 1000.0);
+            assert!(ratio < MAX_GROWTH, "{name}: time per MB grew x{ratio:.2} at {megabytes} MB");
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{TranspileOptions, transpile_jsx_with_options, version, DebugLevel};
+use crate::{TranspileOptions, compile_ui_tree_json, parse_module_json, transpile_jsx_with_options, version, DebugLevel};
 use jni::JNIEnv;
 use jni::objects::{JClass, JString};
 use jni::sys::{jstring, jboolean, jint};
@@ -20,14 +20,14 @@ unsafe extern "C" {
     fn __android_log_print(prio: i32, tag: *const libc::c_char, fmt: *const libc::c_char, ...);
 }
 
+/// Decode a Java string. JNI hands over modified UTF-8, where characters outside the BMP
+/// (emoji, rare CJK) arrive as two encoded surrogates, so it must be decoded as such rather
+/// than validated as plain UTF-8.
 fn jstring_to_string(env: &mut JNIEnv, input: JString) -> Option<String> {
     if input.is_null() {
         return None;
     }
-    match env.get_string(&input) {
-        Ok(jni_str) => jni_str.to_str().ok().map(|s| s.to_string()),
-        Err(_) => None,
-    }
+    env.get_string(&input).ok().map(String::from)
 }
 
 fn new_jstring(env: &mut JNIEnv, value: &str) -> jstring {
@@ -145,7 +145,8 @@ pub extern "system" fn Java_com_relay_pure_RustTranspilerModule_nativeTranspile(
             new_jstring(&mut env, &output)
         },
         Err(err) => {
-            let msg = format!("{}", err);
+            // Include the code frame so the Android error overlay can show the failing line
+            let msg = err.render();
             android_logger(format!("nativeTranspile ERROR: {}", msg));
             let _ = env.throw_new("java/lang/RuntimeException", msg);
             std::ptr::null_mut()
@@ -153,6 +154,115 @@ pub extern "system" fn Java_com_relay_pure_RustTranspilerModule_nativeTranspile(
     }
 }
 
+/// Parse a hook into its typed AST, returned as JSON so the host can analyze it without re-parsing
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_relay_client_RustTranspilerModule_nativeParseModule(
+    env: JNIEnv,
+    class: JClass,
+    code: JString,
+    filename: JString,
+    is_typescript: jboolean,
+) -> jstring {
+    Java_com_relay_pure_RustTranspilerModule_nativeParseModule(env, class, code, filename, is_typescript)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_relay_pure_RustTranspilerModule_nativeParseModule(
+    mut env: JNIEnv,
+    _class: JClass,
+    code: JString,
+    filename: JString,
+    is_typescript: jboolean,
+) -> jstring {
+    let Some(source) = jstring_to_string(&mut env, code) else {
+        let _ = env.throw_new(
+            "java/lang/IllegalArgumentException",
+            "code was null or malformed",
+        );
+        return std::ptr::null_mut();
+    };
+    let filename = jstring_to_string(&mut env, filename).unwrap_or_else(|| "module.tsx".to_string());
+
+    let debug_level = ANDROID_DEBUG_LEVEL.with(|dl| {
+        dl.lock()
+            .map(|level| *level)
+            .unwrap_or(DebugLevel::default())
+    });
+
+    let opts = TranspileOptions {
+        is_typescript: is_typescript != 0,
+        target: crate::TranspileTarget::Android,
+        filename: Some(filename),
+        debug_level,
+        ..Default::default()
+    };
+
+    match parse_module_json(&source, &opts) {
+        Ok(json) => new_jstring(&mut env, &json),
+        Err(err) => {
+            let msg = err.render();
+            android_logger(format!("nativeParseModule ERROR: {}", msg));
+            let _ = env.throw_new("java/lang/RuntimeException", msg);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Compile a hook's JSX to element trees, returned as JSON so the renderer can inflate static
+/// markup without evaluating it
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_relay_client_RustTranspilerModule_nativeCompileUiTree(
+    env: JNIEnv,
+    class: JClass,
+    code: JString,
+    filename: JString,
+    is_typescript: jboolean,
+) -> jstring {
+    Java_com_relay_pure_RustTranspilerModule_nativeCompileUiTree(env, class, code, filename, is_typescript)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_relay_pure_RustTranspilerModule_nativeCompileUiTree(
+    mut env: JNIEnv,
+    _class: JClass,
+    code: JString,
+    filename: JString,
+    is_typescript: jboolean,
+) -> jstring {
+    let Some(source) = jstring_to_string(&mut env, code) else {
+        let _ = env.throw_new(
+            "java/lang/IllegalArgumentException",
+            "code was null or malformed",
+        );
+        return std::ptr::null_mut();
+    };
+    let filename = jstring_to_string(&mut env, filename).unwrap_or_else(|| "module.tsx".to_string());
+
+    let debug_level = ANDROID_DEBUG_LEVEL.with(|dl| {
+        dl.lock()
+            .map(|level| *level)
+            .unwrap_or(DebugLevel::default())
+    });
+
+    let opts = TranspileOptions {
+        is_typescript: is_typescript != 0,
+        target: crate::TranspileTarget::Android,
+        filename: Some(filename),
+        debug_level,
+        ..Default::default()
+    };
+
+    match compile_ui_tree_json(&source, &opts) {
+        Ok(json) => new_jstring(&mut env, &json),
+        Err(err) => {
+            let msg = err.render();
+            android_logger(format!("nativeCompileUiTree ERROR: {}", msg));
+            let _ = env.throw_new("java/lang/RuntimeException", msg);
+            std::ptr::null_mut()
+        }
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_com_relay_client_RustTranspilerModule_nativeGetVersion(
     env: JNIEnv,
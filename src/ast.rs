@@ -0,0 +1,330 @@
+//! Typed syntax tree for a hook module, produced by [`crate::parse_module`].
+//!
+//! The tree models what hosts need to analyze a hook without re-parsing it: import and
+//! export declarations and every JSX element with its attributes and children. Other
+//! JavaScript stays source text inside [`Expression`] nodes, with any JSX nested in it
+//! parsed as well. Every node carries a [`Span`] into the original source; in TypeScript
+//! mode expression text is the code after type syntax was removed.
+//!
+//! With the `ast-json` feature every node serializes with serde, enums tagged by `"type"`.
+
+use std::fmt;
+use std::ops::Range;
+
+#[cfg(feature = "ast-json")]
+use serde::Serialize;
+
+use crate::error::Span;
+use crate::lexer::{self, TokenKind};
+
+/// A parsed source file
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub imports: Vec<ImportDeclaration>,
+    pub exports: Vec<ExportDeclaration>,
+    /// Outermost JSX elements and fragments in source order; JSX inside their expression
+    /// containers hangs off those nodes
+    pub jsx: Vec<JsxNode>,
+    pub span: Span,
+}
+
+/// `import … from "source"` (or a bare `import "source"`)
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDeclaration {
+    pub source: String,
+    pub specifiers: Vec<ImportSpecifier>,
+    /// `import type { … }` (TypeScript)
+    pub type_only: bool,
+    pub span: Span,
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportSpecifier {
+    /// `import React from "react"`
+    Default { local: String },
+    /// `import * as utils from "./utils"`
+    Namespace { local: String },
+    /// `import { useState }` or `import { useState as useLocalState }`
+    Named { imported: String, local: String },
+}
+
+/// An `export` statement. Spans cover the export clause up to the last exported name;
+/// initializers and function bodies are not part of the declaration node.
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportDeclaration {
+    /// `export default …`; `name` is set for `export default function Name` / `class Name`
+    Default { name: Option<String>, span: Span },
+    /// `export const a = …, b = …`, `export function f`, `export class C`
+    Declaration { kind: String, names: Vec<String>, span: Span },
+    /// `export { a, b as c }`, optionally re-exported `from "source"`
+    Named { specifiers: Vec<ExportSpecifier>, source: Option<String>, span: Span },
+    /// `export * from "source"` or `export * as ns from "source"`
+    All { exported: Option<String>, source: String, span: Span },
+}
+
+impl ExportDeclaration {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Default { span, .. }
+            | Self::Declaration { span, .. }
+            | Self::Named { span, .. }
+            | Self::All { span, .. } => *span,
+        }
+    }
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSpecifier {
+    pub local: String,
+    pub exported: String,
+}
+
+/// A JSX element or fragment
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsxNode {
+    Element(JsxElement),
+    Fragment(JsxFragment),
+}
+
+impl JsxNode {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Element(element) => element.span,
+            Self::Fragment(fragment) => fragment.span,
+        }
+    }
+
+    /// Whether the node's type, props and children are all compile-time constants
+    pub fn is_static(&self) -> bool {
+        match self {
+            Self::Element(element) => element.is_static(),
+            Self::Fragment(fragment) => fragment.children.iter().all(JsxChild::is_static),
+        }
+    }
+}
+
+/// `<name …attributes>children</name>` or `<name … />`
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsxElement {
+    pub name: JsxElementName,
+    pub attributes: Vec<JsxAttributeItem>,
+    pub children: Vec<JsxChild>,
+    pub self_closing: bool,
+    pub span: Span,
+}
+
+impl JsxElement {
+    /// Whether the element's type, props and children are all compile-time constants: an
+    /// intrinsic tag with literal props, no spreads and no `ref`, whose children are static
+    pub fn is_static(&self) -> bool {
+        let intrinsic = match &self.name {
+            JsxElementName::Identifier { name } => {
+                name.starts_with(|c: char| c.is_ascii_lowercase()) || name.contains('-')
+            }
+            JsxElementName::Namespaced { .. } => true,
+            JsxElementName::Member { .. } => false,
+        };
+        intrinsic
+            && self.attributes.iter().all(|item| match item {
+                JsxAttributeItem::Attribute(attr) => {
+                    attr.name != "ref"
+                        && match &attr.value {
+                            None | Some(JsxAttributeValue::String { .. }) => true,
+                            Some(JsxAttributeValue::ExpressionContainer(container)) => container.is_static(),
+                            Some(JsxAttributeValue::Element(node)) => node.is_static(),
+                        }
+                }
+                JsxAttributeItem::Spread(_) => false,
+            })
+            && self.children.iter().all(JsxChild::is_static)
+    }
+}
+
+/// `<>children</>`
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsxFragment {
+    pub children: Vec<JsxChild>,
+    pub span: Span,
+}
+
+/// Tag name of an element
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsxElementName {
+    /// `div`, `Button`, `my-element`
+    Identifier { name: String },
+    /// `svg:rect`
+    Namespaced { namespace: String, name: String },
+    /// `Icons.Arrow.Left`
+    Member { parts: Vec<String> },
+}
+
+impl JsxElementName {
+    /// Classify a tag name as written in the source
+    pub fn parse(name: &str) -> Self {
+        if let Some((namespace, local)) = name.split_once(':') {
+            Self::Namespaced { namespace: namespace.to_string(), name: local.to_string() }
+        } else if name.contains('.') {
+            Self::Member { parts: name.split('.').map(str::to_string).collect() }
+        } else {
+            Self::Identifier { name: name.to_string() }
+        }
+    }
+}
+
+impl fmt::Display for JsxElementName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Identifier { name } => f.write_str(name),
+            Self::Namespaced { namespace, name } => write!(f, "{}:{}", namespace, name),
+            Self::Member { parts } => f.write_str(&parts.join(".")),
+        }
+    }
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsxAttributeItem {
+    Attribute(JsxAttribute),
+    /// `{...props}`
+    Spread(JsxSpreadAttribute),
+}
+
+/// `name`, `name="value"`, `name={expr}` or `name=<Element />`
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsxAttribute {
+    /// As written, including any namespace (`xlink:href`)
+    pub name: String,
+    /// `None` for a boolean attribute such as `disabled`
+    pub value: Option<JsxAttributeValue>,
+    pub span: Span,
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsxAttributeValue {
+    /// A quoted string with character references decoded
+    String { value: String, span: Span },
+    ExpressionContainer(JsxExpressionContainer),
+    Element(Box<JsxNode>),
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsxSpreadAttribute {
+    pub argument: Expression,
+    pub span: Span,
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsxChild {
+    Text(JsxText),
+    ExpressionContainer(JsxExpressionContainer),
+    /// `{...items}`
+    Spread(JsxSpreadChild),
+    Element(JsxElement),
+    Fragment(JsxFragment),
+}
+
+impl JsxChild {
+    /// Whether the child is a compile-time constant
+    pub fn is_static(&self) -> bool {
+        match self {
+            Self::Text(_) => true,
+            Self::ExpressionContainer(container) => container.is_static(),
+            Self::Spread(_) => false,
+            Self::Element(element) => element.is_static(),
+            Self::Fragment(fragment) => fragment.children.iter().all(Self::is_static),
+        }
+    }
+}
+
+/// Text between tags
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsxText {
+    /// The string React receives: entities decoded and JSX whitespace rules applied.
+    /// Empty for whitespace-only text, which produces no child.
+    pub value: String,
+    pub raw: String,
+    pub span: Span,
+}
+
+/// `{expr}`; `expression` is `None` for `{}` and `{/* comment */}`
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsxExpressionContainer {
+    pub expression: Option<Expression>,
+    pub span: Span,
+}
+
+impl JsxExpressionContainer {
+    /// Whether the container is empty or holds a literal
+    pub fn is_static(&self) -> bool {
+        self.expression.as_ref().is_none_or(Expression::is_literal)
+    }
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsxSpreadChild {
+    pub expression: Expression,
+    pub span: Span,
+}
+
+/// JavaScript kept as source text, with the JSX inside it parsed
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    pub source: String,
+    /// Byte offset of `source` in the parsed (TypeScript-stripped) text
+    #[cfg_attr(feature = "ast-json", serde(skip))]
+    pub(crate) offset: usize,
+    /// Outermost JSX in `source`, in order
+    pub jsx: Vec<JsxNode>,
+    pub span: Span,
+    /// Byte ranges in `source` that code generation replaces: the index of the `jsx` node
+    /// parsed there, or `None` for JSX that failed to parse in recovery mode
+    #[cfg_attr(feature = "ast-json", serde(skip))]
+    pub(crate) holes: Vec<(Range<usize>, Option<usize>)>,
+}
+
+impl Expression {
+    /// Whether the expression is a single literal: a string, a template without
+    /// interpolations, a possibly negated number, `true`, `false` or `null`
+    pub fn is_literal(&self) -> bool {
+        let source = self.source.as_str();
+        let mut tokens = lexer::Lexer::new(source, 0, source.len()).filter(|token| !token.is_trivia());
+        let mut token = tokens.next();
+        if token.is_some_and(|t| t.is_punct(source, '-')) {
+            token = tokens.next().filter(|t| t.kind == TokenKind::Number);
+        }
+        let Some(token) = token else {
+            return false;
+        };
+        let literal = match token.kind {
+            TokenKind::String | TokenKind::Number => true,
+            TokenKind::Template => lexer::template_interpolations(source, &token).is_empty(),
+            TokenKind::Identifier => matches!(token.text(source), "true" | "false" | "null"),
+            _ => false,
+        };
+        literal && tokens.next().is_none()
+    }
+}
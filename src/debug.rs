@@ -1,6 +1,8 @@
 use std::sync::{Arc, Mutex};
 use std::fmt;
 
+use crate::position::Position;
+
 /// Debug verbosity levels
 #[cfg_attr(feature = "wasm", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -61,8 +63,8 @@ impl std::str::FromStr for DebugLevel {
 pub struct DebugEntry {
     pub level: DebugLevel,
     pub message: String,
-    pub line: Option<usize>,
-    pub column: Option<usize>,
+    /// Where in the source the entry applies, in bytes, chars and UTF-16 alike
+    pub position: Option<Position>,
 }
 
 /// Debug context for a transpilation session
@@ -84,16 +86,15 @@ impl DebugContext {
     }
 
     pub fn log(&self, entry_level: DebugLevel, message: impl Into<String>) {
-        self.log_at(entry_level, message, None, None);
+        self.log_at(entry_level, message, None);
     }
 
-    pub fn log_at(&self, entry_level: DebugLevel, message: impl Into<String>, line: Option<usize>, column: Option<usize>) {
+    pub fn log_at(&self, entry_level: DebugLevel, message: impl Into<String>, position: Option<Position>) {
         if entry_level <= self.level {
             let entry = DebugEntry {
                 level: entry_level,
                 message: message.into(),
-                line,
-                column,
+                position,
             };
             
             if let Ok(mut logs) = self.logs.lock() {
@@ -138,10 +139,9 @@ impl DebugContext {
         let logs = self.get_logs();
         logs.iter()
             .map(|entry| {
-                let location = match (entry.line, entry.column) {
-                    (Some(line), Some(col)) => format!(" [{}:{}]", line, col),
-                    (Some(line), None) => format!(" [line {}]", line),
-                    _ => String::new(),
+                let location = match entry.position {
+                    Some(position) => format!(" [{}:{}]", position.line, position.column),
+                    None => String::new(),
                 };
                 format!("[{}]{}: {}", entry.level, location, entry.message)
             })
@@ -199,10 +199,11 @@ mod tests {
     #[test]
     fn test_debug_entry_with_position() {
         let ctx = DebugContext::new(DebugLevel::Trace);
-        ctx.log_at(DebugLevel::Error, "syntax error", Some(42), Some(10));
+        let position = Position { line: 42, column: 10, utf16_column: 11, ..Default::default() };
+        ctx.log_at(DebugLevel::Error, "syntax error", Some(position));
 
         let logs = ctx.get_logs();
-        assert_eq!(logs[0].line, Some(42));
-        assert_eq!(logs[0].column, Some(10));
+        assert_eq!(logs[0].position, Some(position));
+        assert!(ctx.format_logs().contains("[42:10]"));
     }
 }
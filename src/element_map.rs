@@ -0,0 +1,169 @@
+//! Target-specific mapping of intrinsic JSX elements and their props.
+//!
+//! Intrinsic elements (`<div>`, `<img>`) reach the runtime as plain strings, which a native
+//! renderer may spell differently or not support at all. An [`ElementMap`] renames tags and
+//! props for the target and flags what its renderer cannot display, so parity problems show
+//! up as warnings at compile time instead of blank views on the device. Both the custom code
+//! generator and the SWC pipeline apply it.
+//!
+//! Tables are JSON, e.g.
+//!
+//! ```json
+//! {
+//!   "elements": {
+//!     "div": { "name": "View" },
+//!     "img": { "name": "Image", "props": { "src": "source" } },
+//!     "video": { "supported": false }
+//!   },
+//!   "props": { "onClick": "onPress" },
+//!   "events": ["onClick", "onChange"],
+//!   "unsupported_props": ["dangerouslySetInnerHTML"]
+//! }
+//! ```
+//!
+//! Checks use the names written in the source; renames are applied afterwards.
+
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use serde::Deserialize;
+
+use crate::error::{ErrorCode, TranspileError};
+
+/// Element and prop table for one renderer
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ElementMap {
+    /// Rules for individual intrinsic tags, keyed by the tag written in the source
+    pub elements: BTreeMap<String, ElementRule>,
+    /// Prop renames for every intrinsic element; an element's own `props` take precedence
+    pub props: BTreeMap<String, String>,
+    /// Event props (`onX`) the renderer dispatches; `None` accepts every event
+    pub events: Option<Vec<String>>,
+    /// Props the renderer ignores on every intrinsic element
+    pub unsupported_props: Vec<String>,
+    /// Report intrinsic tags without an entry in `elements` as unsupported
+    pub only_listed_elements: bool,
+}
+
+/// How one intrinsic tag is rendered
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ElementRule {
+    /// Tag the renderer expects instead, emitted as a string whatever its case
+    pub name: Option<String>,
+    /// Prop renames for this tag only
+    pub props: BTreeMap<String, String>,
+    /// Whether the renderer can display the tag at all
+    pub supported: bool,
+}
+
+impl Default for ElementRule {
+    fn default() -> Self {
+        Self { name: None, props: BTreeMap::new(), supported: true }
+    }
+}
+
+/// What the Android `NativeRenderer` displays. It creates views for tags case-insensitively
+/// and reads `src`, `className` and friends as written, so nothing is renamed; the table
+/// only reports media and embedded content it cannot show and events it never dispatches.
+static ANDROID: LazyLock<ElementMap> = LazyLock::new(|| {
+    ElementMap::from_json(include_str!("element_maps/android.json")).expect("built-in Android element map is valid")
+});
+
+impl ElementMap {
+    /// Parse a table from JSON
+    pub fn from_json(json: &str) -> Result<Self, TranspileError> {
+        serde_json::from_str(json).map_err(|e| {
+            TranspileError::new(ErrorCode::InvalidElementMap, format!("Invalid element map: {}", e))
+        })
+    }
+
+    /// The built-in table for the Android native renderer
+    pub fn android() -> &'static Self {
+        &ANDROID
+    }
+
+    /// The tag the renderer expects for the intrinsic `tag`
+    pub fn tag<'a>(&'a self, tag: &'a str) -> &'a str {
+        self.elements.get(tag).and_then(|rule| rule.name.as_deref()).unwrap_or(tag)
+    }
+
+    /// The prop the renderer expects for `prop` on the intrinsic `tag`
+    pub fn prop<'a>(&'a self, tag: &str, prop: &'a str) -> &'a str {
+        self.elements
+            .get(tag)
+            .and_then(|rule| rule.props.get(prop))
+            .or_else(|| self.props.get(prop))
+            .map_or(prop, String::as_str)
+    }
+
+    /// Why the renderer cannot display the intrinsic `tag`, if it cannot
+    pub fn unsupported_tag(&self, tag: &str) -> Option<String> {
+        match self.elements.get(tag) {
+            Some(rule) if !rule.supported => Some(format!("<{}> is not supported by the target renderer", tag)),
+            None if self.only_listed_elements => Some(format!("<{}> is not supported by the target renderer", tag)),
+            _ => None,
+        }
+    }
+
+    /// Why the renderer ignores `prop` on the intrinsic `tag`, if it does
+    pub fn unsupported_prop(&self, tag: &str, prop: &str) -> Option<String> {
+        if self.unsupported_props.iter().any(|p| p == prop) {
+            return Some(format!("'{}' on <{}> is not supported by the target renderer", prop, tag));
+        }
+        let is_event = prop.strip_prefix("on").is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()));
+        match &self.events {
+            Some(events) if is_event && !events.iter().any(|e| e == prop) => {
+                Some(format!("Event '{}' on <{}> is never dispatched by the target renderer", prop, tag))
+            }
+            _ => None,
+        }
+    }
+
+    /// Warnings for the intrinsic `tag` written at byte offset `start`, with each of its
+    /// `(prop, byte offset)` pairs
+    pub(crate) fn check(&self, tag: &str, start: usize, props: &[(&str, usize)]) -> Vec<TranspileError> {
+        let tag_warning = self
+            .unsupported_tag(tag)
+            .map(|msg| TranspileError::new(ErrorCode::UnsupportedElement, msg).at_offsets(start, start + tag.len()));
+        let prop_warnings = props.iter().filter_map(|&(prop, at)| {
+            self.unsupported_prop(tag, prop)
+                .map(|msg| TranspileError::new(ErrorCode::UnsupportedProp, msg).at_offsets(at, at + prop.len()))
+        });
+        tag_warning.into_iter().chain(prop_warnings).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_android_table_parses() {
+        let android = ElementMap::android();
+        assert!(android.unsupported_tag("video").is_some());
+        assert_eq!(android.unsupported_tag("div"), None);
+        assert_eq!(android.unsupported_prop("button", "onClick"), None);
+        assert!(android.unsupported_prop("div", "onMouseEnter").is_some());
+        assert_eq!(android.tag("img"), "img");
+    }
+
+    #[test]
+    fn element_props_take_precedence() {
+        let map = ElementMap::from_json(
+            r#"{ "elements": { "img": { "props": { "src": "source", "alt": "label" } } }, "props": { "alt": "title" } }"#,
+        )
+        .unwrap();
+        assert_eq!(map.prop("img", "src"), "source");
+        assert_eq!(map.prop("img", "alt"), "label");
+        assert_eq!(map.prop("area", "alt"), "title");
+        assert_eq!(map.prop("area", "src"), "src");
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let err = ElementMap::from_json(r#"{ "element": {} }"#).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidElementMap);
+    }
+}
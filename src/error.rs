@@ -0,0 +1,458 @@
+//! Structured transpiler errors shared by the custom JSX parser and the SWC pipeline.
+//!
+//! Every public entry point reports failures as a [`TranspileError`] carrying a stable
+//! error code, a byte/line/column span into the original source and a rendered code frame,
+//! so the web and Android error overlays can point at the offending line.
+
+use std::fmt;
+
+use crate::position::{LineIndex, Position};
+
+#[cfg(any(feature = "wasm", feature = "ast-json"))]
+use serde::{Deserialize, Serialize};
+
+/// Broad category of a transpile failure
+#[cfg_attr(feature = "wasm", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Malformed JSX or JavaScript syntax
+    Syntax,
+    /// TypeScript-only syntax found in a JavaScript file
+    TypeScript,
+    /// A post-parse transform (downleveling, module rewriting) failed
+    Transform,
+    /// The target renderer cannot display the JSX, or its element map is invalid
+    Target,
+    /// The SWC pipeline rejected the input (native targets only)
+    Swc,
+    /// Unexpected internal failure
+    Internal,
+}
+
+/// Whether a diagnostic stops the transpile
+#[cfg_attr(feature = "wasm", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Severity {
+    #[default]
+    Error,
+    /// Reported alongside the output; never fails the transpile
+    Warning,
+}
+
+/// Stable, machine-readable error codes (`HTxxxx`)
+///
+/// Codes are never reused or renumbered so hosts can key documentation and quick fixes on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// A specific character was expected (e.g. `>` or `}`)
+    ExpectedChar,
+    /// A closing tag appeared where an element was expected
+    UnexpectedClosingTag,
+    /// Closing tag name does not match the open element
+    MismatchedClosingTag,
+    /// Input ended inside an element
+    UnexpectedEof,
+    /// An attribute had `=` but no string or `{expr}` value
+    ExpectedPropValue,
+    /// A character that cannot start an attribute appeared inside a tag
+    UnexpectedChar,
+    /// `&name;` in JSX text or an attribute string is not a known character reference
+    UnknownEntity,
+    /// The same prop was given more than once on one element
+    DuplicateProp,
+    /// HTML-compatibility mode rewrote pasted markup into JSX (e.g. `class` to `className`)
+    HtmlCompat,
+    /// `interface`, `enum`, `type X =` or an access modifier in a JavaScript file
+    TypeScriptSyntax,
+    /// A type annotation such as `: string` in a JavaScript file
+    TypeScriptAnnotation,
+    /// A generic parameter list such as `<T>` in a JavaScript file
+    TypeScriptGeneric,
+    /// SWC failed to parse the module
+    SwcParse,
+    /// SWC panicked while transforming the module
+    SwcPanic,
+    /// ES5 downleveling failed
+    Downlevel,
+    /// An intrinsic element the target renderer cannot display
+    UnsupportedElement,
+    /// A prop or event the target renderer ignores
+    UnsupportedProp,
+    /// An element map table could not be parsed
+    InvalidElementMap,
+    /// Any other failure
+    Internal,
+}
+
+impl ErrorCode {
+    /// The stable string form, e.g. `"HT1003"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ExpectedChar => "HT1001",
+            Self::UnexpectedClosingTag => "HT1002",
+            Self::MismatchedClosingTag => "HT1003",
+            Self::UnexpectedEof => "HT1004",
+            Self::ExpectedPropValue => "HT1005",
+            Self::UnexpectedChar => "HT1006",
+            Self::UnknownEntity => "HT1007",
+            Self::DuplicateProp => "HT1008",
+            Self::HtmlCompat => "HT1009",
+            Self::TypeScriptSyntax => "HT2001",
+            Self::TypeScriptAnnotation => "HT2002",
+            Self::TypeScriptGeneric => "HT2003",
+            Self::SwcParse => "HT3001",
+            Self::SwcPanic => "HT3002",
+            Self::Downlevel => "HT4001",
+            Self::UnsupportedElement => "HT5001",
+            Self::UnsupportedProp => "HT5002",
+            Self::InvalidElementMap => "HT5003",
+            Self::Internal => "HT9000",
+        }
+    }
+
+    /// The category this code belongs to
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::ExpectedChar
+            | Self::UnexpectedClosingTag
+            | Self::MismatchedClosingTag
+            | Self::UnexpectedEof
+            | Self::ExpectedPropValue
+            | Self::UnexpectedChar
+            | Self::UnknownEntity
+            | Self::DuplicateProp
+            | Self::HtmlCompat => ErrorKind::Syntax,
+            Self::TypeScriptSyntax | Self::TypeScriptAnnotation | Self::TypeScriptGeneric => {
+                ErrorKind::TypeScript
+            }
+            Self::SwcParse | Self::SwcPanic => ErrorKind::Swc,
+            Self::Downlevel => ErrorKind::Transform,
+            Self::UnsupportedElement | Self::UnsupportedProp | Self::InvalidElementMap => ErrorKind::Target,
+            Self::Internal => ErrorKind::Internal,
+        }
+    }
+
+    /// Default severity for diagnostics with this code
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::UnknownEntity
+            | Self::DuplicateProp
+            | Self::HtmlCompat
+            | Self::UnsupportedElement
+            | Self::UnsupportedProp => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl Serialize for ErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Location of an error in the original source
+///
+/// `start`/`end` are byte offsets; `line`/`column` are 1-based and count characters. The
+/// char and UTF-16 fields give the same range in the units the parser and JavaScript or
+/// Java hosts use (see [`Position`]).
+#[cfg_attr(any(feature = "wasm", feature = "ast-json"), derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+    pub utf16_start: usize,
+    pub utf16_end: usize,
+    pub utf16_column: usize,
+    pub end_utf16_column: usize,
+}
+
+impl Span {
+    /// Build a span from byte offsets, resolving the other units against `source`
+    pub fn from_offsets(source: &str, start: usize, end: usize) -> Self {
+        let index = LineIndex::new(source);
+        let start = index.position_at_byte(start);
+        Self::from_positions(start, index.position_at_byte(end.max(start.offset)))
+    }
+
+    pub fn from_positions(start: Position, end: Position) -> Self {
+        Self {
+            start: start.offset,
+            end: end.offset,
+            line: start.line,
+            column: start.column,
+            end_line: end.line,
+            end_column: end.column,
+            char_start: start.char_offset,
+            char_end: end.char_offset,
+            utf16_start: start.utf16_offset,
+            utf16_end: end.utf16_offset,
+            utf16_column: start.utf16_column,
+            end_utf16_column: end.utf16_column,
+        }
+    }
+
+    pub fn start_position(&self) -> Position {
+        Position {
+            offset: self.start,
+            char_offset: self.char_start,
+            utf16_offset: self.utf16_start,
+            line: self.line,
+            column: self.column,
+            utf16_column: self.utf16_column,
+        }
+    }
+
+    pub fn end_position(&self) -> Position {
+        Position {
+            offset: self.end,
+            char_offset: self.char_end,
+            utf16_offset: self.utf16_end,
+            line: self.end_line,
+            column: self.end_column,
+            utf16_column: self.end_utf16_column,
+        }
+    }
+}
+
+/// A transpile failure with a stable code, optional span and a rendered code frame
+#[cfg_attr(feature = "wasm", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranspileError {
+    pub kind: ErrorKind,
+    pub code: ErrorCode,
+    pub severity: Severity,
+    pub message: String,
+    pub filename: Option<String>,
+    /// Boxed so `Result<_, TranspileError>` stays small
+    pub span: Option<Box<Span>>,
+    /// Source excerpt with a caret under the error, present once the source is attached
+    pub code_frame: Option<String>,
+}
+
+impl TranspileError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            kind: code.kind(),
+            code,
+            severity: code.severity(),
+            message: message.into(),
+            filename: None,
+            span: None,
+            code_frame: None,
+        }
+    }
+
+    /// Record the byte range of the error; line/column are filled in by [`Self::with_source`]
+    pub fn at_offsets(mut self, start: usize, end: usize) -> Self {
+        self.span = Some(Box::new(Span { start, end: end.max(start), ..Default::default() }));
+        self
+    }
+
+    /// Move the span by `delta` bytes (used when an error came from a nested sub-parse)
+    pub fn shifted(mut self, delta: usize) -> Self {
+        if let Some(span) = self.span.as_mut() {
+            span.start += delta;
+            span.end += delta;
+        }
+        self
+    }
+
+    /// Carry a span computed against a rewritten copy of the source (e.g. after TypeScript
+    /// stripping, which preserves line breaks) back onto `original` by line and column
+    pub fn remapped(mut self, rewritten: &str, original: &str) -> Self {
+        if let Some(span) = self.span.as_mut() {
+            let resolved = Span::from_offsets(rewritten, span.start, span.end);
+            span.start = offset_at_line_col(original, resolved.line, resolved.column);
+            span.end = offset_at_line_col(original, resolved.end_line, resolved.end_column).max(span.start);
+        }
+        self
+    }
+
+    /// Resolve the span against the full source and render the code frame
+    pub fn with_source(mut self, source: &str, filename: Option<&str>) -> Self {
+        if self.filename.is_none() {
+            self.filename = filename.map(|f| f.to_string());
+        }
+        if let Some(span) = self.span.as_mut() {
+            **span = Span::from_offsets(source, span.start, span.end);
+            self.code_frame = Some(render_code_frame(source, span));
+        }
+        self
+    }
+
+    /// Convert an internal `anyhow` error, keeping structure when it wraps a `TranspileError`
+    pub fn from_anyhow(err: anyhow::Error, fallback: ErrorCode) -> Self {
+        match err.downcast::<TranspileError>() {
+            Ok(e) => e,
+            Err(other) => Self::new(fallback, other.to_string()),
+        }
+    }
+
+    /// Full diagnostic report: header, location and code frame
+    pub fn render(&self) -> String {
+        let label = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let mut out = format!("{}[{}]: {}", label, self.code, self.message);
+        let file = self.filename.as_deref().unwrap_or("<input>");
+        match self.span.as_deref() {
+            Some(span) if span.line > 0 => {
+                out.push_str(&format!("\n --> {}:{}:{}", file, span.line, span.column));
+            }
+            _ if self.filename.is_some() => {
+                out.push_str(&format!("\n --> {}", file));
+            }
+            _ => {}
+        }
+        if let Some(frame) = &self.code_frame {
+            out.push('\n');
+            out.push_str(frame);
+        }
+        out
+    }
+}
+
+impl fmt::Display for TranspileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span.as_deref() {
+            Some(span) if span.line > 0 => {
+                write!(f, "{} at line {}, column {}", self.message, span.line, span.column)
+            }
+            _ => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for TranspileError {}
+
+fn offset_at_line_col(source: &str, line: usize, column: usize) -> usize {
+    let line_start = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(|l| l.len())
+        .sum::<usize>();
+    let rest = &source[line_start.min(source.len())..];
+    let line_text = rest.split('\n').next().unwrap_or("");
+    let col_bytes = line_text
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(line_text.len());
+    line_start + col_bytes
+}
+
+/// Render the error line with one line of context on either side and a caret underline
+fn render_code_frame(source: &str, span: &Span) -> String {
+    let lines: Vec<&str> = source.split('\n').collect();
+    let first = span.line.saturating_sub(1).max(1);
+    let last = (span.line + 1).min(lines.len());
+    let gutter = last.to_string().len();
+
+    let mut out = String::new();
+    for line_no in first..=last {
+        let text = lines[line_no - 1].trim_end_matches('\r');
+        let marker = if line_no == span.line { '>' } else { ' ' };
+        out.push_str(&format!("{} {:>width$} | {}\n", marker, line_no, text, width = gutter));
+        if line_no == span.line {
+            let line_len = text.chars().count();
+            let caret_len = if span.end_line == span.line {
+                span.end_column.saturating_sub(span.column)
+            } else {
+                line_len.saturating_sub(span.column - 1)
+            }
+            .max(1);
+            out.push_str(&format!(
+                "  {:>width$} | {}{}\n",
+                "",
+                " ".repeat(span.column - 1),
+                "^".repeat(caret_len),
+                width = gutter
+            ));
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_resolves_line_and_column() {
+        let src = "const a = 1;\nconst b = <div>;\n";
+        let start = src.find("<div>").unwrap();
+        let span = Span::from_offsets(src, start, start + 5);
+        assert_eq!((span.line, span.column), (2, 11));
+        assert_eq!((span.end_line, span.end_column), (2, 16));
+    }
+
+    #[test]
+    fn test_span_counts_utf16_for_hosts() {
+        let src = "const s = '😀';
+const b = <div>;
+";
+        let start = src.find("<div>").unwrap();
+        let span = Span::from_offsets(src, start, start + 5);
+        assert_eq!((span.line, span.column, span.utf16_column), (2, 11, 11));
+        assert_eq!((span.char_start, span.utf16_start), (start - 3, start - 2));
+        let utf16: Vec<u16> = src.encode_utf16().collect();
+        assert_eq!(String::from_utf16(&utf16[span.utf16_start..span.utf16_end]).unwrap(), "<div>");
+
+        let span = Span::from_offsets(src, 11, src.find("';").unwrap() + 1);
+        assert_eq!((span.start, span.char_start, span.utf16_column, span.end_utf16_column), (11, 11, 12, 15));
+        assert_eq!(span.end_position().column, 14);
+        assert_eq!(Span::from_positions(span.start_position(), span.end_position()), span);
+    }
+
+    #[test]
+    fn test_code_frame_points_at_error() {
+        let src = "line one\nlet x = <b></i>;\nline three";
+        let start = src.find("</i>").unwrap();
+        let err = TranspileError::new(ErrorCode::MismatchedClosingTag, "Mismatched closing tag")
+            .at_offsets(start, start + 4)
+            .with_source(src, Some("hook.jsx"));
+
+        let frame = err.code_frame.as_deref().unwrap();
+        assert!(frame.contains("> 2 | let x = <b></i>;"));
+        assert!(frame.contains("|            ^^^^"));
+        assert!(err.render().contains("error[HT1003]"));
+        assert!(err.render().contains("--> hook.jsx:2:12"));
+        assert_eq!(err.to_string(), "Mismatched closing tag at line 2, column 12");
+    }
+
+    #[test]
+    fn test_shifted_span_and_kind() {
+        let err = TranspileError::new(ErrorCode::TypeScriptGeneric, "generic")
+            .at_offsets(2, 3)
+            .shifted(10);
+        assert_eq!(err.kind, ErrorKind::TypeScript);
+        assert_eq!(err.span.as_deref().unwrap().start, 12);
+    }
+
+    #[test]
+    fn test_from_anyhow_preserves_structure() {
+        let inner = TranspileError::new(ErrorCode::UnexpectedEof, "eof").at_offsets(1, 1);
+        let err = TranspileError::from_anyhow(anyhow::Error::new(inner.clone()), ErrorCode::Internal);
+        assert_eq!(err, inner);
+
+        let plain = TranspileError::from_anyhow(anyhow::anyhow!("boom"), ErrorCode::Downlevel);
+        assert_eq!(plain.code, ErrorCode::Downlevel);
+        assert_eq!(plain.message, "boom");
+    }
+}
@@ -32,6 +32,12 @@ pub unsafe extern "C" fn hook_transpile_jsx(
         inline_source_map: false,
         compat_for_jsc: true,
         debug_level: DebugLevel::default(),
+        jsx_runtime: Default::default(),
+        development: false,
+        hoist_static_jsx: false,
+        html_compat: false,
+        element_map: None,
+        jsx_backend: crate::JsxBackend::Runtime,
     };
 
     match transpile_jsx_with_options(code_str, &opts) {
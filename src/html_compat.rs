@@ -0,0 +1,216 @@
+//! Rewrites for HTML pasted into JSX (`TranspileOptions::html_compat`).
+//!
+//! HTML lets void elements such as `<br>` go unclosed and spells attributes the DOM way:
+//! `class`, `for`, all-lowercase names like `tabindex`, `style="color:red"` and string event
+//! handlers such as `onclick="save()"`. The parser applies these helpers to turn each of them
+//! into the JSX React expects, warning about every fix.
+
+use crate::jsx_codegen::escape_string;
+
+/// Elements that never have children, so HTML needs no closing tag for them
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// HTML attribute names whose React prop is spelled differently, sorted for lookup
+const ATTRIBUTE_NAMES: &[(&str, &str)] = &[
+    ("accept-charset", "acceptCharset"), ("accesskey", "accessKey"), ("allowfullscreen", "allowFullScreen"),
+    ("autocomplete", "autoComplete"), ("autofocus", "autoFocus"), ("autoplay", "autoPlay"),
+    ("cellpadding", "cellPadding"), ("cellspacing", "cellSpacing"), ("class", "className"),
+    ("colspan", "colSpan"), ("contenteditable", "contentEditable"), ("crossorigin", "crossOrigin"),
+    ("datetime", "dateTime"), ("enctype", "encType"), ("enterkeyhint", "enterKeyHint"),
+    ("for", "htmlFor"), ("formaction", "formAction"), ("frameborder", "frameBorder"),
+    ("http-equiv", "httpEquiv"), ("inputmode", "inputMode"), ("maxlength", "maxLength"),
+    ("minlength", "minLength"), ("novalidate", "noValidate"), ("playsinline", "playsInline"),
+    ("readonly", "readOnly"), ("referrerpolicy", "referrerPolicy"), ("rowspan", "rowSpan"),
+    ("spellcheck", "spellCheck"), ("srcset", "srcSet"), ("tabindex", "tabIndex"), ("usemap", "useMap"),
+];
+
+/// React props for SVG attributes (and a few others) that React DOM writes hyphenated or
+/// namespaced, sorted for lookup: `strokeWidth` renders as `stroke-width`
+const SVG_ATTRIBUTE_NAMES: &[(&str, &str)] = &[
+    ("alignmentBaseline", "alignment-baseline"), ("arabicForm", "arabic-form"),
+    ("baselineShift", "baseline-shift"), ("capHeight", "cap-height"), ("clipPath", "clip-path"),
+    ("clipRule", "clip-rule"), ("colorInterpolation", "color-interpolation"),
+    ("colorInterpolationFilters", "color-interpolation-filters"), ("colorProfile", "color-profile"),
+    ("colorRendering", "color-rendering"), ("dominantBaseline", "dominant-baseline"),
+    ("enableBackground", "enable-background"), ("fillOpacity", "fill-opacity"), ("fillRule", "fill-rule"),
+    ("floodColor", "flood-color"), ("floodOpacity", "flood-opacity"), ("fontFamily", "font-family"),
+    ("fontSize", "font-size"), ("fontSizeAdjust", "font-size-adjust"), ("fontStretch", "font-stretch"),
+    ("fontStyle", "font-style"), ("fontVariant", "font-variant"), ("fontWeight", "font-weight"),
+    ("glyphName", "glyph-name"), ("glyphOrientationHorizontal", "glyph-orientation-horizontal"),
+    ("glyphOrientationVertical", "glyph-orientation-vertical"), ("horizAdvX", "horiz-adv-x"),
+    ("horizOriginX", "horiz-origin-x"), ("imageRendering", "image-rendering"),
+    ("letterSpacing", "letter-spacing"), ("lightingColor", "lighting-color"), ("markerEnd", "marker-end"),
+    ("markerMid", "marker-mid"), ("markerStart", "marker-start"), ("overlinePosition", "overline-position"),
+    ("overlineThickness", "overline-thickness"), ("paintOrder", "paint-order"), ("panose1", "panose-1"),
+    ("pointerEvents", "pointer-events"), ("renderingIntent", "rendering-intent"),
+    ("shapeRendering", "shape-rendering"), ("stopColor", "stop-color"), ("stopOpacity", "stop-opacity"),
+    ("strikethroughPosition", "strikethrough-position"), ("strikethroughThickness", "strikethrough-thickness"),
+    ("strokeDasharray", "stroke-dasharray"), ("strokeDashoffset", "stroke-dashoffset"),
+    ("strokeLinecap", "stroke-linecap"), ("strokeLinejoin", "stroke-linejoin"),
+    ("strokeMiterlimit", "stroke-miterlimit"), ("strokeOpacity", "stroke-opacity"),
+    ("strokeWidth", "stroke-width"), ("textAnchor", "text-anchor"), ("textDecoration", "text-decoration"),
+    ("textRendering", "text-rendering"), ("transformOrigin", "transform-origin"),
+    ("underlinePosition", "underline-position"), ("underlineThickness", "underline-thickness"),
+    ("unicodeBidi", "unicode-bidi"), ("unicodeRange", "unicode-range"), ("unitsPerEm", "units-per-em"),
+    ("vAlphabetic", "v-alphabetic"), ("vHanging", "v-hanging"), ("vIdeographic", "v-ideographic"),
+    ("vMathematical", "v-mathematical"), ("vectorEffect", "vector-effect"), ("vertAdvY", "vert-adv-y"),
+    ("vertOriginX", "vert-origin-x"), ("vertOriginY", "vert-origin-y"), ("wordSpacing", "word-spacing"),
+    ("writingMode", "writing-mode"), ("xHeight", "x-height"), ("xlinkActuate", "xlink:actuate"),
+    ("xlinkArcrole", "xlink:arcrole"), ("xlinkHref", "xlink:href"), ("xlinkRole", "xlink:role"),
+    ("xlinkShow", "xlink:show"), ("xlinkTitle", "xlink:title"), ("xlinkType", "xlink:type"),
+    ("xmlBase", "xml:base"), ("xmlLang", "xml:lang"), ("xmlSpace", "xml:space"), ("xmlnsXlink", "xmlns:xlink"),
+];
+
+/// DOM event handler attributes and their React props, sorted for lookup
+const EVENT_NAMES: &[(&str, &str)] = &[
+    ("onblur", "onBlur"), ("onchange", "onChange"), ("onclick", "onClick"),
+    ("oncontextmenu", "onContextMenu"), ("oncopy", "onCopy"), ("oncut", "onCut"),
+    ("ondblclick", "onDoubleClick"), ("ondrag", "onDrag"), ("ondragend", "onDragEnd"),
+    ("ondragenter", "onDragEnter"), ("ondragleave", "onDragLeave"), ("ondragover", "onDragOver"),
+    ("ondragstart", "onDragStart"), ("ondrop", "onDrop"), ("onended", "onEnded"),
+    ("onerror", "onError"), ("onfocus", "onFocus"), ("oninput", "onInput"), ("oninvalid", "onInvalid"),
+    ("onkeydown", "onKeyDown"), ("onkeypress", "onKeyPress"), ("onkeyup", "onKeyUp"),
+    ("onload", "onLoad"), ("onmousedown", "onMouseDown"), ("onmouseenter", "onMouseEnter"),
+    ("onmouseleave", "onMouseLeave"), ("onmousemove", "onMouseMove"), ("onmouseout", "onMouseOut"),
+    ("onmouseover", "onMouseOver"), ("onmouseup", "onMouseUp"), ("onpaste", "onPaste"),
+    ("onpause", "onPause"), ("onplay", "onPlay"), ("onpointerdown", "onPointerDown"),
+    ("onpointermove", "onPointerMove"), ("onpointerup", "onPointerUp"), ("onreset", "onReset"),
+    ("onscroll", "onScroll"), ("onselect", "onSelect"), ("onsubmit", "onSubmit"),
+    ("ontoggle", "onToggle"), ("ontouchend", "onTouchEnd"), ("ontouchmove", "onTouchMove"),
+    ("ontouchstart", "onTouchStart"), ("onwheel", "onWheel"),
+];
+
+/// Whether `tag` is an HTML void element such as `br` or `input`
+pub(crate) fn is_void_element(tag: &str) -> bool {
+    VOID_ELEMENTS.contains(&tag)
+}
+
+/// The React prop for the HTML attribute `name`, if it is spelled differently
+pub(crate) fn react_attribute_name(name: &str) -> Option<&'static str> {
+    ATTRIBUTE_NAMES.binary_search_by(|(html, _)| html.cmp(&name)).ok().map(|i| ATTRIBUTE_NAMES[i].1)
+}
+
+/// The attribute React DOM writes for the prop `name`, if it is spelled differently
+/// (`className` renders as `class`, `strokeWidth` as `stroke-width`)
+pub(crate) fn html_attribute_name(name: &str) -> Option<&'static str> {
+    ATTRIBUTE_NAMES.iter().find(|(_, react)| *react == name).map(|(html, _)| *html).or_else(|| {
+        let svg = SVG_ATTRIBUTE_NAMES.binary_search_by(|(react, _)| react.cmp(&name)).ok()?;
+        Some(SVG_ATTRIBUTE_NAMES[svg].1)
+    })
+}
+
+/// The React prop for the DOM event handler attribute `name`, e.g. `onClick` for `onclick`
+pub(crate) fn react_event_name(name: &str) -> Option<&'static str> {
+    EVENT_NAMES.binary_search_by(|(html, _)| html.cmp(&name)).ok().map(|i| EVENT_NAMES[i].1)
+}
+
+/// A function running the inline handler `code`, which sees the event as `event` as it does
+/// in the browser
+pub(crate) fn event_handler_function(code: &str) -> String {
+    // Keep a trailing `//` comment from swallowing the closing brace
+    let end = if code.lines().last().is_some_and(|line| line.contains("//")) { "\n" } else { " " };
+    format!("function (event) {{ {}{}}}", code.trim(), end)
+}
+
+/// The style object literal for the inline CSS `css`, e.g. `{ color: "red", fontSize: "2em" }`
+/// for `color: red; font-size: 2em`. Declarations without a `:` are dropped, as browsers do.
+pub(crate) fn style_object(css: &str) -> String {
+    let fields: Vec<String> = css_declarations(css)
+        .into_iter()
+        .filter_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            let (property, value) = (property.trim(), value.trim());
+            if property.is_empty() {
+                return None;
+            }
+            Some(format!("{}: \"{}\"", style_key(property), escape_string(value)))
+        })
+        .collect();
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+    format!("{{ {} }}", fields.join(", "))
+}
+
+/// `css` split at the `;`s that are outside quotes and parentheses (`url(a;b)`)
+fn css_declarations(css: &str) -> Vec<&str> {
+    let mut declarations = Vec::new();
+    let (mut start, mut depth, mut quote) = (0, 0usize, None);
+    for (i, ch) in css.char_indices() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ';') if depth == 0 => {
+                declarations.push(&css[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    declarations.push(&css[start..]);
+    declarations
+}
+
+/// The style object key for a CSS property: camelCase as React expects (`font-size` is
+/// `fontSize`, `-webkit-transition` is `WebkitTransition`, `-ms-grid` is `msGrid`), with
+/// custom properties (`--accent`) kept verbatim as quoted keys
+fn style_key(property: &str) -> String {
+    if property.starts_with("--") {
+        return format!("\"{}\"", escape_string(property));
+    }
+    let property = property.to_ascii_lowercase();
+    let property = match property.strip_prefix("-ms-") {
+        Some(rest) => format!("ms-{}", rest),
+        None => property,
+    };
+    let mut key = String::with_capacity(property.len());
+    let mut upper = false;
+    for ch in property.chars() {
+        if ch == '-' {
+            upper = true;
+        } else if upper {
+            key.push(ch.to_ascii_uppercase());
+            upper = false;
+        } else {
+            key.push(ch);
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_are_sorted() {
+        assert!(ATTRIBUTE_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(SVG_ATTRIBUTE_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(EVENT_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn style_strings_become_objects() {
+        assert_eq!(
+            style_object("color:red; font-size: 2em;-webkit-line-clamp:2;-ms-grid-row:1"),
+            r#"{ color: "red", fontSize: "2em", WebkitLineClamp: "2", msGridRow: "1" }"#
+        );
+        assert_eq!(
+            style_object(r#"background: url("a;b.png"); --Accent: #0af; bogus; "#),
+            r##"{ background: "url(\"a;b.png\")", "--Accent": "#0af" }"##
+        );
+        assert_eq!(style_object(" ; "), "{}");
+    }
+
+    #[test]
+    fn inline_handlers_become_functions() {
+        assert_eq!(event_handler_function("save(event)"), "function (event) { save(event) }");
+        assert_eq!(event_handler_function("go() // later"), "function (event) { go() // later\n}");
+    }
+}
@@ -0,0 +1,319 @@
+//! HTML pre-rendering of JSX for the web, produced by [`crate::prerender_html`].
+//!
+//! Each outermost JSX element or fragment of a module is rendered to the markup React DOM
+//! would produce for it, so a page can show it (and crawlers index it) before the hook
+//! runs. Roots that are compile-time constants ([`JsxNode::is_static`]) render completely.
+//! In the others every part that needs code is left as a hole for hydration: a dynamic
+//! child or component becomes a `<!--hook:N-->` comment where its output belongs, and an
+//! element with dynamic props or spreads gets a `data-hook-holes="N …"` attribute. `N`
+//! indexes [`HtmlRoot::holes`], which keeps each hole's source.
+//! Like React DOM, a `<textarea>` renders its literal `value` as content and a `<select>`
+//! marks the `<option>` matching its literal `value` as `selected`.
+//!
+//! With the `ast-json` feature the result serializes with serde, enums tagged by `"type"`.
+
+#[cfg(feature = "ast-json")]
+use serde::Serialize;
+
+use crate::ast::{
+    Expression, JsxAttributeItem, JsxAttributeValue, JsxChild, JsxElement, JsxFragment, JsxNode, Module,
+};
+use crate::error::Span;
+use crate::html_compat::{html_attribute_name, is_void_element};
+use crate::jsx_codegen::is_intrinsic;
+use crate::ui_tree::{literal_value, number_text, UiValue};
+
+/// The rendered JSX roots of a module in source order
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PrerenderedHtml {
+    pub roots: Vec<HtmlRoot>,
+}
+
+/// One outermost JSX element or fragment as HTML
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlRoot {
+    pub html: String,
+    /// What the hook fills in on hydration, indexed by the markers in `html`
+    pub holes: Vec<HtmlHole>,
+    /// Whether the root is a compile-time constant, so `html` is complete and has no holes
+    pub is_static: bool,
+    pub span: Span,
+}
+
+/// A part of a root that needs the hook's code
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlHole {
+    pub kind: HtmlHoleKind,
+    /// The expression, or the JSX of a component, as written
+    pub source: String,
+    pub span: Span,
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum HtmlHoleKind {
+    /// A child expression or component, marked by `<!--hook:N-->`
+    Child,
+    /// The prop `name` of the element whose `data-hook-holes` lists the hole
+    Prop { name: String },
+    /// `{...props}` on the element whose `data-hook-holes` lists the hole
+    Spread,
+}
+
+impl PrerenderedHtml {
+    /// Render the JSX roots of `module`, parsed from `source`
+    pub fn from_module(module: &Module, source: &str) -> Self {
+        let roots = module
+            .jsx
+            .iter()
+            .map(|node| {
+                let mut renderer = Renderer { source, html: String::new(), holes: Vec::new(), selected: None };
+                renderer.node(node);
+                HtmlRoot { html: renderer.html, holes: renderer.holes, is_static: node.is_static(), span: node.span() }
+            })
+            .collect();
+        Self { roots }
+    }
+}
+
+/// Renders one root, collecting its holes
+struct Renderer<'a> {
+    source: &'a str,
+    html: String,
+    holes: Vec<HtmlHole>,
+    /// The literal value of the `<select>` being rendered, which marks its matching `<option>`
+    selected: Option<String>,
+}
+
+impl Renderer<'_> {
+    fn node(&mut self, node: &JsxNode) {
+        match node {
+            JsxNode::Element(element) => self.element(element),
+            JsxNode::Fragment(fragment) => self.fragment(fragment),
+        }
+    }
+
+    fn element(&mut self, element: &JsxElement) {
+        let tag = element.name.to_string();
+        if !is_intrinsic(&tag) {
+            let source = self.source.get(element.span.start..element.span.end).unwrap_or_default().to_string();
+            let hole = self.hole(HtmlHoleKind::Child, source, element.span);
+            self.marker(hole);
+            return;
+        }
+        self.html.push('<');
+        self.html.push_str(&tag);
+        // React DOM renders the value of a `<textarea>` as its text and marks the `<option>`
+        // of a `<select>` value as selected, rather than writing `value` attributes
+        let form_value = matches!(tag.as_str(), "textarea" | "select");
+        let mut value = None;
+        let mut prop_holes = Vec::new();
+        for item in &element.attributes {
+            let attr = match item {
+                JsxAttributeItem::Attribute(attr) => attr,
+                JsxAttributeItem::Spread(spread) => {
+                    prop_holes.push(self.expression_hole(HtmlHoleKind::Spread, &spread.argument));
+                    continue;
+                }
+            };
+            if attr.name == "key" {
+                continue;
+            }
+            let kind = || HtmlHoleKind::Prop { name: attr.name.clone() };
+            if form_value && matches!(attr.name.as_str(), "value" | "defaultValue") {
+                match &attr.value {
+                    Some(JsxAttributeValue::String { value: text, .. }) => value = Some(text.clone()),
+                    Some(JsxAttributeValue::ExpressionContainer(container)) => {
+                        let Some(expression) = &container.expression else { continue };
+                        match literal_value(expression).as_ref().and_then(text_value) {
+                            Some(text) => value = Some(text),
+                            None => prop_holes.push(self.expression_hole(kind(), expression)),
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            match &attr.value {
+                None => self.attribute(&attr.name, &UiValue::Boolean { value: true }),
+                Some(JsxAttributeValue::String { value, .. }) => {
+                    self.attribute(&attr.name, &UiValue::String { value: value.clone() })
+                }
+                Some(JsxAttributeValue::ExpressionContainer(container)) => {
+                    let Some(expression) = &container.expression else { continue };
+                    match literal_value(expression) {
+                        Some(value) if attr.name != "ref" => self.attribute(&attr.name, &value),
+                        _ => prop_holes.push(self.expression_hole(kind(), expression)),
+                    }
+                }
+                Some(JsxAttributeValue::Element(node)) => {
+                    let span = node.span();
+                    let source = self.source.get(span.start..span.end).unwrap_or_default().to_string();
+                    prop_holes.push(self.hole(kind(), source, span));
+                }
+            }
+        }
+        if tag == "option" && self.selected.is_some() && self.selected == option_value(element) {
+            self.html.push_str(" selected=\"\"");
+        }
+        if !prop_holes.is_empty() {
+            let holes: Vec<String> = prop_holes.iter().map(usize::to_string).collect();
+            self.html.push_str(&format!(" data-hook-holes=\"{}\"", holes.join(" ")));
+        }
+        if is_void_element(&tag) {
+            self.html.push_str("/>");
+            return;
+        }
+        self.html.push('>');
+        match tag.as_str() {
+            "textarea" if value.is_some() => self.html.push_str(&escape_html(&value.unwrap_or_default())),
+            "select" => {
+                let outer = std::mem::replace(&mut self.selected, value);
+                self.children(&element.children);
+                self.selected = outer;
+            }
+            _ => self.children(&element.children),
+        }
+        self.html.push_str(&format!("</{}>", tag));
+    }
+
+    fn fragment(&mut self, fragment: &JsxFragment) {
+        self.children(&fragment.children);
+    }
+
+    /// Render children, separating adjacent text with `<!-- -->` as React does so each
+    /// string hydrates into its own text node
+    fn children(&mut self, children: &[JsxChild]) {
+        let mut after_text = false;
+        for child in children {
+            let text = match child {
+                JsxChild::Text(text) if text.value.is_empty() => continue,
+                JsxChild::Text(text) => Some(text.value.clone()),
+                JsxChild::ExpressionContainer(container) => {
+                    let Some(expression) = &container.expression else { continue };
+                    match literal_value(expression) {
+                        Some(UiValue::String { value }) => Some(value),
+                        Some(UiValue::Number { value }) => Some(number_text(value)),
+                        Some(_) => continue,
+                        None => {
+                            let hole = self.expression_hole(HtmlHoleKind::Child, expression);
+                            self.marker(hole);
+                            None
+                        }
+                    }
+                }
+                JsxChild::Spread(spread) => {
+                    let hole = self.expression_hole(HtmlHoleKind::Child, &spread.expression);
+                    self.marker(hole);
+                    None
+                }
+                JsxChild::Element(element) => {
+                    self.element(element);
+                    None
+                }
+                JsxChild::Fragment(fragment) => {
+                    self.fragment(fragment);
+                    None
+                }
+            };
+            if let Some(text) = &text {
+                if after_text {
+                    self.html.push_str("<!-- -->");
+                }
+                self.html.push_str(&escape_html(text));
+            }
+            after_text = text.is_some();
+        }
+    }
+
+    /// ` name="value"` for a literal prop, or nothing for the values React leaves out
+    fn attribute(&mut self, name: &str, value: &UiValue) {
+        // Event handlers are attached on hydration, never written as attributes
+        if name.strip_prefix("on").is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase())) {
+            return;
+        }
+        // `data-*` and `aria-*` keep `true` and `false` as text; other props are boolean attributes
+        let stringified = name.starts_with("data-") || name.starts_with("aria-");
+        let value = match value {
+            UiValue::String { value } => value.clone(),
+            UiValue::Number { value } => number_text(*value),
+            UiValue::Boolean { value } if stringified => value.to_string(),
+            UiValue::Boolean { value: true } => String::new(),
+            _ => return,
+        };
+        // React DOM's spelling (`class`, `stroke-width`), or hydration reports a mismatch
+        let name = html_attribute_name(name).unwrap_or(name);
+        self.html.push_str(&format!(" {}=\"{}\"", name, escape_html(&value)));
+    }
+
+    fn marker(&mut self, hole: usize) {
+        self.html.push_str(&format!("<!--hook:{}-->", hole));
+    }
+
+    fn expression_hole(&mut self, kind: HtmlHoleKind, expression: &Expression) -> usize {
+        self.hole(kind, expression.source.clone(), expression.span)
+    }
+
+    fn hole(&mut self, kind: HtmlHoleKind, source: String, span: Span) -> usize {
+        self.holes.push(HtmlHole { kind, source, span });
+        self.holes.len() - 1
+    }
+}
+
+/// A string or number literal as the text it renders
+fn text_value(value: &UiValue) -> Option<String> {
+    match value {
+        UiValue::String { value } => Some(value.clone()),
+        UiValue::Number { value } => Some(number_text(*value)),
+        _ => None,
+    }
+}
+
+/// The value an `<option>` submits: its literal `value` prop, or else its literal text
+fn option_value(option: &JsxElement) -> Option<String> {
+    let prop = option.attributes.iter().find_map(|item| match item {
+        JsxAttributeItem::Attribute(attr) if attr.name == "value" => Some(attr),
+        _ => None,
+    });
+    if let Some(prop) = prop {
+        return match &prop.value {
+            Some(JsxAttributeValue::String { value, .. }) => Some(value.clone()),
+            Some(JsxAttributeValue::ExpressionContainer(container)) => {
+                container.expression.as_ref().and_then(literal_value).as_ref().and_then(text_value)
+            }
+            _ => None,
+        };
+    }
+    let mut text = String::new();
+    for child in &option.children {
+        match child {
+            JsxChild::Text(child) => text.push_str(&child.value),
+            JsxChild::ExpressionContainer(container) => {
+                let Some(expression) = &container.expression else { continue };
+                text.push_str(&text_value(&literal_value(expression)?)?);
+            }
+            _ => return None,
+        }
+    }
+    Some(text)
+}
+
+/// `text` escaped for HTML text and quoted attribute values, as React DOM escapes it
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,789 @@
+//! Code generation for parsed JSX.
+//!
+//! Turns the typed tree from `jsx_parser` into `jsx`/`jsxs`/`jsxDEV` calls for the automatic
+//! runtime or `pragma(type, props, ...children)` calls for the classic one, or with the DOM
+//! backend into code that builds the nodes directly, and splices them back into the
+//! surrounding JavaScript.
+
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+
+use crate::ast::{
+    Expression, JsxAttribute, JsxAttributeItem, JsxAttributeValue, JsxChild, JsxElement, JsxFragment, JsxNode,
+};
+use crate::element_map::ElementMap;
+use crate::error::{Span, TranspileError};
+use crate::html_compat::html_attribute_name;
+use crate::jsx_parser::SourceOrigin;
+use crate::source_map::MappedText;
+use crate::ui_tree::{UiValue, literal_value, number_text};
+use crate::{JsxBackend, JsxRuntime, TranspileOptions};
+
+pub(crate) struct Codegen<'a> {
+    runtime: &'a JsxRuntime,
+    /// File name for development output (`jsxDEV` / `__source`); `None` outside dev mode
+    dev_filename: Option<String>,
+    /// The file behind the parsed text, so output maps back to original positions
+    origin: &'a SourceOrigin,
+    /// Whether static elements and fragments become module-level constants
+    hoist: bool,
+    /// `const` declarations for the hoisted nodes, in the order they were generated
+    hoisted: RefCell<Vec<MappedText>>,
+    /// Set while generating a hoisted node, whose static descendants stay inline
+    in_hoisted: Cell<bool>,
+    /// Renames for intrinsic tags and their props on the target renderer
+    element_map: Option<&'a ElementMap>,
+    /// Tags and props the target renderer does not support
+    warnings: RefCell<Vec<TranspileError>>,
+    /// Build DOM nodes directly instead of calling the JSX runtime
+    dom: bool,
+    /// Set while generating the contents of an `<svg>`, whose elements need its namespace
+    in_svg: Cell<bool>,
+}
+
+/// Prefix of the module-level constants holding hoisted static JSX
+pub(crate) const HOISTED_JSX_PREFIX: &str = "__hook_static_jsx_";
+
+impl<'a> Codegen<'a> {
+    /// Code generator for `opts`, whose runtime should already have file pragmas applied
+    pub fn new(opts: &'a TranspileOptions, origin: &'a SourceOrigin) -> Self {
+        Self {
+            runtime: &opts.jsx_runtime,
+            dev_filename: opts
+                .development
+                .then(|| opts.filename.clone().unwrap_or_else(|| "hook.jsx".to_string())),
+            origin,
+            // Development output passes `this` and the element's own location, so it stays inline
+            hoist: opts.hoist_static_jsx && !opts.development,
+            hoisted: RefCell::default(),
+            in_hoisted: Cell::new(false),
+            element_map: opts.resolved_element_map(),
+            warnings: RefCell::default(),
+            dom: opts.jsx_backend == JsxBackend::Dom,
+            in_svg: Cell::new(false),
+        }
+    }
+
+    /// Warnings found while generating code, with spans in the original source
+    pub fn into_warnings(self) -> Vec<TranspileError> {
+        self.warnings.into_inner()
+    }
+
+    /// The whole parsed file: [`Self::splice`] of `text`, with the constants for hoisted
+    /// static JSX declared at byte index `hoist_at`. That is the start of a line, after the
+    /// imports, so later lines keep their numbers.
+    pub fn module(&self, text: &str, holes: &[(Range<usize>, Option<usize>)], nodes: &[JsxNode], hoist_at: usize) -> MappedText {
+        if !self.hoist {
+            return self.splice(text, 0, holes, nodes);
+        }
+        let at = holes
+            .iter()
+            .find(|(range, _)| range.start < hoist_at && hoist_at < range.end)
+            .map_or(hoist_at, |(range, _)| range.end);
+        let split = holes.partition_point(|(range, _)| range.end <= at);
+        let rest: Vec<_> = holes[split..].iter().map(|(range, node)| (range.start - at..range.end - at, *node)).collect();
+        let mut out = self.splice(&text[..at], 0, &holes[..split], nodes);
+        let after = self.splice(&text[at..], at, &rest, nodes);
+        for declaration in self.hoisted.borrow().iter() {
+            out.append(declaration);
+        }
+        // Declarations are separated by a space; an empty line needs no separator
+        if !self.hoisted.borrow().is_empty() && (after.as_str().is_empty() || after.as_str().starts_with('\n')) {
+            out.truncate(out.as_str().len() - 1);
+        }
+        out.append(&after);
+        out
+    }
+
+    /// `text`, which starts at byte offset `offset` of the parsed text, with each hole (a
+    /// byte range of `text`) replaced by the code for its node, or by `null` for JSX that failed to
+    /// parse in recovery mode
+    pub fn splice(&self, text: &str, offset: usize, holes: &[(Range<usize>, Option<usize>)], nodes: &[JsxNode]) -> MappedText {
+        let map = self.origin.map();
+        let mut out = MappedText::default();
+        let (mut copied, mut pos) = (0, offset);
+        for (range, node) in holes {
+            let code = &text[copied..range.start];
+            out.copy_mapped(code, pos, map);
+            pos += code.len();
+            match node {
+                Some(index) => out.append(&self.node(&nodes[*index])),
+                None => out.generate("null", map.original_pos(pos)),
+            }
+            pos += range.len();
+            copied = range.end;
+        }
+        out.copy_mapped(&text[copied..], pos, map);
+        out
+    }
+
+    pub fn expression(&self, expr: &Expression) -> MappedText {
+        let mut code = self.splice(&expr.source, expr.offset, &expr.holes, &expr.jsx);
+        // Expression text is trimmed, so keep the line break that ends a trailing `//` comment
+        if expr.source.lines().last().is_some_and(|line| line.contains("//")) {
+            code.push('\n');
+        }
+        code
+    }
+
+    pub fn node(&self, node: &JsxNode) -> MappedText {
+        match node {
+            JsxNode::Element(element) => self.element(element),
+            JsxNode::Fragment(fragment) => self.fragment(fragment),
+        }
+    }
+
+    /// The code from `build` for the node at `span`, or for a static node with hoisting on,
+    /// the name of a module-level constant holding it, so it is created once rather than
+    /// on every render
+    fn hoisted_or(&self, span: &Span, is_static: impl FnOnce() -> bool, build: impl FnOnce() -> MappedText) -> MappedText {
+        if !self.hoist || self.in_hoisted.get() || !is_static() {
+            return build();
+        }
+        self.in_hoisted.set(true);
+        let code = build();
+        self.in_hoisted.set(false);
+
+        let mut hoisted = self.hoisted.borrow_mut();
+        let name = format!("{}{}", HOISTED_JSX_PREFIX, hoisted.len() + 1);
+        let at = self.origin_of(span);
+        let mut declaration = MappedText::generated(&format!("const {} = ", name), at);
+        declaration.append(&code);
+        declaration.push_str("; ");
+        hoisted.push(declaration);
+        // DOM nodes can only be in one place, so each use gets its own copy
+        let reference = if self.dom { format!("{}.cloneNode(true)", name) } else { name };
+        MappedText::generated(&reference, at)
+    }
+
+    /// Original byte offset where the code for the node at `span` comes from
+    fn origin_of(&self, span: &Span) -> usize {
+        span.start
+    }
+
+    fn element(&self, element: &JsxElement) -> MappedText {
+        self.hoisted_or(&element.span, || element.is_static(), || {
+            if self.dom {
+                return self.dom_element(element);
+            }
+            let tag_name = element.name.to_string();
+            let mapped = self.element_map.filter(|_| is_intrinsic(&tag_name)).map(|map| (map, tag_name.as_str()));
+            if let Some((map, tag)) = mapped {
+                self.check_element(map, tag, element);
+            }
+            let props = self.props(&element.attributes, mapped);
+            let children = self.children(&element.children);
+            let tag_value = match mapped {
+                Some((map, tag)) => format!("\"{}\"", escape_string(map.tag(tag))),
+                None => self.tag_expression(&tag_name),
+            };
+            self.build_element_call(&element.span, true, &tag_value, &props, &children)
+        })
+    }
+
+    /// Warn about the intrinsic `tag` or its props if the target renderer cannot display them
+    fn check_element(&self, map: &ElementMap, tag: &str, element: &JsxElement) {
+        let props: Vec<(&str, usize)> = element
+            .attributes
+            .iter()
+            .filter_map(|item| match item {
+                JsxAttributeItem::Attribute(attr) => Some((attr.name.as_str(), attr.span.start)),
+                JsxAttributeItem::Spread(_) => None,
+            })
+            .collect();
+        // The tag name follows the element's `<`
+        self.warnings.borrow_mut().extend(map.check(tag, element.span.start + 1, &props));
+    }
+
+    fn fragment(&self, fragment: &JsxFragment) -> MappedText {
+        self.hoisted_or(&fragment.span, || fragment.children.iter().all(JsxChild::is_static), || {
+            if self.dom {
+                return self.dom_fragment(&fragment.span, &fragment.children);
+            }
+            let children = self.children(&fragment.children);
+            self.build_element_call(&fragment.span, false, &self.fragment_expression(), &PropList::default(), &children)
+        })
+    }
+
+    fn children(&self, children: &[JsxChild]) -> Vec<MappedText> {
+        children
+            .iter()
+            .filter_map(|child| match child {
+                JsxChild::Text(text) if text.value.is_empty() => None,
+                JsxChild::Text(text) => Some(MappedText::generated(
+                    &format!("\"{}\"", escape_string(&text.value)),
+                    self.origin_of(&text.span),
+                )),
+                JsxChild::ExpressionContainer(container) => {
+                    container.expression.as_ref().map(|expr| self.expression(expr))
+                }
+                JsxChild::Spread(spread) => {
+                    let mut code = MappedText::generated("...", self.origin_of(&spread.span));
+                    code.append(&self.expression(&spread.expression));
+                    Some(code)
+                }
+                JsxChild::Element(element) => Some(self.element(element)),
+                JsxChild::Fragment(fragment) => Some(self.fragment(fragment)),
+            })
+            .collect()
+    }
+
+    /// Props for `attributes`, renamed for the target renderer when `mapped` gives the
+    /// intrinsic tag they belong to
+    fn props(&self, attributes: &[JsxAttributeItem], mapped: Option<(&ElementMap, &str)>) -> PropList {
+        let mut props = PropList::default();
+        for item in attributes {
+            match item {
+                JsxAttributeItem::Spread(spread) => props.push_spread(self.expression(&spread.argument)),
+                JsxAttributeItem::Attribute(attr) => {
+                    let name = mapped.map_or(attr.name.as_str(), |(map, tag)| map.prop(tag, &attr.name));
+                    props.push(name, self.attribute_value(attr));
+                }
+            }
+        }
+        props
+    }
+
+    /// The code for an attribute's value
+    fn attribute_value(&self, attr: &JsxAttribute) -> MappedText {
+        match &attr.value {
+            // Boolean prop (no value means true)
+            None => MappedText::generated("true", self.origin_of(&attr.span)),
+            Some(JsxAttributeValue::String { value, span }) => {
+                MappedText::generated(&format!("\"{}\"", escape_string(value)), self.origin_of(span))
+            }
+            Some(JsxAttributeValue::ExpressionContainer(container)) => container
+                .expression
+                .as_ref()
+                .map_or_else(MappedText::default, |expr| self.expression(expr)),
+            Some(JsxAttributeValue::Element(node)) => self.node(node),
+        }
+    }
+
+    /// DOM construction for an intrinsic element, or a call of a component with its props
+    fn dom_element(&self, element: &JsxElement) -> MappedText {
+        let tag = element.name.to_string();
+        let at = self.origin_of(&element.span);
+        if tag == "Fragment" {
+            return self.dom_fragment(&element.span, &element.children);
+        }
+        if !is_intrinsic(&tag) {
+            let mut fields = self.props(&element.attributes, None).fields(false);
+            fields.extend(children_field(&self.children(&element.children)));
+            return call(at, &tag, &[object_literal(&fields)]);
+        }
+
+        let in_svg = self.in_svg.get() || tag == "svg";
+        // The value of a `<textarea>` or `<select>` is a property; the attribute does nothing
+        let form_value = !in_svg && matches!(tag.as_str(), "textarea" | "select");
+        let mut builder = DomBuilder::new(&if in_svg {
+            format!("document.createElementNS(\"{}\", \"{}\")", SVG_NAMESPACE, escape_string(&tag))
+        } else {
+            format!("document.createElement(\"{}\")", escape_string(&tag))
+        });
+        for item in &element.attributes {
+            let attr = match item {
+                JsxAttributeItem::Attribute(attr) => attr,
+                JsxAttributeItem::Spread(spread) => {
+                    let props = builder.arg(self.expression(&spread.argument));
+                    builder.statement(format!("__hook_dom_props(__el, {});", props));
+                    continue;
+                }
+            };
+            let literal = match &attr.value {
+                None => Some(UiValue::Boolean { value: true }),
+                Some(JsxAttributeValue::String { value, .. }) => Some(UiValue::String { value: value.clone() }),
+                Some(JsxAttributeValue::ExpressionContainer(container)) => match &container.expression {
+                    Some(expression) => literal_value(expression),
+                    None => continue,
+                },
+                Some(JsxAttributeValue::Element(_)) => None,
+            };
+            let value_prop = form_value && matches!(attr.name.as_str(), "value" | "defaultValue");
+            match literal {
+                Some(value) if value_prop => builder.statements.extend(dom_value(&value)),
+                Some(value) if attr.name != "ref" => builder.statements.extend(dom_attribute(&attr.name, &value)),
+                _ => {
+                    let value = builder.arg(self.attribute_value(attr));
+                    // Props the helper passes on as attributes are named for the DOM here already
+                    let name = if value_prop { "value" } else { html_attribute_name(&attr.name).unwrap_or(&attr.name) };
+                    builder.statement(format!("__hook_dom_prop(__el, \"{}\", {});", escape_string(name), value));
+                }
+            }
+        }
+        // A `<select>` value picks one of its options, so it is set once they are appended
+        let props = if tag == "select" { builder.statements.split_off(1) } else { Vec::new() };
+        // `foreignObject` holds HTML again
+        let outer_svg = self.in_svg.replace(in_svg && tag != "foreignObject");
+        self.dom_children(&mut builder, &element.children);
+        self.in_svg.set(outer_svg);
+        builder.statements.extend(props);
+        builder.finish(at)
+    }
+
+    /// A `DocumentFragment` holding `children`
+    fn dom_fragment(&self, span: &Span, children: &[JsxChild]) -> MappedText {
+        let mut builder = DomBuilder::new("document.createDocumentFragment()");
+        self.dom_children(&mut builder, children);
+        builder.finish(self.origin_of(span))
+    }
+
+    /// Append `children` to the node `builder` creates: text directly, elements with
+    /// `appendChild`, anything computed through the helper that also takes strings, arrays
+    /// and nothing
+    fn dom_children(&self, builder: &mut DomBuilder, children: &[JsxChild]) {
+        for child in children {
+            let (value, intrinsic) = match child {
+                JsxChild::Text(text) if text.value.is_empty() => continue,
+                JsxChild::Text(text) => {
+                    builder.statement(format!(
+                        "__el.appendChild(document.createTextNode(\"{}\"));",
+                        escape_string(&text.value)
+                    ));
+                    continue;
+                }
+                JsxChild::ExpressionContainer(container) => match &container.expression {
+                    Some(expression) => (self.expression(expression), false),
+                    None => continue,
+                },
+                JsxChild::Spread(spread) => (self.expression(&spread.expression), false),
+                JsxChild::Element(element) => (self.element(element), is_intrinsic(&element.name.to_string())),
+                JsxChild::Fragment(fragment) => (self.fragment(fragment), true),
+            };
+            let value = builder.arg(value);
+            builder.statement(if intrinsic {
+                format!("__el.appendChild({});", value)
+            } else {
+                format!("__hook_dom_append(__el, {});", value)
+            });
+        }
+    }
+
+    /// The fragment component for the active runtime
+    fn fragment_expression(&self) -> String {
+        match self.runtime {
+            JsxRuntime::Automatic { object, .. } => format!("{}.Fragment", object),
+            JsxRuntime::Classic { pragma_frag, .. } => pragma_frag.clone(),
+        }
+    }
+
+    /// Emit a JSX tag name as the first argument of a runtime call
+    fn tag_expression(&self, tag_name: &str) -> String {
+        if tag_name == "Fragment" {
+            self.fragment_expression()
+        } else if is_custom_component(tag_name) {
+            tag_name.to_string()
+        } else {
+            format!("\"{}\"", tag_name)
+        }
+    }
+
+    /// Emit the runtime call for the element or fragment at `span`, whose type is already
+    /// an expression. Only elements (`with_source`) carry a location in development output,
+    /// as in Babel and SWC.
+    fn build_element_call(
+        &self,
+        span: &Span,
+        with_source: bool,
+        tag_value: &str,
+        props: &PropList,
+        children: &[MappedText],
+    ) -> MappedText {
+        let at = self.origin_of(span);
+        let dev_source = self
+            .dev_filename
+            .as_deref()
+            .filter(|_| with_source)
+            .map(|file| dev_source_object(file, span));
+        let object = match self.runtime {
+            JsxRuntime::Classic { pragma, .. } => {
+                return create_element_call(at, pragma, tag_value, props, dev_source, children);
+            }
+            // `jsx()` takes the key as its own argument, which would let a key written after a
+            // spread be overridden by the spread's key, so React falls back to createElement
+            JsxRuntime::Automatic { object, import_source } if props.key_follows_spread() => {
+                let callee = create_element_callee(object, import_source.is_some());
+                return create_element_call(at, &callee, tag_value, props, dev_source, children);
+            }
+            JsxRuntime::Automatic { object, .. } => object,
+        };
+
+        let mut fields = props.fields(false);
+        fields.extend(children_field(children));
+        let props_obj = object_literal(&fields);
+        let is_static = uses_jsxs(children);
+        if self.dev_filename.is_some() {
+            let mut args = vec![
+                MappedText::from(tag_value),
+                props_obj,
+                props.key().unwrap_or_else(|| MappedText::from("void 0")),
+                MappedText::from(if is_static { "true" } else { "false" }),
+            ];
+            if let Some(source) = dev_source {
+                args.push(MappedText::from(source.as_str()));
+                args.push(MappedText::from("this"));
+            }
+            return call(at, &format!("{}.jsxDEV", object), &args);
+        }
+        let runtime_fn = if is_static { "jsxs" } else { "jsx" };
+        let mut args = vec![MappedText::from(tag_value), props_obj];
+        args.extend(props.key());
+        call(at, &format!("{}.{}", object, runtime_fn), &args)
+    }
+}
+
+/// The `children` prop: a single child as is, several (or a spread) as an array
+fn children_field(children: &[MappedText]) -> Option<MappedText> {
+    match children {
+        [] => None,
+        [child] if !child.as_str().starts_with("...") => Some(field("children: ", child)),
+        _ => {
+            let mut array = MappedText::from("[");
+            join(&mut array, children, ", ");
+            array.push(']');
+            Some(field("children: ", &array))
+        }
+    }
+}
+
+/// Namespace of the elements inside an `<svg>`
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+/// Statements building one DOM node as `__el` inside `(function (__v0, …) { … })(…)`. The
+/// values the statements use are evaluated by the caller, in source order, and passed in,
+/// so `this`, `arguments`, `await` and `yield` in them keep their meaning.
+struct DomBuilder {
+    statements: Vec<String>,
+    args: Vec<MappedText>,
+}
+
+impl DomBuilder {
+    fn new(create: &str) -> Self {
+        Self { statements: vec![format!("var __el = {};", create)], args: Vec::new() }
+    }
+
+    fn statement(&mut self, statement: String) {
+        self.statements.push(statement);
+    }
+
+    /// The parameter receiving `value`
+    fn arg(&mut self, value: MappedText) -> String {
+        self.args.push(value);
+        format!("__v{}", self.args.len() - 1)
+    }
+
+    fn finish(self, at: usize) -> MappedText {
+        let params: Vec<String> = (0..self.args.len()).map(|i| format!("__v{}", i)).collect();
+        let mut code = MappedText::generated(
+            &format!("(function ({}) {{ {} return __el; }})(", params.join(", "), self.statements.join(" ")),
+            at,
+        );
+        join(&mut code, &self.args, ", ");
+        code.push(')');
+        code
+    }
+}
+
+/// The statement setting the literal prop `name` on `__el`, or `None` for props that never
+/// become attributes: `key`, event handlers, and `false` and `null`, except that `data-*`
+/// and `aria-*` keep booleans as text
+fn dom_attribute(name: &str, value: &UiValue) -> Option<String> {
+    if name == "key" || name.strip_prefix("on").is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase())) {
+        return None;
+    }
+    let stringified = name.starts_with("data-") || name.starts_with("aria-");
+    let value = match value {
+        UiValue::String { value } => value.clone(),
+        UiValue::Number { value } => number_text(*value),
+        UiValue::Boolean { value } if stringified => value.to_string(),
+        UiValue::Boolean { value: true } => String::new(),
+        _ => return None,
+    };
+    let name = html_attribute_name(name).unwrap_or(name);
+    Some(format!("__el.setAttribute(\"{}\", \"{}\");", escape_string(name), escape_string(&value)))
+}
+
+/// The statement setting the `value` property of a `<textarea>` or `<select>` to a literal
+fn dom_value(value: &UiValue) -> Option<String> {
+    let value = match value {
+        UiValue::String { value } => value.clone(),
+        UiValue::Number { value } => number_text(*value),
+        _ => return None,
+    };
+    Some(format!("__el.value = \"{}\";", escape_string(&value)))
+}
+
+/// Helpers for values only known at run time, declared by [`with_dom_helpers`]
+const DOM_HELPERS: &str = concat!(
+    "function __hook_dom_append(parent, child) { ",
+    "if (child == null || typeof child === \"boolean\") return; ",
+    "if (Array.isArray(child)) { for (var i = 0; i < child.length; i++) __hook_dom_append(parent, child[i]); return; } ",
+    "parent.appendChild(typeof child === \"object\" ? child : document.createTextNode(String(child))); } ",
+    "function __hook_dom_prop(el, name, value) { ",
+    "if (name === \"key\" || name === \"children\") return; ",
+    "if (name === \"ref\") { if (typeof value === \"function\") value(el); else if (value) value.current = el; return; } ",
+    "if (/^on[A-Z]/.test(name)) { if (value) el.addEventListener(name === \"onDoubleClick\" ? \"dblclick\" : name.slice(2).toLowerCase(), value); return; } ",
+    "if (name === \"style\" && value && typeof value === \"object\") { for (var key in value) { ",
+    "if (key.slice(0, 2) === \"--\") el.style.setProperty(key, value[key]); else el.style[key] = value[key]; } return; } ",
+    "if (name === \"dangerouslySetInnerHTML\") { if (value) el.innerHTML = value.__html; return; } ",
+    "if (name === \"value\" || name === \"checked\" || name === \"selected\" || name === \"muted\") { el[name] = value; return; } ",
+    "if (value == null || (value === false && !/^(aria|data)-/.test(name))) return; ",
+    "el.setAttribute(name === \"className\" ? \"class\" : name === \"htmlFor\" ? \"for\" : name, value === true ? \"\" : String(value)); } ",
+    "function __hook_dom_props(el, props) { for (var name in props) __hook_dom_prop(el, name, props[name]); } ",
+);
+
+/// Declare the DOM helpers the code uses, on the first line so line numbers are unchanged
+pub(crate) fn with_dom_helpers(code: MappedText, opts: &TranspileOptions) -> MappedText {
+    if opts.jsx_backend != JsxBackend::Dom || !code.as_str().contains("__hook_dom_") {
+        return code;
+    }
+    let mut output = MappedText::generated(DOM_HELPERS, 0);
+    output.append(&code);
+    output
+}
+
+/// `callee(args)` generated for the construct at original byte offset `at`
+fn call(at: usize, callee: &str, args: &[MappedText]) -> MappedText {
+    let mut code = MappedText::generated(&format!("{}(", callee), at);
+    join(&mut code, args, ", ");
+    code.push(')');
+    code
+}
+
+/// Append `parts` to `code`, separated by `separator`
+fn join(code: &mut MappedText, parts: &[MappedText], separator: &str) {
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            code.push_str(separator);
+        }
+        code.append(part);
+    }
+}
+
+/// `prefix` (e.g. `name: `) followed by `value`
+fn field(prefix: &str, value: &MappedText) -> MappedText {
+    let mut code = MappedText::from(prefix);
+    code.append(value);
+    code
+}
+
+/// `{ fields }`, or `{}` without any
+fn object_literal(fields: &[MappedText]) -> MappedText {
+    if fields.is_empty() {
+        return MappedText::from("{}");
+    }
+    let mut code = MappedText::from("{ ");
+    join(&mut code, fields, ", ");
+    code.push_str(" }");
+    code
+}
+
+/// Bind the automatic runtime object to `{import_source}/jsx-runtime` (or `jsx-dev-runtime`)
+/// when an import source is set, plus `createElement` from the package root when the
+/// key-after-spread fallback is used. The imports share the first line so line numbers are unchanged.
+pub(crate) fn with_runtime_import(code: MappedText, opts: &TranspileOptions) -> MappedText {
+    let JsxRuntime::Automatic { object, import_source: Some(source) } = &opts.jsx_runtime else {
+        return code;
+    };
+    let mut imports = String::new();
+    if code.as_str().contains(&format!("{}.jsx", object)) {
+        let module = if opts.development { "jsx-dev-runtime" } else { "jsx-runtime" };
+        imports.push_str(&format!("import * as {} from \"{}/{}\"; ", object, source, module));
+    }
+    let create_element = create_element_callee(object, true);
+    if code.as_str().contains(&format!("{}(", create_element)) {
+        imports.push_str(&format!("import {{ createElement as {} }} from \"{}\"; ", create_element, source));
+    }
+    if imports.is_empty() {
+        return code;
+    }
+    let mut output = MappedText::generated(&imports, 0);
+    output.append(&code);
+    output
+}
+
+/// Whether a tag name is emitted as an expression rather than a string.
+/// Mirrors Babel: member expressions are references, namespaced names and names that are
+/// not valid identifiers (`my-element`) are strings, otherwise only lowercase ASCII starts
+/// an intrinsic element.
+fn is_custom_component(tag: &str) -> bool {
+    let Some(first_char) = tag.chars().next() else {
+        return false;
+    };
+    if tag.contains(':') {
+        return false;
+    }
+    if tag.contains('.') {
+        return true;
+    }
+    !first_char.is_ascii_lowercase() && !tag.contains('-')
+}
+
+/// Whether `tag` is an intrinsic element, passed to the runtime as a string
+pub(crate) fn is_intrinsic(tag: &str) -> bool {
+    tag != "Fragment" && !is_custom_component(tag)
+}
+
+/// Spread children (`{...items}`) can only be passed as a static children array
+fn uses_jsxs(children: &[MappedText]) -> bool {
+    children.len() > 1 || children.iter().any(|c| c.as_str().starts_with("..."))
+}
+
+/// `{ fileName, lineNumber, columnNumber }` for an element at `span`
+fn dev_source_object(file: &str, span: &Span) -> String {
+    format!(
+        "{{ fileName: \"{}\", lineNumber: {}, columnNumber: {} }}",
+        escape_string(file),
+        span.line,
+        span.column
+    )
+}
+
+/// Callee for the automatic runtime's createElement fallback. With an import source it is
+/// imported from the package root (see `with_runtime_import`), otherwise the runtime object
+/// provides it.
+fn create_element_callee(object: &str, has_import_source: bool) -> String {
+    if has_import_source {
+        format!("{}_createElement", object)
+    } else {
+        format!("{}.createElement", object)
+    }
+}
+
+/// `callee(type, props | null, ...children)` with `key` kept in props
+fn create_element_call(
+    at: usize,
+    callee: &str,
+    tag_value: &str,
+    props: &PropList,
+    dev_source: Option<String>,
+    children: &[MappedText],
+) -> MappedText {
+    let mut fields = props.fields(true);
+    if let Some(source) = dev_source {
+        fields.push(MappedText::from(format!("__source: {}", source).as_str()));
+        fields.push(MappedText::from("__self: this"));
+    }
+    let mut args = vec![
+        MappedText::from(tag_value),
+        if fields.is_empty() { MappedText::from("null") } else { object_literal(&fields) },
+    ];
+    args.extend(children.iter().cloned());
+    call(at, callee, &args)
+}
+
+/// Generated props of one element in source order
+#[derive(Default)]
+struct PropList {
+    entries: Vec<PropEntry>,
+}
+
+enum PropEntry {
+    Spread(MappedText),
+    /// `name: value`; `repeated` if an earlier entry has the same name
+    Named { name: String, value: MappedText, repeated: bool },
+}
+
+impl PropList {
+    fn push_spread(&mut self, expr: MappedText) {
+        self.entries.push(PropEntry::Spread(expr));
+    }
+
+    /// Add `name: value`. The parser has already warned about a repeated name.
+    fn push(&mut self, name: &str, value: MappedText) {
+        let repeated = self.entries.iter().any(|entry| matches!(entry, PropEntry::Named { name: seen, .. } if seen == name));
+        self.entries.push(PropEntry::Named { name: name.to_string(), value, repeated });
+    }
+
+    /// Value of the `key` prop; a repeated key is a sequence, so every value is evaluated
+    /// and the last one wins
+    fn key(&self) -> Option<MappedText> {
+        let keys: Vec<MappedText> = self
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                PropEntry::Named { name, value, .. } if name == "key" => Some(value.clone()),
+                _ => None,
+            })
+            .collect();
+        match &keys[..] {
+            [] => None,
+            [key] => Some(key.clone()),
+            _ => {
+                let mut code = MappedText::from("(");
+                join(&mut code, &keys, ", ");
+                code.push(')');
+                Some(code)
+            }
+        }
+    }
+
+    /// Whether `key` is given after a spread, which the `jsx()` signature cannot express
+    fn key_follows_spread(&self) -> bool {
+        let mut entries = self.entries.iter();
+        entries.any(|entry| matches!(entry, PropEntry::Spread(_)))
+            && entries.any(|entry| matches!(entry, PropEntry::Named { name, .. } if name == "key"))
+    }
+
+    /// Object literal members in source order; the automatic runtime passes `key` separately.
+    /// A repeated name is spread from an object of its own: duplicate keys are a syntax error
+    /// in ES5 strict mode, and the spread still evaluates both values in order, keeps the
+    /// first position of the prop and lets the later value win as in React.
+    fn fields(&self, include_key: bool) -> Vec<MappedText> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                PropEntry::Spread(expr) => Some(field("...", expr)),
+                PropEntry::Named { name, .. } if name == "key" && !include_key => None,
+                PropEntry::Named { name, value, repeated } => {
+                    let member = field(&format!("{}: ", prop_key(name)), value);
+                    Some(if *repeated { field("...", &object_literal(&[member])) } else { member })
+                }
+            })
+            .collect()
+    }
+}
+
+/// Words that older engines (ES3-era JavaScriptCore) reject as bare object keys
+const RESERVED_WORDS: &[&str] = &[
+    "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default",
+    "delete", "do", "else", "enum", "export", "extends", "false", "finally", "for", "function",
+    "if", "implements", "import", "in", "instanceof", "interface", "let", "new", "null",
+    "package", "private", "protected", "public", "return", "static", "super", "switch", "this",
+    "throw", "true", "try", "typeof", "var", "void", "while", "with", "yield",
+];
+
+/// Object key for an attribute: identifiers stay bare, while hyphenated (`aria-label`),
+/// namespaced (`xlink:href`) and reserved (`class`) names are emitted as string keys
+fn prop_key(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_identifier = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '\u{200C}' | '\u{200D}'));
+    if is_identifier && !RESERVED_WORDS.contains(&name) {
+        name.to_string()
+    } else {
+        format!("\"{}\"", escape_string(name))
+    }
+}
+
+pub(crate) fn escape_string(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prop_key() {
+        assert_eq!(prop_key("className"), "className");
+        assert_eq!(prop_key("$ref"), "$ref");
+        assert_eq!(prop_key("aria-hidden"), "\"aria-hidden\"");
+        assert_eq!(prop_key("xlink:href"), "\"xlink:href\"");
+        assert_eq!(prop_key("default"), "\"default\"");
+    }
+}
@@ -1,52 +1,233 @@
 /// Minimal JSX to JavaScript transpiler
-/// Converts JSX syntax to __hook_jsx_runtime.jsx() calls
+/// Parses JSX into the typed tree in `ast`, which `jsx_codegen` turns into runtime calls
 /// Supports: elements, props, children, fragments, spreads
 /// Does NOT support: TypeScript, complex expressions in JSX attributes
 
 use crate::TranspileOptions;
-use anyhow::{Result, anyhow};
+use crate::ast::{
+    ExportDeclaration, ExportSpecifier, Expression, ImportDeclaration, ImportSpecifier,
+    JsxAttribute, JsxAttributeItem, JsxAttributeValue, JsxChild, JsxElement, JsxElementName,
+    JsxExpressionContainer, JsxFragment, JsxNode, JsxSpreadAttribute, JsxSpreadChild, JsxText,
+    Module,
+};
+use crate::entities::decode_entities;
+use crate::error::{ErrorCode, ErrorKind, Span, TranspileError};
+use crate::html_compat;
+use crate::jsx_codegen::{Codegen, with_dom_helpers, with_runtime_import};
+use crate::lexer::{self, Token, TokenKind, is_identifier_char, is_identifier_start};
+use crate::position::LineIndex;
+use crate::source_map::{MappedText, OffsetMap};
+use anyhow::Result;
+use std::ops::Range;
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
-pub struct ParseContext {
-    pub source: Vec<char>,
+pub struct ParseContext<'a> {
+    pub source: &'a str,
+    /// Byte offset of the next char to parse
     pub pos: usize,
+    /// Byte offset where the text being parsed ends; nested expressions are parsed with
+    /// this narrowed to their closing brace
+    pub end: usize,
     pub is_typescript: bool,
+    /// Whether a `/` at `pos` starts a regex literal rather than a division
+    pub regex_allowed: bool,
+    /// Recovery mode: record errors in `diagnostics` and resync instead of stopping
+    pub recover: bool,
+    /// HTML-compatibility mode: accept pasted HTML markup, warning about each fix
+    pub html_compat: bool,
+    pub diagnostics: Vec<TranspileError>,
+    /// Tag names of the elements currently being parsed (fragments are "")
+    pub open_tags: Vec<String>,
+    /// Tag names of the elements around the `{expr}` container being parsed: one of their
+    /// closing tags ends the container when its `}` is missing
+    pub enclosing_tags: Vec<String>,
+    /// The original file this text came from, used for node spans
+    pub origin: Rc<SourceOrigin>,
+}
+
+/// The original file behind a parse, used to give nodes spans in it
+#[derive(Debug, Default)]
+pub struct SourceOrigin {
+    /// Positions in the original source
+    index: LineIndex,
+    /// Stripped-text positions back to the original (empty for JavaScript)
+    map: OffsetMap,
 }
 
-impl ParseContext {
-    pub fn new(source: String, is_typescript: bool) -> Self {
-        Self { source: source.chars().collect(), pos: 0, is_typescript }
+impl SourceOrigin {
+    pub fn new(original: &str, map: OffsetMap) -> Self {
+        Self { index: LineIndex::new(original), map }
     }
 
-    pub fn get_line_col(&self) -> (usize, usize) {
-        let mut line = 1;
-        let mut col = 1;
-        for i in 0..self.pos {
-            if self.source[i] == '\n' {
-                line += 1;
-                col = 1;
-            } else {
-                col += 1;
+    /// Span in the original source of the stripped-text byte range `start..end`
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let start = self.index.position_at_byte(self.map.original_pos(start));
+        let end = self.map.original_pos(end).max(start.offset);
+        Span::from_positions(start, self.index.position_at_byte(end))
+    }
+
+    /// Stripped-text positions back to the original
+    pub fn map(&self) -> &OffsetMap {
+        &self.map
+    }
+}
+
+impl<'a> ParseContext<'a> {
+    pub fn new(source: &'a str, is_typescript: bool) -> Self {
+        Self {
+            end: source.len(),
+            source,
+            pos: 0,
+            is_typescript,
+            regex_allowed: true,
+            recover: false,
+            html_compat: false,
+            diagnostics: Vec::new(),
+            open_tags: Vec::new(),
+            enclosing_tags: Vec::new(),
+            origin: Rc::default(),
+        }
+    }
+
+    /// Span in the original file of the byte range `start..end`
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        self.origin.span(start, end)
+    }
+
+    /// In recovery mode, record the error and carry on; otherwise propagate it
+    pub fn report(&mut self, err: anyhow::Error) -> Result<()> {
+        if !self.recover {
+            return Err(err);
+        }
+        self.diagnostics.push(TranspileError::from_anyhow(err, ErrorCode::Internal));
+        Ok(())
+    }
+
+    /// Record a non-fatal diagnostic over the byte range `start..end`; never stops the parse
+    pub fn warn_at(&mut self, code: ErrorCode, msg: &str, start: usize, end: usize) {
+        self.diagnostics.push(TranspileError::new(code, msg).at_offsets(start, end));
+    }
+
+    /// Decode character references in `text`, which starts at byte offset `start`,
+    /// warning about any that do not resolve
+    pub fn decode_entities_at(&mut self, text: &str, start: usize) -> String {
+        let (decoded, unknown) = decode_entities(text);
+        for range in unknown {
+            let name = &text[range.clone()];
+            self.warn_at(
+                ErrorCode::UnknownEntity,
+                &format!("Unknown HTML entity {}; it will be rendered literally", name),
+                start + range.start,
+                start + range.end,
+            );
+        }
+        decoded
+    }
+
+    /// After a failed element inside children, skip to the next tag or expression
+    pub fn resync_children(&mut self, failed_at: usize) {
+        self.pos = self.pos.max(failed_at + 1);
+        while let Some(ch) = self.current_char() {
+            if ch == '<' || ch == '{' {
+                break;
             }
+            self.advance();
         }
-        (line, col)
     }
 
-    pub fn error(&self, msg: &str) -> anyhow::Error {
-        let (line, col) = self.get_line_col();
-        anyhow!("{} at line {}, column {}", msg, line, col)
+    /// After a failed top-level element, skip to the end of the enclosing statement:
+    /// a `;`, a line break, or a `}`/`)` that closes something opened before the element.
+    /// An element that read past its statement (an unclosed string or tag) is cut back to
+    /// it, so the code after is still parsed, and errors reported past it are dropped.
+    pub fn resync_statement(&mut self, failed_at: usize) {
+        let statement_end = self.statement_end(failed_at + 1);
+        if self.pos > statement_end {
+            // Keep the error that ended the element; the rest are found again
+            let fatal = self.diagnostics.pop();
+            self.diagnostics.retain(|d| d.span.as_deref().is_none_or(|span| span.start < statement_end));
+            self.diagnostics.extend(fatal);
+        }
+        self.pos = self.pos.max(failed_at + 1).min(statement_end);
+        let mut depth = 0usize;
+        while let Some(ch) = self.current_char() {
+            match ch {
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' if depth == 0 => break,
+                '}' | ')' | ']' => depth -= 1,
+                ';' | '\n' if depth == 0 => break,
+                _ => {}
+            }
+            self.advance();
+        }
+        self.pos = self.pos.min(self.end);
+    }
+
+    /// Byte offset of the first `;` or unmatched closing bracket from `from`, ignoring
+    /// strings and tags, which may be the broken part; or if brackets opened on the way never
+    /// close, of the end of that line
+    fn statement_end(&self, from: usize) -> usize {
+        let mut depth = 0usize;
+        let mut line_end = None;
+        for (i, ch) in self.source[from.min(self.end)..self.end].char_indices() {
+            let at = from + i;
+            match ch {
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' | ';' if depth == 0 => return at,
+                '}' | ')' | ']' => depth -= 1,
+                '\n' => {
+                    line_end.get_or_insert(at);
+                }
+                _ => {}
+            }
+        }
+        line_end.unwrap_or(self.end)
+    }
+
+    /// Error at the current char
+    pub fn error(&self, code: ErrorCode, msg: &str) -> anyhow::Error {
+        let end = self.pos + self.current_char().map_or(1, char::len_utf8);
+        self.error_at(code, msg, self.pos, end)
+    }
+
+    /// Error covering the byte range `start..end`
+    pub fn error_at(&self, code: ErrorCode, msg: &str, start: usize, end: usize) -> anyhow::Error {
+        let end = end.min(self.source.len());
+        TranspileError::new(code, msg).at_offsets(start, end.max(start)).into()
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.pos >= self.end
     }
 
     pub fn current_char(&self) -> Option<char> {
-        self.source.get(self.pos).copied()
+        self.char_at(self.pos)
     }
 
+    /// The char `offset` chars after the current one
     pub fn peek(&self, offset: usize) -> Option<char> {
-        self.source.get(self.pos + offset).copied()
+        self.source.get(self.pos..self.end)?.chars().nth(offset)
     }
 
+    /// The char at byte offset `index`, if it is before `end`
+    fn char_at(&self, index: usize) -> Option<char> {
+        lexer::char_at(self.source, index, self.end)
+    }
+
+    /// Step over the current char
     pub fn advance(&mut self) {
-        self.pos += 1;
+        self.pos += self.current_char().map_or(1, char::len_utf8);
+    }
+
+    /// Lex the token at `pos` and move past it
+    pub fn next_token(&mut self) -> Option<Token> {
+        if self.at_end() {
+            return None;
+        }
+        let token = lexer::token_at(self.source, self.pos, self.end, self.regex_allowed);
+        self.regex_allowed = token.regex_allowed_after(self.source, self.regex_allowed);
+        self.pos = token.end;
+        Some(token)
     }
 
     pub fn skip_whitespace(&mut self) {
@@ -61,499 +242,657 @@ impl ParseContext {
 
     pub fn consume(&mut self, expected: char) -> Result<()> {
         if self.current_char() == Some(expected) {
-            self.advance();
+            self.pos += expected.len_utf8();
             Ok(())
         } else {
-            Err(self.error(&format!("Expected '{}'", expected)))
+            Err(self.error(ErrorCode::ExpectedChar, &format!("Expected '{}'", expected)))
         }
     }
 
-    pub fn slice(&self, start: usize, end: usize) -> String {
-        self.source[start..end].iter().collect()
+    /// Step over the `}` closing the expression container that opened at byte offset `open`,
+    /// or report it missing there rather than wherever the expression ended
+    pub fn close_container(&mut self, open: usize) -> Result<()> {
+        if self.current_char() == Some('}') {
+            self.advance();
+            return Ok(());
+        }
+        Err(self.error_at(ErrorCode::ExpectedChar, "Expected '}' to close this expression container", open, open + 1))
+    }
+
+    pub fn slice(&self, start: usize, end: usize) -> &'a str {
+        &self.source[start..end]
+    }
+
+    /// Which of `words` the bytes `start..end` spell
+    pub fn word_in<'w>(&self, start: usize, end: usize, words: &[&'w str]) -> Option<&'w str> {
+        let text = &self.source[start..end];
+        words.iter().copied().find(|&word| word == text)
+    }
+
+    /// Run `f` over the byte range `start..end` as its own unit of code, sharing this
+    /// context's buffers, then continue after the range with the outer state
+    fn within<T>(&mut self, start: usize, end: usize, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let outer_end = std::mem::replace(&mut self.end, end);
+        let outer_tags = std::mem::take(&mut self.open_tags);
+        let outer_regex_allowed = std::mem::replace(&mut self.regex_allowed, true);
+        self.pos = start;
+        let result = f(self);
+        self.end = outer_end;
+        self.open_tags = outer_tags;
+        self.regex_allowed = outer_regex_allowed;
+        self.pos = end;
+        result
     }
+
+    /// The line breaks inside `start..end`, so removed code keeps later lines aligned
+    pub fn newlines_in(&self, start: usize, end: usize) -> String {
+        "\n".repeat(self.source[start..end].matches('\n').count())
+    }
+}
+
+/// A parsed file together with what code generation needs to rebuild it
+#[derive(Debug)]
+pub struct ParsedModule {
+    pub module: Module,
+    /// The text that was parsed (TypeScript already stripped)
+    text: String,
+    /// Byte ranges in `text` holding JSX, as in [`Expression`]
+    holes: Vec<(Range<usize>, Option<usize>)>,
+    /// Non-fatal warnings, plus every error in recovery mode
+    pub diagnostics: Vec<TranspileError>,
+    /// The original file behind `text`
+    origin: Rc<SourceOrigin>,
+}
+
+/// JSX found while scanning JavaScript: the outermost nodes and, per node, the byte range it
+/// replaces (`None` for an element that failed to parse in recovery mode)
+#[derive(Default)]
+struct ScannedCode {
+    jsx: Vec<JsxNode>,
+    holes: Vec<(Range<usize>, Option<usize>)>,
 }
 
-/// Main transpiler entry point
+/// Import and export declarations collected while scanning the top level
+#[derive(Default)]
+struct ModuleItems {
+    imports: Vec<ImportDeclaration>,
+    exports: Vec<ExportDeclaration>,
+}
+
+/// Strict entry point that discards warnings
+#[cfg(test)]
 pub fn transpile_jsx(source: &str, opts: &TranspileOptions) -> Result<String> {
-    if !opts.is_typescript {
-        // Strict JavaScript mode: No TypeScript allowed
-        // We'll run a quick check for TS-only syntax
-        check_for_typescript_syntax(source)?;
+    transpile_jsx_with_warnings(source, opts).map(|(code, _)| code.into_parts().0)
+}
+
+/// Main transpiler entry point: stops at the first error and also returns non-fatal warnings (e.g. unknown HTML entities).
+/// The output maps back to char positions in `source`. Spans are relative to `source` and
+/// still need [`TranspileError::with_source`].
+pub fn transpile_jsx_with_warnings(source: &str, opts: &TranspileOptions) -> Result<(MappedText, Vec<TranspileError>)> {
+    let opts = with_file_pragmas(source, opts);
+    let mut parsed = parse_module(source, &opts, false)?;
+    let (code, warnings) = generate(&parsed, &opts);
+    parsed.diagnostics.extend(warnings);
+    Ok((code, parsed.diagnostics))
+}
+
+/// Apply the file's JSX pragma comments once for the whole file
+fn with_file_pragmas(source: &str, opts: &TranspileOptions) -> TranspileOptions {
+    TranspileOptions {
+        jsx_runtime: opts.jsx_runtime.with_pragmas(source),
+        ..opts.clone()
     }
+}
 
-    let source = if opts.is_typescript {
-        strip_typescript(source)?
-    } else {
-        source.to_string()
-    };
+/// The parsed text with its JSX replaced by runtime calls, plus warnings about JSX the
+/// target renderer does not support
+fn generate(parsed: &ParsedModule, opts: &TranspileOptions) -> (MappedText, Vec<TranspileError>) {
+    let codegen = Codegen::new(opts, &parsed.origin);
+    let code = codegen.module(&parsed.text, &parsed.holes, &parsed.module.jsx, hoist_point(parsed));
+    (with_dom_helpers(with_runtime_import(code, opts), opts), codegen.into_warnings())
+}
 
-    let mut ctx = ParseContext::new(source, opts.is_typescript);
-    let mut output = String::new();
-    
-    while ctx.pos < ctx.source.len() {
-        let ch = ctx.current_char();
-        
-        // Handle strings to avoid transpiling JSX inside them
-        if ch == Some('"') || ch == Some('\'') || ch == Some('`') {
-            let quote = ch.unwrap();
-            output.push(quote);
-            ctx.advance();
-            while let Some(c) = ctx.current_char() {
-                output.push(c);
-                if c == '\\' {
-                    ctx.advance();
-                    if let Some(next) = ctx.current_char() {
-                        output.push(next);
-                        ctx.advance();
-                    }
-                    continue;
-                }
-                
-                if c == quote {
-                    ctx.advance();
-                    break;
-                }
-                
-                // Handle template literal interpolation
-                if quote == '`' && c == '$' && ctx.peek(1) == Some('{') {
-                    output.push('{');
-                    ctx.advance(); // consume $
-                    ctx.advance(); // consume {
-                    let expr = parse_js_expression(&mut ctx, '}')?;
-                    ctx.consume('}')?;
-                    let transpiled_expr = transpile_jsx(&expr, opts)?;
-                    output.push_str(&transpiled_expr);
-                    output.push('}');
-                    continue;
-                }
-                
-                ctx.advance();
+/// Byte index in the parsed text where hoisted JSX is declared: the start of the line after
+/// the last import, so bindings the JSX calls may use are already imported (also after a
+/// later conversion to `require` calls), and after the directive prologue, which must stay
+/// first for `'use strict'` to apply. TypeScript stripping keeps line breaks, so the
+/// original line of the import is also its line in the parsed text.
+fn hoist_point(parsed: &ParsedModule) -> usize {
+    let text = &parsed.text;
+    let line_after = |line: usize| text.match_indices('\n').nth(line).map_or(text.len(), |(newline, _)| newline + 1);
+    let after_imports = parsed.module.imports.last().map_or(0, |import| line_after(import.span.end_line - 1));
+    let prologue_end = lexer::directive_prologue_end(text, 0, text.len());
+    if prologue_end == 0 {
+        return after_imports;
+    }
+    after_imports.max(line_after(text[..prologue_end].matches('\n').count()))
+}
+
+/// `@jsx`-family pragmas in comments, in source order, as `(name, value)` pairs,
+/// e.g. `/** @jsx h */` yields `("jsx", "h")`
+pub fn jsx_pragmas(source: &str) -> Vec<(String, String)> {
+    let mut pragmas = Vec::new();
+    let mut collect = |comment: &str| {
+        let mut words = comment.split_whitespace().map(|w| w.trim_start_matches('*'));
+        while let Some(word) = words.next() {
+            if let Some(name) = word.strip_prefix('@').filter(|n| n.starts_with("jsx"))
+                && let Some(value) = words.next().filter(|v| !v.is_empty() && !v.starts_with('@'))
+            {
+                pragmas.push((name.to_string(), value.trim_end_matches("*/").to_string()));
             }
-            continue;
         }
+    };
 
-        // Handle comments
-        if ch == Some('/') {
-            if ctx.peek(1) == Some('/') {
-                output.push_str("//");
-                ctx.advance();
-                ctx.advance();
-                while let Some(c) = ctx.current_char() {
-                    output.push(c);
-                    ctx.advance();
-                    if c == '\n' {
-                        break;
-                    }
-                }
-                continue;
-            } else if ctx.peek(1) == Some('*') {
-                output.push_str("/*");
-                ctx.advance();
-                ctx.advance();
-                while let Some(c) = ctx.current_char() {
-                    if c == '*' && ctx.peek(1) == Some('/') {
-                        output.push_str("*/");
-                        ctx.advance();
-                        ctx.advance();
-                        break;
-                    }
-                    output.push(c);
-                    ctx.advance();
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'\'' | b'`' => {
+                let quote = bytes[i];
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
                 }
-                continue;
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = source[i..].find('\n').map_or(source.len(), |n| i + n);
+                collect(&source[i + 2..end]);
+                i = end;
             }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = source[i + 2..].find("*/").map_or(source.len(), |n| i + 2 + n);
+                collect(&source[i + 2..end]);
+                i = end + 2;
+            }
+            _ => i += 1,
         }
+    }
+    pragmas
+}
 
-        if ch == Some('<') && is_jsx_start(&ctx) {
-            let jsx_code = parse_jsx_element(&mut ctx)?;
-            output.push_str(&jsx_code);
-        } else {
-            // Pass through non-JSX code as-is
-            if let Some(ch) = ctx.current_char() {
-                output.push(ch);
-            }
-            ctx.advance();
+/// Recovery-mode entry point: returns best-effort output plus every diagnostic found.
+/// Spans are relative to `source` and still need [`TranspileError::with_source`].
+pub fn transpile_jsx_recovering(source: &str, opts: &TranspileOptions) -> (String, Vec<TranspileError>) {
+    let opts = with_file_pragmas(source, opts);
+    match parse_module(source, &opts, true) {
+        Ok(mut parsed) => {
+            let (code, warnings) = generate(&parsed, &opts);
+            parsed.diagnostics.extend(warnings);
+            (code.into_parts().0, parsed.diagnostics)
         }
+        // Recovery mode only fails on internal errors; keep the source as the output
+        Err(e) => (source.to_string(), vec![TranspileError::from_anyhow(e, ErrorCode::Internal)]),
     }
-    
-    Ok(output)
 }
 
-pub fn strip_typescript(source: &str) -> Result<String> {
-    let mut ctx = ParseContext::new(source.to_string(), true);
-    let mut output = String::new();
-    
-    while ctx.pos < ctx.source.len() {
-        let ch = match ctx.current_char() {
-            Some(c) => c,
-            None => break,
+/// Parse a whole file into its typed tree. In TypeScript mode type syntax is stripped
+/// first; node spans and error spans both refer to `source`.
+pub fn parse_module(source: &str, opts: &TranspileOptions, recover: bool) -> Result<ParsedModule> {
+    if opts.is_typescript {
+        let (stripped, map) = strip_typescript(source, opts.html_compat)?;
+        let origin = Rc::new(SourceOrigin::new(source, map));
+        return match parse_stripped(&stripped, opts, recover, origin) {
+            Ok(mut parsed) => {
+                parsed.diagnostics = parsed.diagnostics.into_iter().map(|d| d.remapped(&stripped, source)).collect();
+                Ok(parsed)
+            }
+            Err(e) => Err(match e.downcast::<TranspileError>() {
+                Ok(err) => err.remapped(&stripped, source).into(),
+                Err(other) => other,
+            }),
         };
-        
-        // Handle strings
-        if ch == '"' || ch == '\'' || ch == '`' {
-            let quote = ch;
-            output.push(quote);
-            ctx.advance();
-            while let Some(c) = ctx.current_char() {
-                output.push(c);
-                ctx.advance();
-                if c == '\\' {
-                    if let Some(c2) = ctx.current_char() {
-                        output.push(c2);
-                        ctx.advance();
-                    }
-                } else if c == quote {
+    }
+
+    // Strict JavaScript mode: TypeScript-only syntax is reported as the code is scanned
+    let origin = Rc::new(SourceOrigin::new(source, OffsetMap::default()));
+    let mut parsed = parse_stripped(source, opts, recover, origin)?;
+    // TypeScript diagnostics lead, as the likelier cause of any syntax errors
+    parsed.diagnostics.sort_by_key(|d| d.kind != ErrorKind::TypeScript);
+    Ok(parsed)
+}
+
+/// Parse source that contains no TypeScript syntax
+fn parse_stripped(
+    source: &str,
+    opts: &TranspileOptions,
+    recover: bool,
+    origin: Rc<SourceOrigin>,
+) -> Result<ParsedModule> {
+    let mut ctx = ParseContext::new(source, opts.is_typescript);
+    ctx.recover = recover;
+    ctx.html_compat = opts.html_compat;
+    ctx.origin = origin;
+    let mut scan = ScannedCode::default();
+    let mut items = ModuleItems::default();
+    scan_code(&mut ctx, &mut scan, Some(&mut items), None)?;
+
+    let module = Module {
+        imports: items.imports,
+        exports: items.exports,
+        jsx: scan.jsx,
+        span: ctx.span(0, source.len()),
+    };
+    Ok(ParsedModule {
+        module,
+        text: source.to_string(),
+        holes: scan.holes,
+        diagnostics: ctx.diagnostics,
+        origin: ctx.origin,
+    })
+}
+
+/// Walk JavaScript from `ctx.pos` to `ctx.end`, parsing every JSX element outside strings,
+/// comments and regex literals into `scan`, and in JavaScript mode reporting TypeScript-only
+/// syntax. Nested code (template interpolations, `{expr}` containers) is scanned in the
+/// same pass. At the top level of a file `items` collects imports and exports. With `close`
+/// set the scan instead stops at the unmatched `close` bracket, leaving `ctx.pos` on it.
+fn scan_code(
+    ctx: &mut ParseContext,
+    scan: &mut ScannedCode,
+    mut items: Option<&mut ModuleItems>,
+    close: Option<char>,
+) -> Result<()> {
+    let mut depth = 0isize;
+    while let Some(token) = ctx.next_token() {
+        if !ctx.is_typescript {
+            check_typescript_token(ctx, &token)?;
+        }
+        match token.kind {
+            TokenKind::Punct if close.is_some() && matches!(token.text(ctx.source), "(" | "[" | "{") => depth += 1,
+            TokenKind::Punct if close.is_some() && matches!(token.text(ctx.source), ")" | "]" | "}") => {
+                if depth == 0 && close.is_some_and(|close| token.is_punct(ctx.source, close)) {
+                    ctx.pos = token.start;
                     break;
                 }
+                depth -= 1;
             }
-            continue;
-        }
-        
-        // Handle comments
-        if ch == '/' {
-            if ctx.peek(1) == Some('/') {
-                while let Some(c) = ctx.current_char() {
-                    output.push(c);
-                    ctx.advance();
-                    if c == '\n' { break; }
+            // Template interpolations are code; strings, comments and regexes are skipped whole
+            TokenKind::Template => {
+                for range in lexer::template_interpolations(ctx.source, &token) {
+                    let nested = scan_nested(ctx, range.start, range.end)?;
+                    scan.holes.extend(nested.holes.into_iter().map(|(range, node)| {
+                        (range, node.map(|index| index + scan.jsx.len()))
+                    }));
+                    scan.jsx.extend(nested.jsx);
+                    ctx.consume('}')?;
                 }
-                continue;
-            } else if ctx.peek(1) == Some('*') {
-                while let Some(c) = ctx.current_char() {
-                    output.push(c);
-                    ctx.advance();
-                    if c == '*' && ctx.current_char() == Some('/') {
-                        output.push('/');
-                        ctx.advance();
+                ctx.pos = token.end;
+            }
+            TokenKind::Punct if token.is_punct(ctx.source, '<') => {
+                let start = token.start;
+                // The closing tag of an element around a container whose `}` is missing
+                if close == Some('}') && ctx.char_at(token.end) == Some('/') {
+                    ctx.pos = start;
+                    if ctx.enclosing_tags.iter().any(|tag| closes_immediately(ctx, tag)) {
                         break;
                     }
+                    ctx.pos = token.end;
                 }
-                continue;
+                match classify_angle_bracket(ctx, &token) {
+                    AngleBracket::Jsx => {}
+                    // Type arguments are only left in JavaScript mode, where they are an error
+                    AngleBracket::Types { end } if !ctx.is_typescript => {
+                        let err = ctx.error_at(ErrorCode::TypeScriptGeneric, "Unexpected TypeScript generic", start, end);
+                        ctx.report(err)?;
+                        continue;
+                    }
+                    _ => continue,
+                }
+                ctx.pos = start;
+                match parse_jsx_element(ctx) {
+                    Ok(node) => {
+                        scan.holes.push((start..ctx.pos, Some(scan.jsx.len())));
+                        scan.jsx.push(node);
+                    }
+                    Err(e) => {
+                        ctx.report(e)?;
+                        ctx.open_tags.clear();
+                        ctx.resync_statement(start);
+                        scan.holes.push((start..ctx.pos, None));
+                    }
+                }
+                ctx.regex_allowed = false;
             }
+            // Identifiers are read whole so `import`/`export` are only seen as separate words
+            TokenKind::Identifier => {
+                let start = token.start;
+                let after_dot = ctx.source[..start].ends_with('.');
+                if let Some(items) = items.as_deref_mut()
+                    && !after_dot
+                {
+                    let keyword_end = ctx.pos;
+                    match ctx.word_in(start, keyword_end, &["import", "export"]) {
+                        Some("import") => {
+                            if let Some(import) = parse_import_declaration(ctx, start) {
+                                items.imports.push(import);
+                            }
+                        }
+                        Some(_) => {
+                            if let Some(export) = parse_export_declaration(ctx, start) {
+                                items.exports.push(export);
+                            }
+                        }
+                        None => {}
+                    }
+                    // Declarations are read by lookahead; JSX inside them is scanned as usual
+                    ctx.pos = keyword_end;
+                }
+            }
+            _ => {}
         }
+    }
+    Ok(())
+}
 
-        // Handle keywords
-        if ch.is_alphabetic() {
-            let start = ctx.pos;
-            while let Some(c) = ctx.current_char() {
-                if c.is_alphanumeric() || c == '_' {
-                    ctx.advance();
-                } else {
-                    break;
+/// Scan the JavaScript in the byte range `start..end` as its own unit, then leave
+/// `ctx.pos` at `end`. JSX inside it cannot close elements outside it.
+fn scan_nested(ctx: &mut ParseContext, start: usize, end: usize) -> Result<ScannedCode> {
+    ctx.within(start, end, |ctx| {
+        let mut scan = ScannedCode::default();
+        scan_code(ctx, &mut scan, None, None)?;
+        Ok(scan)
+    })
+}
+
+/// Parse the `{expr}` contents starting at `ctx.pos` in the same pass that finds their end,
+/// leaving `ctx.pos` at the closing `}`, or at the closing tag of an enclosing element if
+/// the `}` is missing. `None` for a JSX empty expression (only whitespace and comments).
+fn parse_expression(ctx: &mut ParseContext) -> Result<Option<Expression>> {
+    ctx.skip_whitespace();
+    let start = ctx.pos;
+    let outer_tags = std::mem::take(&mut ctx.open_tags);
+    let enclosing_count = ctx.enclosing_tags.len();
+    ctx.enclosing_tags.extend(outer_tags.iter().cloned());
+    let outer_regex_allowed = std::mem::replace(&mut ctx.regex_allowed, true);
+    let mut scan = ScannedCode::default();
+    let result = scan_code(ctx, &mut scan, None, Some('}'));
+    ctx.open_tags = outer_tags;
+    ctx.enclosing_tags.truncate(enclosing_count);
+    ctx.regex_allowed = outer_regex_allowed;
+    result?;
+
+    if is_empty_expression(ctx.source, start, ctx.pos) {
+        return Ok(None);
+    }
+    let end = start + ctx.slice(start, ctx.pos).trim_end().len();
+    let holes = scan.holes.into_iter().map(|(range, node)| (range.start - start..range.end - start, node)).collect();
+    Ok(Some(Expression {
+        source: ctx.slice(start, end).to_string(),
+        offset: start,
+        jsx: scan.jsx,
+        span: ctx.span(start, end),
+        holes,
+    }))
+}
+
+/// Strip TypeScript syntax, also returning where each part of the output came from. In
+/// HTML-compatibility mode unclosed void elements such as `<br>` are stepped over.
+pub fn strip_typescript(source: &str, html_compat: bool) -> Result<(String, OffsetMap)> {
+    let mut ctx = ParseContext::new(source, true);
+    ctx.html_compat = html_compat;
+    let mut output = MappedText::default();
+    strip_code(&mut ctx, &mut output)?;
+    Ok(output.into_parts())
+}
+
+/// Strip TypeScript syntax from `ctx.pos` to `ctx.end` into `output`
+fn strip_code(ctx: &mut ParseContext, output: &mut MappedText) -> Result<()> {
+    // Byte offset just past the `<` of the last opening tag
+    let mut tag_open = None;
+    while let Some(token) = ctx.next_token() {
+        match token.kind {
+            // Template interpolations are code: strip them on their own
+            TokenKind::Template => {
+                let mut copied = token.start;
+                for range in lexer::template_interpolations(ctx.source, &token) {
+                    output.push_str(ctx.slice(copied, range.start));
+                    output.anchor(range.start);
+                    ctx.within(range.start, range.end, |ctx| strip_code(ctx, output))?;
+                    output.anchor(range.end);
+                    copied = range.end;
                 }
+                output.push_str(ctx.slice(copied, token.end));
+                ctx.pos = token.end;
+                continue;
             }
-            let word = ctx.slice(start, ctx.pos);
-            if word == "interface" || word == "enum" {
-                // Skip the name
-                ctx.skip_whitespace();
-                while let Some(c) = ctx.current_char() {
-                    if c.is_alphanumeric() || c == '_' { ctx.advance(); }
-                    else { break; }
-                }
-                ctx.skip_whitespace();
-                if ctx.current_char() == Some('{') {
-                    ctx.advance();
-                    let _ = parse_js_expression(&mut ctx, '}');
-                    ctx.consume('}').ok();
+
+            // Handle keywords
+            TokenKind::Identifier => {
+                let start = token.start;
+                // `as` in `import { a as b }` and `export * as ns` renames, it is not a cast
+                if let Some(end) = specifier_clause_end(ctx, &token) {
+                    output.push_str(ctx.slice(start, end));
+                    ctx.pos = end;
+                    ctx.regex_allowed = false;
+                    continue;
                 }
-                continue;
-            } else if word == "type" {
-                // Check if it's 'type name =' or just a variable named 'type'
-                let saved_pos = ctx.pos;
-                ctx.skip_whitespace();
-                let mut is_type_decl = false;
-                if let Some(c) = ctx.current_char() {
-                    if c.is_alphabetic() {
-                        while let Some(c2) = ctx.current_char() {
-                            if c2.is_alphanumeric() || c2 == '_' { ctx.advance(); }
-                            else { break; }
+                let keywords = ["interface", "enum", "type", "as", "public", "private", "protected", "readonly", "abstract"];
+                let word = ctx.word_in(start, token.end, &keywords).unwrap_or_default();
+                if word == "interface" || word == "enum" {
+                    // Skip the name
+                    ctx.skip_whitespace();
+                    while let Some(c) = ctx.current_char() {
+                        if is_identifier_char(c) { ctx.advance(); }
+                        else { break; }
+                    }
+                    ctx.skip_whitespace();
+                    if ctx.current_char() == Some('{') {
+                        ctx.advance();
+                        skip_js_expression(ctx, '}');
+                        ctx.consume('}').ok();
+                    }
+                    output.push_str(&ctx.newlines_in(start, ctx.pos));
+                    output.anchor(ctx.pos);
+                    ctx.regex_allowed = true;
+                    continue;
+                } else if word == "type" {
+                    // Check if it's 'type name =' or just a variable named 'type'
+                    let saved_pos = ctx.pos;
+                    ctx.skip_whitespace();
+                    let mut is_type_decl = false;
+                    if ctx.current_char().is_some_and(is_identifier_start) {
+                        while ctx.current_char().is_some_and(is_identifier_char) {
+                            ctx.advance();
                         }
                         ctx.skip_whitespace();
                         if ctx.current_char() == Some('=') {
                             is_type_decl = true;
                         }
                     }
-                }
-                
-                if is_type_decl {
-                    let _ = parse_js_expression(&mut ctx, ';');
-                    ctx.consume(';').ok();
-                    continue;
-                } else {
-                    ctx.pos = saved_pos;
-                }
-            } else if word == "as" {
-                // Only treat as 'as' if followed by a type-looking thing
-                let saved_pos = ctx.pos;
-                ctx.skip_whitespace();
-                if let Some(c) = ctx.current_char() {
-                    if c.is_alphabetic() || c == '{' || c == '[' {
-                        skip_type_at_pos(&mut ctx);
+                    
+                    if is_type_decl {
+                        skip_js_expression(ctx, ';');
+                        ctx.consume(';').ok();
+                        output.push_str(&ctx.newlines_in(start, ctx.pos));
+                        output.anchor(ctx.pos);
+                        ctx.regex_allowed = true;
+                        continue;
+                    } else {
+                        ctx.pos = saved_pos;
+                    }
+                } else if word == "as" {
+                    // Only treat as 'as' if followed by a type-looking thing
+                    let saved_pos = ctx.pos;
+                    ctx.skip_whitespace();
+                    if ctx.current_char().is_some_and(|c| c.is_alphabetic() || c == '{' || c == '[') {
+                        skip_type_at_pos(ctx);
                         output.push(' ');
+                        output.push_str(&ctx.newlines_in(saved_pos, ctx.pos));
+                        output.anchor(ctx.pos);
+                        ctx.regex_allowed = false;
                         continue;
                     }
-                }
-                ctx.pos = saved_pos;
-            } else if word == "public" || word == "private" || word == "protected" || word == "readonly" || word == "abstract" {
-                // Check if it's a modifier or a variable
-                let saved_pos = ctx.pos;
-                ctx.skip_whitespace();
-                if let Some(c) = ctx.current_char() {
-                    if c.is_alphabetic() {
+                    ctx.pos = saved_pos;
+                } else if word == "public" || word == "private" || word == "protected" || word == "readonly" || word == "abstract" {
+                    // Check if it's a modifier or a variable
+                    let saved_pos = ctx.pos;
+                    ctx.skip_whitespace();
+                    if ctx.current_char().is_some_and(char::is_alphabetic) {
                         // Likely a modifier, skip it
+                        output.anchor(ctx.pos);
                         continue;
                     }
+                    ctx.pos = saved_pos;
                 }
-                ctx.pos = saved_pos;
             }
-            output.push_str(&word);
-            continue;
-        }
-        
-        // Handle type annotations
-        if ch == ':' {
-            let saved_pos = ctx.pos;
-            ctx.advance();
-            ctx.skip_whitespace();
-            if let Some(_) = ctx.current_char() {
-                // Heuristic: if it looks like a type, skip it.
-                let type_start = ctx.pos;
-                let mut word = String::new();
-                while let Some(c) = ctx.current_char() {
-                    if c.is_alphanumeric() || c == '_' { 
-                        word.push(c);
+
+            // Handle type annotations
+            TokenKind::Punct if token.is_punct(ctx.source, ':') => {
+                let saved_pos = token.start;
+                ctx.skip_whitespace();
+                if ctx.current_char().is_some() {
+                    // Heuristic: if it looks like a type, skip it.
+                    let type_start = ctx.pos;
+                    while ctx.current_char().is_some_and(|c| c.is_alphanumeric() || c == '_') {
                         ctx.advance();
-                    } else { break; }
+                    }
+                    
+                    let builtins = ["string", "number", "boolean", "any", "void", "unknown", "never", "object"];
+                    let is_builtin = ctx.word_in(type_start, ctx.pos, &builtins).is_some();
+                    
+                    let is_type = is_builtin || (ctx.pos > type_start && ctx.char_at(type_start).is_some_and(char::is_uppercase));
+                    
+                    if is_type {
+                        // It looks like a type! Skip until terminator
+                        ctx.pos = type_start;
+                        skip_type_at_pos(ctx);
+                        output.push(' ');
+                        output.push_str(&ctx.newlines_in(saved_pos, ctx.pos));
+                        output.anchor(ctx.pos);
+                        ctx.regex_allowed = false;
+                        continue;
+                    }
                 }
-                
-                let is_builtin = match word.as_str() {
-                    "string" | "number" | "boolean" | "any" | "void" | "unknown" | "never" | "object" => true,
-                    _ => false
-                };
-                
-                let is_type = is_builtin || (word.len() > 0 && word.chars().next().unwrap().is_uppercase());
-                
-                if is_type {
-                    // It looks like a type! Skip until terminator
-                    ctx.pos = type_start;
-                    skip_type_at_pos(&mut ctx);
+                ctx.pos = token.end;
+            }
+
+            // Handle generics
+            TokenKind::Punct if token.is_punct(ctx.source, '<') => {
+                let class = classify_angle_bracket(ctx, &token);
+                // `<Select<Option> …>`: type arguments of the element's tag name
+                let before = ctx.source[..token.start].trim_end();
+                let name_start = before.trim_end_matches(|c| is_jsx_name_char(c) || c == '.').len();
+                let tag_arguments = (name_start < before.len() && Some(name_start) == tag_open)
+                    .then(|| type_list_end(ctx, token.end))
+                    .flatten();
+                if matches!(class, AngleBracket::Jsx) {
+                    tag_open = Some(token.end);
+                }
+                if let Some(end) = tag_arguments.or(match class {
+                    AngleBracket::Types { end } => Some(end),
+                    _ => None,
+                }) {
                     output.push(' ');
+                    output.push_str(&ctx.newlines_in(token.start, end));
+                    output.anchor(end);
+                    ctx.pos = end;
+                    ctx.regex_allowed = false;
                     continue;
-                } else {
-                    ctx.pos = saved_pos;
                 }
-            } else {
-                ctx.pos = saved_pos;
             }
-        }
-        
-        // Handle generics
-        if ch == '<' && !is_jsx_start(&ctx) {
-             let saved_pos = ctx.pos;
-             ctx.advance();
-             skip_type_at_pos(&mut ctx);
-             if ctx.current_char() == Some('>') {
-                 ctx.advance();
-                 output.push(' ');
-                 continue;
-             }
-             ctx.pos = saved_pos;
-        }
-
-        // Handle non-null assertion
-        if ch == '!' && ctx.peek(1).map_or(false, |c| !c.is_alphanumeric() && c != '=' && c != '!' && !c.is_whitespace()) {
-             // Likely a non-null assertion, skip it
-             ctx.advance();
-             continue;
-        }
-
-        if let Some(c) = ctx.current_char() {
-            output.push(c);
-            ctx.advance();
-        }
-    }
-    
-    let output = output;
-    if source.len() > 0 && output.len() == 0 {
-         // This would be weird
-    }
-    Ok(output)
-}
 
-fn check_for_typescript_syntax(source: &str) -> Result<()> {
-    let mut ctx = ParseContext::new(source.to_string(), false);
-    
-    while ctx.pos < ctx.source.len() {
-        let ch = match ctx.current_char() {
-            Some(c) => c,
-            None => break,
-        };
-        
-        // Handle strings to skip them
-        if ch == '"' || ch == '\'' || ch == '`' {
-            let quote = ch;
-            ctx.advance();
-            while let Some(c) = ctx.current_char() {
-                ctx.advance();
-                if c == '\\' {
-                    ctx.advance();
-                } else if c == quote {
-                    break;
-                }
-            }
-            continue;
-        }
-        
-        // Handle comments
-        if ch == '/' {
-            if ctx.peek(1) == Some('/') {
-                while let Some(c) = ctx.current_char() {
-                    ctx.advance();
-                    if c == '\n' { break; }
-                }
-                continue;
-            } else if ctx.peek(1) == Some('*') {
-                while let Some(c) = ctx.current_char() {
-                    ctx.advance();
-                    if c == '*' && ctx.current_char() == Some('/') {
-                        ctx.advance();
-                        break;
-                    }
-                }
+            // Handle non-null assertion
+            TokenKind::Punct
+                if token.is_punct(ctx.source, '!')
+                    && ctx.current_char().is_some_and(|c| !c.is_alphanumeric() && c != '=' && c != '!' && !c.is_whitespace()) =>
+            {
+                // Likely a non-null assertion, skip it
+                output.anchor(ctx.pos);
+                ctx.regex_allowed = false;
                 continue;
             }
-        }
 
-        // Handle JSX elements - skip over them entirely since keywords in JSX text are not code
-        if ch == '<' && is_jsx_start(&ctx) {
-            skip_jsx_element(&mut ctx)?;
-            continue;
+            _ => {}
         }
 
-        // Handle keywords
-        if ch.is_alphabetic() {
-            let start = ctx.pos;
-            while let Some(c) = ctx.current_char() {
-                if c.is_alphanumeric() || c == '_' {
-                    ctx.advance();
-                } else {
-                    break;
-                }
+        output.push_str(token.text(ctx.source));
+    }
+    Ok(())
+}
+
+/// Byte offset just past the bindings of the `import` or `export` keyword `token`, up to
+/// its `from` or the end of its `{ … }` list: `import D, { a as b }`, `import * as ns`,
+/// `export { a as default }` or `export * as ns`. `None` for other uses of the words.
+fn specifier_clause_end(ctx: &ParseContext, token: &Token) -> Option<usize> {
+    let word = ctx.word_in(token.start, token.end, &["import", "export"])?;
+    if ctx.source[..token.start].ends_with('.') {
+        return None;
+    }
+    let mut end = None;
+    let mut tokens = lexer::Lexer::new(ctx.source, token.end, ctx.end);
+    while let Some(next) = tokens.find(|next| !next.is_trivia()) {
+        let text = next.text(ctx.source);
+        match next.kind {
+            TokenKind::Punct if text == "{" => {
+                return Some((lexer::closing_bracket(ctx.source, next.end, ctx.end) + 1).min(ctx.end));
             }
-            let word = ctx.slice(start, ctx.pos);
-            
-            // Only flag keywords if they're actual standalone words (not part of larger identifiers)
-            // Check that the character before was not alphanumeric or underscore
-            let has_valid_prefix = if start == 0 {
-                true
-            } else {
-                let prev_char = ctx.source.get(start - 1).copied();
-                match prev_char {
-                    Some(c) if c.is_alphanumeric() || c == '_' => false,
-                    _ => true,
-                }
+            TokenKind::Punct if text == "*" || (text == "," && word == "import") => {}
+            // A default import, or the `as ns` after `*`
+            TokenKind::Identifier if text != "from" && (word == "import" || end.is_some()) => {}
+            _ => return end,
+        }
+        end = Some(next.end);
+    }
+    end
+}
+
+/// Report `token` if it starts TypeScript-only syntax, for JavaScript mode. Type argument
+/// lists are reported where angle brackets are classified; `ctx.pos` is left after `token`.
+fn check_typescript_token(ctx: &mut ParseContext, token: &Token) -> Result<()> {
+    match token.kind {
+        // Handle keywords; identifiers are lexed whole, so these never match part of a longer name
+        TokenKind::Identifier => {
+            let start = token.start;
+            // Note: 'as' is valid ES6 syntax in import/export statements like "import { x as y }"
+            let keywords = ["type", "interface", "enum", "public", "private", "protected", "readonly"];
+            let Some(word) = ctx.word_in(start, token.end, &keywords) else {
+                return Ok(());
             };
-            
-            if !has_valid_prefix {
-                // This word is part of a larger identifier, not a keyword
-                continue;
-            }
-            
             if word == "type" {
                 // Distinguish between `type Foo =` (TS) and property names like `type:` inside objects/JSX text.
                 let mut looks_like_type_alias = false;
-                let saved = ctx.pos;
                 ctx.skip_whitespace();
-                if let Some(c) = ctx.current_char() {
-                    if c.is_alphabetic() {
-                        while let Some(c2) = ctx.current_char() {
-                            if c2.is_alphanumeric() || c2 == '_' { ctx.advance(); } else { break; }
-                        }
-                        ctx.skip_whitespace();
-                        if ctx.current_char() == Some('=') {
-                            looks_like_type_alias = true;
-                        }
+                if ctx.current_char().is_some_and(is_identifier_start) {
+                    while ctx.current_char().is_some_and(is_identifier_char) {
+                        ctx.advance();
                     }
+                    ctx.skip_whitespace();
+                    looks_like_type_alias = ctx.current_char() == Some('=');
                 }
-                ctx.pos = saved;
-                if looks_like_type_alias {
-                    return Err(anyhow!("Unexpected TypeScript syntax '{}' at position {}", word, start));
+                ctx.pos = token.end;
+                if !looks_like_type_alias {
+                    return Ok(());
                 }
-                continue;
-            }
-                // Note: 'as' is valid ES6 syntax in import/export statements like "import { x as y }"
-                // Only reject it if used for TypeScript type assertions (handled separately below)
-                if word == "interface" || word == "enum" || 
-               word == "public" || word == "private" || word == "protected" || word == "readonly" {
-                return Err(anyhow!("Unexpected TypeScript syntax '{}' at position {}", word, start));
             }
-            continue;
+            let err = ctx.error_at(ErrorCode::TypeScriptSyntax, &format!("Unexpected TypeScript syntax '{}'", word), start, token.end);
+            ctx.report(err)?;
         }
 
         // Check for type annotations (colon but NOT object literal/destructuring)
-        if ch == ':' {
-            let saved_pos = ctx.pos;
-            // A colon is TS if it's NOT in an object literal or destructuring.
-            // This is hard to detect perfectly without a full parser.
-            // But we can look at the preceding context or following.
-            // Actually, in JS, a colon only appears in:
-            // 1. { key: value }
-            // 2. label: statement
-            // 3. ternary ? true : false
-            // 4. switch case:
-            
-            // Heuristic: If it's followed by a type-looking thing and NOT followed by something that looks like an object value or ternary branch.
-            // Let's simplify: if it looks like ': string', ': number', etc.
-            ctx.advance();
+        TokenKind::Punct if token.is_punct(ctx.source, ':') => {
+            // In JS a colon only appears in object literals, labels, conditionals and
+            // `case`s, so a builtin type name followed by a type terminator is an annotation
             ctx.skip_whitespace();
-            if ctx.current_char().is_some() {
-                let mut word = String::new();
-                while let Some(c) = ctx.current_char() {
-                    if c.is_alphanumeric() || c == '_' {
-                        word.push(c);
-                        ctx.advance();
-                    } else { break; }
-                }
-
-                let is_builtin = matches!(word.as_str(),
-                    "string" | "number" | "boolean" | "any" | "void" | "unknown" | "never" | "object"
-                );
+            let word_start = ctx.pos;
+            while ctx.current_char().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                ctx.advance();
+            }
+            let builtins = ["string", "number", "boolean", "any", "void", "unknown", "never", "object"];
+            let is_builtin = ctx.word_in(word_start, ctx.pos, &builtins).is_some();
 
-                // Check the next non-whitespace character to reduce false positives (e.g. JSX text like "Status: Ready")
-                let mut peek_pos = ctx.pos;
-                while let Some(c) = ctx.source.get(peek_pos) {
-                    if c.is_whitespace() { peek_pos += 1; continue; }
-                    break;
-                }
-                let next_non_ws = ctx.source.get(peek_pos).copied();
-                let type_terminated = matches!(next_non_ws, Some(',') | Some(';') | Some('=') | Some(')') | Some('>') | Some('{') | Some('}') | Some('|') | Some('&'));
+            // Check the next non-whitespace character to reduce false positives (e.g. JSX text like "Status: Ready")
+            let next_non_ws = ctx.slice(ctx.pos, ctx.end).trim_start().chars().next();
+            let type_terminated = matches!(next_non_ws, Some(',' | ';' | '=' | ')' | '>' | '{' | '}' | '|' | '&'));
 
-                if is_builtin && type_terminated {
-                     return Err(ctx.error("Unexpected TypeScript type annotation"));
-                }
+            if is_builtin && type_terminated {
+                let err = ctx.error_at(ErrorCode::TypeScriptAnnotation, "Unexpected TypeScript type annotation", token.start, ctx.pos);
+                ctx.report(err)?;
             }
-            ctx.pos = saved_pos;
+            ctx.pos = token.end;
         }
 
-        // Handle generics (e.g., <T>)
-        if ch == '<' && !is_jsx_start(&ctx) {
-             // If it's not JSX and it's < something >, it might be a generic
-             let saved_pos = ctx.pos;
-             ctx.advance();
-             let mut word = String::new();
-             while let Some(c) = ctx.current_char() {
-                 if c.is_alphanumeric() || c == '_' {
-                     word.push(c);
-                     ctx.advance();
-                 } else { break; }
-             }
-             if word.len() > 0 && ctx.current_char() == Some('>') {
-                  return Err(anyhow!("Unexpected TypeScript generic at position {}", saved_pos));
-             }
-             ctx.pos = saved_pos;
-        }
-
-        ctx.advance();
+        _ => {}
     }
     Ok(())
 }
@@ -570,77 +909,56 @@ fn skip_jsx_element(ctx: &mut ParseContext) -> Result<()> {
     }
     
     // Skip tag name
-    while let Some(ch) = ctx.current_char() {
-        if ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == '.' {
-            ctx.advance();
-        } else {
-            break;
-        }
-    }
+    ctx.skip_whitespace();
+    let tag_name = parse_jsx_name(ctx);
     
     // Skip attributes/props
-    while ctx.current_char() != Some('>') && ctx.current_char() != Some('/') {
-        ctx.skip_whitespace();
+    loop {
+        skip_tag_trivia(ctx);
         
-        if ctx.current_char() == Some('>') || ctx.current_char() == Some('/') {
+        if matches!(ctx.current_char(), None | Some('>') | Some('/')) {
             break;
         }
+
+        // Skip spread props {...obj}
+        if ctx.current_char() == Some('{') {
+            skip_braced(ctx);
+            continue;
+        }
         
         // Skip prop name
-        while let Some(ch) = ctx.current_char() {
-            if ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == ':' {
-                ctx.advance();
-            } else {
-                break;
-            }
-        }
+        let name_start = ctx.pos;
+        parse_jsx_attr_name(ctx);
         
-        ctx.skip_whitespace();
+        skip_tag_trivia(ctx);
+
+        // Step over anything that is neither a name nor a value so malformed tags cannot stall
+        if ctx.pos == name_start && !matches!(ctx.current_char(), Some('=') | Some('>') | Some('/')) {
+            ctx.advance();
+            continue;
+        }
         
         // Skip prop value if it exists
         if ctx.current_char() == Some('=') {
             ctx.advance();
             ctx.skip_whitespace();
             
-            if ctx.current_char() == Some('"') || ctx.current_char() == Some('\'') || ctx.current_char() == Some('`') {
+            if ctx.current_char() == Some('<') {
+                skip_jsx_element(ctx)?;
+            } else if ctx.current_char() == Some('"') || ctx.current_char() == Some('\'') || ctx.current_char() == Some('`') {
                 let quote = ctx.current_char().unwrap();
                 ctx.advance();
                 while let Some(c) = ctx.current_char() {
                     ctx.advance();
                     if c == '\\' {
                         ctx.advance();
-                    } else if c == quote {
-                        break;
-                    }
-                }
-            } else if ctx.current_char() == Some('{') {
-                // Skip {expr} prop value
-                ctx.advance();
-                let mut depth = 1;
-                while let Some(ch) = ctx.current_char() {
-                    if ch == '{' {
-                        depth += 1;
-                    } else if ch == '}' {
-                        depth -= 1;
-                        if depth == 0 {
-                            ctx.advance();
-                            break;
-                        }
-                    } else if ch == '"' || ch == '\'' || ch == '`' {
-                        let q = ch;
-                        ctx.advance();
-                        while let Some(c) = ctx.current_char() {
-                            ctx.advance();
-                            if c == '\\' {
-                                ctx.advance();
-                            } else if c == q {
-                                break;
-                            }
-                        }
-                        continue;
+                    } else if c == quote {
+                        break;
                     }
-                    ctx.advance();
                 }
+            } else if ctx.current_char() == Some('{') {
+                // Skip {expr} prop value
+                skip_braced(ctx);
             }
         }
     }
@@ -648,58 +966,41 @@ fn skip_jsx_element(ctx: &mut ParseContext) -> Result<()> {
     // Check for self-closing tag
     if ctx.current_char() == Some('/') {
         ctx.advance();
+        ctx.skip_whitespace();
         ctx.consume('>')?;
         return Ok(());
     }
     
     // Consume opening >
     ctx.consume('>')?;
-    
-    // Get the tag name to match closing tag
-    // We need to parse it from before, but for now we'll just skip children until we find a closing tag
-    // This is a simplified approach - we scan backwards to find the tag name
-    let mut tag_name = String::new();
-    let mut tag_pos = ctx.pos.saturating_sub(1);
-    
-    // Go back from the > we just consumed
-    while tag_pos > 0 && ctx.source[tag_pos] != '<' {
-        tag_pos -= 1;
-    }
-    
-    if tag_pos < ctx.pos && ctx.source[tag_pos] == '<' {
-        tag_pos += 1; // Skip the <
-        while tag_pos < ctx.source.len() {
-            let c = ctx.source[tag_pos];
-            if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
-                tag_name.push(c);
-                tag_pos += 1;
-            } else {
-                break;
-            }
-        }
+
+    if ctx.html_compat && html_compat::is_void_element(&tag_name) && !closes_immediately(ctx, &tag_name) {
+        return Ok(());
     }
     
     skip_jsx_children(ctx, &tag_name)
 }
 
+/// Skip a `{...}` expression container, including nested braces, strings and comments
+fn skip_braced(ctx: &mut ParseContext) {
+    ctx.advance();
+    skip_js_expression(ctx, '}');
+    if ctx.current_char() == Some('}') {
+        ctx.advance();
+    }
+}
+
 /// Skip JSX children until the closing tag is found
 fn skip_jsx_children(ctx: &mut ParseContext, _parent_tag: &str) -> Result<()> {
     loop {
-        ctx.skip_whitespace();
-        
         // Check for closing tag
         if ctx.current_char() == Some('<') && ctx.peek(1) == Some('/') {
             ctx.advance(); // <
             ctx.advance(); // /
             
             // Skip closing tag name
-            while let Some(ch) = ctx.current_char() {
-                if ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == '.' {
-                    ctx.advance();
-                } else {
-                    break;
-                }
-            }
+            ctx.skip_whitespace();
+            parse_jsx_name(ctx);
             
             ctx.skip_whitespace();
             ctx.consume('>')?;
@@ -714,32 +1015,7 @@ fn skip_jsx_children(ctx: &mut ParseContext, _parent_tag: &str) -> Result<()> {
         
         // Check for JS expression {expr}
         if ctx.current_char() == Some('{') {
-            ctx.advance();
-            let mut depth = 1;
-            while let Some(ch) = ctx.current_char() {
-                if ch == '{' {
-                    depth += 1;
-                } else if ch == '}' {
-                    depth -= 1;
-                    if depth == 0 {
-                        ctx.advance();
-                        break;
-                    }
-                } else if ch == '"' || ch == '\'' || ch == '`' {
-                    let q = ch;
-                    ctx.advance();
-                    while let Some(c) = ctx.current_char() {
-                        ctx.advance();
-                        if c == '\\' {
-                            ctx.advance();
-                        } else if c == q {
-                            break;
-                        }
-                    }
-                    continue;
-                }
-                ctx.advance();
-            }
+            skip_braced(ctx);
             continue;
         }
         
@@ -752,7 +1028,7 @@ fn skip_jsx_children(ctx: &mut ParseContext, _parent_tag: &str) -> Result<()> {
         }
         
         // Check if we're at the end
-        if ctx.pos >= ctx.source.len() {
+        if ctx.at_end() {
             break;
         }
     }
@@ -787,375 +1063,630 @@ fn skip_type_at_pos(ctx: &mut ParseContext) {
     }
 }
 
-/// Main transpiler entry point
+/// What a `<` starts, decided the way TypeScript parses `.tsx` files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AngleBracket {
+    /// A JSX element, fragment or closing tag
+    Jsx,
+    /// Type parameters or type arguments whose `>` ends before byte offset `end`:
+    /// `<T,>(x: T) => x`, `useState<User | null>(null)`, `class Box<T> {`
+    Types { end: usize },
+    /// The less-than operator
+    LessThan,
+}
 
-fn is_jsx_start(ctx: &ParseContext) -> bool {
-    if ctx.current_char() != Some('<') {
-        return false;
+/// Classify the `<` token `token`.
+///
+/// Where an expression may start, `<` opens JSX unless it is a generic arrow function's
+/// parameter list, which TSX requires to be written `<T,>` or `<T extends U>` (either may
+/// start with `const`); `<T>(x) => x` is also accepted when an arrow really follows, as in
+/// `.ts` files. After an operand, `<`
+/// opens type arguments when a type list parses and is followed by `(`, a template, a line
+/// break, a binary operator or something that cannot start an expression; otherwise it is a
+/// comparison, so `a <b && c> d` stays two comparisons.
+fn classify_angle_bracket(ctx: &ParseContext, token: &Token) -> AngleBracket {
+    let start = token.start;
+    // A `}` may close a block, after which a statement starts; an object literal on the
+    // left of `<` is never meaningful, so treat it as the former
+    let after_brace = ctx.source[..start].trim_end().ends_with('}');
+    if !token.expression_position && !after_brace {
+        // `</` after an operand is a closing tag (or a comparison with a regex), never types
+        if ctx.char_at(start + 1) == Some('/') {
+            return AngleBracket::LessThan;
+        }
+        return match type_list_end(ctx, start + 1) {
+            Some(end) if follows_declaration_name(ctx.source, start) || can_follow_type_arguments(ctx, end) => {
+                AngleBracket::Types { end }
+            }
+            _ => AngleBracket::LessThan,
+        };
     }
 
-    // Always treat </ as JSX start (closing tag)
-    if ctx.peek(1) == Some('/') {
-        return true;
+    match ctx.char_at(start + 1) {
+        Some('>' | '/') => return AngleBracket::Jsx,
+        Some(c) if is_jsx_name_start(c) => {}
+        _ => return AngleBracket::LessThan,
     }
+    let mut tokens = lexer::Lexer::new(ctx.source, start + 1, ctx.end).filter(|t| !t.is_trivia()).peekable();
+    // `<const T,>`: a const type parameter
+    if tokens.next().is_some_and(|t| ctx.word_in(t.start, t.end, &["const"]).is_some())
+        && tokens.peek().is_some_and(|t| t.kind == TokenKind::Identifier)
+    {
+        tokens.next();
+    }
+    let generic = match tokens.next() {
+        Some(t) if t.is_punct(ctx.source, ',') => true,
+        Some(t) if t.kind == TokenKind::Identifier && ctx.word_in(t.start, t.end, &["extends"]).is_some() => {
+            // `<div extends="x">` and `<T extends>` are elements
+            !tokens.next().is_some_and(|t| matches!(ctx.char_at(t.start), Some('=' | '>' | '/')))
+        }
+        Some(t) if t.is_punct(ctx.source, '>') => arrow_follows(ctx, t.end),
+        _ => false,
+    };
+    match type_list_end(ctx, start + 1) {
+        Some(end) if generic => AngleBracket::Types { end },
+        _ => AngleBracket::Jsx,
+    }
+}
 
-    // Heuristic: if preceded by an alphanumeric character, it's likely a generic function call
-    // e.g., useState<T> or f<T>. JSX tags are usually preceded by whitespace, operators, or brackets.
-    if ctx.pos > 0 {
-        if let Some(&prev) = ctx.source.get(ctx.pos - 1) {
-            if prev.is_alphanumeric() || prev == '_' || prev == '$' {
-                return false;
+/// Byte offset just past the `>` closing a type list whose contents start at `start`, or
+/// `None` if a `;` or an unmatched closing bracket comes first
+fn type_list_end(ctx: &ParseContext, start: usize) -> Option<usize> {
+    // `<=` and `<<` are operators
+    if matches!(ctx.char_at(start), Some('=' | '<')) {
+        return None;
+    }
+    let mut tokens = lexer::Lexer::new(ctx.source, start, ctx.end);
+    let mut depth = 0usize;
+    while let Some(token) = tokens.next() {
+        if token.kind != TokenKind::Punct {
+            continue;
+        }
+        let next = ctx.char_at(token.end);
+        match token.text(ctx.source) {
+            // Logical and equality operators never appear in types
+            "&" if next == Some('&') => return None,
+            "|" if next == Some('|') => return None,
+            "=" | "!" if next == Some('=') => return None,
+            "(" | "[" | "{" => {
+                let close = lexer::closing_bracket(ctx.source, token.end, ctx.end);
+                if close >= ctx.end {
+                    return None;
+                }
+                tokens.seek(close + 1, false);
             }
+            ")" | "]" | "}" | ";" => return None,
+            "<" => depth += 1,
+            // The `>` of a function type's `=>`
+            ">" if ctx.source[..token.start].ends_with('=') => {}
+            ">" if depth == 0 && next == Some('=') => return None,
+            ">" if depth == 0 => return Some(token.end),
+            ">" => depth -= 1,
+            _ => {}
         }
     }
+    None
+}
 
-    match ctx.peek(1) {
-        Some(ch) if ch.is_alphabetic() => {
-            let mut i = 1;
-            while let Some(c) = ctx.peek(i) {
-                if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
-                    i += 1;
-                } else {
-                    break;
+/// Whether `(params) =>` or `(params): ReturnType =>` starts at byte offset `pos`
+fn arrow_follows(ctx: &ParseContext, pos: usize) -> bool {
+    let is_arrow = |t: &Token| t.is_punct(ctx.source, '=') && ctx.char_at(t.end) == Some('>');
+    let mut tokens = lexer::Lexer::new(ctx.source, pos, ctx.end);
+    let significant = |tokens: &mut lexer::Lexer| tokens.find(|t| !t.is_trivia());
+
+    let Some(open) = significant(&mut tokens).filter(|t| t.is_punct(ctx.source, '(')) else {
+        return false;
+    };
+    let close = lexer::closing_bracket(ctx.source, open.end, ctx.end);
+    if ctx.char_at(close) != Some(')') {
+        return false;
+    }
+    tokens.seek(close + 1, false);
+    match significant(&mut tokens) {
+        Some(t) if is_arrow(&t) => true,
+        // A return type annotation, up to the arrow
+        Some(t) if t.is_punct(ctx.source, ':') => {
+            let mut depth = 0usize;
+            while let Some(t) = significant(&mut tokens) {
+                if is_arrow(&t) && depth == 0 {
+                    return true;
                 }
-            }
-            let mut j = i;
-            while let Some(c) = ctx.peek(j) {
-                if c.is_whitespace() {
-                    j += 1;
-                } else {
-                    break;
+                if t.kind != TokenKind::Punct {
+                    continue;
                 }
-            }
-            let peek_j = ctx.peek(j);
-            let res = match peek_j {
-                Some('>') => {
-                    // Heuristic: if followed by '(', it's likely a generic arrow function <T>(x: T) => ...
-                    if ctx.peek(j + 1) == Some('(') {
-                        false
-                    } else {
-                        true
+                match t.text(ctx.source) {
+                    "(" | "[" | "{" => {
+                        let close = lexer::closing_bracket(ctx.source, t.end, ctx.end);
+                        tokens.seek(close + 1, false);
                     }
+                    // A closing tag: this was JSX text
+                    "<" if ctx.char_at(t.end) == Some('/') => return false,
+                    "<" => depth += 1,
+                    ">" if depth > 0 => depth -= 1,
+                    ")" | "]" | "}" | ";" | "," => return false,
+                    _ => {}
                 }
-                Some('/') => true,
-                Some(c) if c.is_alphabetic() => {
-                    // Check if it's an attribute name or part of a type
-                    // Heuristic: attributes are usually followed by = or another attribute or >
-                    // If it's a type like <User | null>, we'll see | which is handled by the next case
-                    true
-                }
-                _ => {
-                    // If we see something like <User | or <User & or <User [, it's a generic
-                    false
-                }
-            };
-            res
+            }
+            false
         }
-        Some('>') | Some('/') => true,
         _ => false,
     }
 }
 
-fn is_custom_component(tag: &str) -> bool {
-    if tag.is_empty() {
+/// TypeScript's `canFollowTypeArgumentsInExpression` for the text after a type list
+/// ending at byte offset `end`
+fn can_follow_type_arguments(ctx: &ParseContext, end: usize) -> bool {
+    let mut line_break = false;
+    let next = lexer::Lexer::new(ctx.source, end, ctx.end).find(|t| {
+        if t.is_trivia() {
+            line_break |= t.text(ctx.source).contains('\n');
+        }
+        !t.is_trivia()
+    });
+    let Some(next) = next else {
+        return true;
+    };
+    match next.kind {
+        TokenKind::Template => true,
+        TokenKind::Punct => match next.text(ctx.source) {
+            "++" | "--" => line_break,
+            "(" => true,
+            // A type list is never followed by `<` or `>`, and `+`/`-` here are unary
+            "<" | ">" | "+" | "-" => false,
+            // Can start an expression
+            "[" | "{" | "!" | "~" | "#" | "@" => line_break,
+            _ => true,
+        },
+        TokenKind::Identifier => {
+            line_break || ctx.word_in(next.start, next.end, &["in", "instanceof", "as", "satisfies"]).is_some()
+        }
+        _ => line_break,
+    }
+}
+
+/// Whether the name just before byte offset `pos` is declared or extended by `class`,
+/// `interface`, `function`, `extends` or `implements`, or names a return type (`): T`),
+/// where `<` always opens type parameters or arguments
+fn follows_declaration_name(source: &str, pos: usize) -> bool {
+    let skip_back = |i: usize, keep: &dyn Fn(char) -> bool| source[..i].trim_end_matches(keep).len();
+    let name_end = skip_back(pos, &|c| c.is_whitespace());
+    let name_start = skip_back(name_end, &|c| is_identifier_char(c) || c == '.');
+    if name_start == name_end {
         return false;
     }
-    let first_char = tag.chars().next().unwrap();
-    first_char.is_uppercase() || tag.contains('.')
+    let keyword_end = skip_back(name_start, &|c| c.is_whitespace());
+    if let Some(colon) = source[..keyword_end].strip_suffix(':') {
+        return colon.trim_end().ends_with(')');
+    }
+    let keyword_start = skip_back(keyword_end, &|c| is_identifier_char(c));
+    matches!(&source[keyword_start..keyword_end], "class" | "interface" | "function" | "extends" | "implements")
+}
+
+/// First character of a JSX identifier (any Unicode letter, `_` or `$`)
+fn is_jsx_name_start(ch: char) -> bool {
+    ch.is_alphabetic() || ch == '_' || ch == '$'
+}
+
+/// Subsequent characters of a JSX identifier, which unlike JS identifiers may contain `-`
+fn is_jsx_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '$' | '-' | '\u{200C}' | '\u{200D}')
+}
+
+fn scan_jsx_identifier(ctx: &mut ParseContext) {
+    if ctx.current_char().is_some_and(is_jsx_name_start) {
+        ctx.advance();
+        while ctx.current_char().is_some_and(is_jsx_name_char) {
+            ctx.advance();
+        }
+    }
+}
+
+/// Scan an element name: `div`, `my-element`, `svg:rect` or `Foo.Bar.Baz`
+fn parse_jsx_name(ctx: &mut ParseContext) -> String {
+    let start = ctx.pos;
+    scan_jsx_identifier(ctx);
+    if ctx.pos == start {
+        return String::new();
+    }
+    if ctx.current_char() == Some(':') && ctx.peek(1).is_some_and(is_jsx_name_start) {
+        ctx.advance();
+        scan_jsx_identifier(ctx);
+    } else {
+        while ctx.current_char() == Some('.') && ctx.peek(1).is_some_and(is_jsx_name_start) {
+            ctx.advance();
+            scan_jsx_identifier(ctx);
+        }
+    }
+    ctx.slice(start, ctx.pos).to_string()
+}
+
+/// Scan an attribute name: `id`, `aria-label` or `xlink:href`
+fn parse_jsx_attr_name(ctx: &mut ParseContext) -> String {
+    let start = ctx.pos;
+    scan_jsx_identifier(ctx);
+    if ctx.pos > start && ctx.current_char() == Some(':') && ctx.peek(1).is_some_and(is_jsx_name_start) {
+        ctx.advance();
+        scan_jsx_identifier(ctx);
+    }
+    ctx.slice(start, ctx.pos).to_string()
+}
+
+/// Skip whitespace and comments between attributes
+fn skip_tag_trivia(ctx: &mut ParseContext) {
+    loop {
+        ctx.skip_whitespace();
+        if ctx.current_char() == Some('/') && ctx.peek(1) == Some('*') {
+            ctx.advance();
+            ctx.advance();
+            while ctx.current_char().is_some() && !(ctx.current_char() == Some('*') && ctx.peek(1) == Some('/')) {
+                ctx.advance();
+            }
+            if ctx.current_char().is_some() {
+                ctx.advance();
+                ctx.advance();
+            }
+        } else if ctx.current_char() == Some('/') && ctx.peek(1) == Some('/') {
+            while ctx.current_char().is_some() && ctx.current_char() != Some('\n') {
+                ctx.advance();
+            }
+        } else {
+            break;
+        }
+    }
 }
 
-fn parse_jsx_element(ctx: &mut ParseContext) -> Result<String> {
+fn parse_jsx_element(ctx: &mut ParseContext) -> Result<JsxNode> {
+    let element_start = ctx.pos;
     ctx.consume('<')?;
-    
+
     // Handle fragments <>...</>
     if ctx.current_char() == Some('>') {
         ctx.advance();
-        return parse_fragment(ctx);
+        return parse_fragment(ctx, element_start).map(JsxNode::Fragment);
     }
-    
+
     // Handle closing tag (shouldn't happen at top level, but handle gracefully)
     if ctx.current_char() == Some('/') {
-        return Err(anyhow!("Unexpected closing tag at position {}", ctx.pos));
+        return Err(ctx.error_at(ErrorCode::UnexpectedClosingTag, "Unexpected closing tag", ctx.pos - 1, ctx.pos + 1));
     }
-    
+
     // Parse tag name
-    let tag_start = ctx.pos;
-    while let Some(ch) = ctx.current_char() {
-        if ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == '.' {
-            ctx.advance();
-        } else {
-            break;
-        }
-    }
-    let tag_name = ctx.slice(tag_start, ctx.pos);
-    
     ctx.skip_whitespace();
-    
+    let tag_name = parse_jsx_name(ctx);
+    let name_end = ctx.pos;
+
+    skip_tag_trivia(ctx);
+
     // Parse props
-    let (props, key) = parse_props(ctx)?;
-    
-    ctx.skip_whitespace();
-    
+    let attributes = parse_props(ctx)?;
+
+    skip_tag_trivia(ctx);
+
     // Check for self-closing tag
     if ctx.current_char() == Some('/') {
         ctx.advance();
+        ctx.skip_whitespace();
         ctx.consume('>')?;
-        let tag_value = if tag_name == "Fragment" {
-            "__hook_jsx_runtime.Fragment".to_string()
-        } else if is_custom_component(&tag_name) {
-            tag_name
-        } else {
-            format!("\"{}\"", tag_name)
-        };
-        
-        if let Some(k) = key {
-            return Ok(format!(
-                "__hook_jsx_runtime.jsx({}, {}, {})",
-                tag_value,
-                props,
-                k
-            ));
-        } else {
-            return Ok(format!(
-                "__hook_jsx_runtime.jsx({}, {})",
-                tag_value,
-                props
-            ));
-        }
+        return Ok(JsxNode::Element(JsxElement {
+            name: JsxElementName::parse(&tag_name),
+            attributes,
+            children: Vec::new(),
+            self_closing: true,
+            span: ctx.span(element_start, ctx.pos),
+        }));
     }
-    
+
     ctx.consume('>')?;
-    
+
+    // HTML leaves void elements unclosed: `<br>` has no children or closing tag
+    if ctx.html_compat && html_compat::is_void_element(&tag_name) && !closes_immediately(ctx, &tag_name) {
+        ctx.warn_at(
+            ErrorCode::HtmlCompat,
+            &format!("Closed HTML void element <{}>; JSX needs <{} />", tag_name, tag_name),
+            element_start,
+            ctx.pos,
+        );
+        return Ok(JsxNode::Element(JsxElement {
+            name: JsxElementName::parse(&tag_name),
+            attributes,
+            children: Vec::new(),
+            self_closing: true,
+            span: ctx.span(element_start, ctx.pos),
+        }));
+    }
+
     // Parse children
-    let children = parse_children(ctx, &tag_name)?;
-    
-    // Build jsx call
-    let tag_value = if tag_name == "Fragment" {
-        "__hook_jsx_runtime.Fragment".to_string()
-    } else if is_custom_component(&tag_name) {
-        tag_name.clone()
-    } else {
-        format!("\"{}\"", tag_name)
-    };
+    ctx.open_tags.push(tag_name.clone());
+    let children = parse_children(ctx, &tag_name, element_start..name_end);
+    ctx.open_tags.pop();
+    let children = children?;
+
+    Ok(JsxNode::Element(JsxElement {
+        name: JsxElementName::parse(&tag_name),
+        attributes,
+        children,
+        self_closing: false,
+        span: ctx.span(element_start, ctx.pos),
+    }))
+}
 
-    let jsx_call = if children.is_empty() {
-        if let Some(k) = key {
-            format!(
-                "__hook_jsx_runtime.jsx({}, {}, {})",
-                tag_value,
-                props,
-                k
-            )
-        } else {
-            format!(
-                "__hook_jsx_runtime.jsx({}, {})",
-                tag_value,
-                props
-            )
-        }
-    } else {
-        // Add children to props object without spread syntax
-        let props_with_children = if props == "{}" {
-            format!("{{ children: [{}] }}", children.join(", "))
-        } else {
-            let inner = props.trim_start_matches('{').trim_end_matches('}').trim();
-            if inner.is_empty() {
-                format!("{{ children: [{}] }}", children.join(", "))
-            } else {
-                format!("{{ {}, children: [{}] }}", inner, children.join(", "))
-            }
-        };
-        
-        let runtime_fn = if children.len() > 1 {
-            "__hook_jsx_runtime.jsxs"
-        } else {
-            "__hook_jsx_runtime.jsx"
-        };
-        
-        if let Some(k) = key {
-            format!(
-                "{}({}, {}, {})",
-                runtime_fn,
-                tag_value,
-                props_with_children,
-                k
-            )
-        } else {
-            format!(
-                "{}({}, {})",
-                runtime_fn,
-                tag_value,
-                props_with_children
-            )
-        }
-    };
-    
-    Ok(jsx_call)
+/// Parse a fragment's children; `start` is the byte offset of its `<`
+fn parse_fragment(ctx: &mut ParseContext, start: usize) -> Result<JsxFragment> {
+    ctx.open_tags.push(String::new());
+    let children = parse_children(ctx, "", start..start + 2);
+    ctx.open_tags.pop();
+    let children = children?;
+
+    Ok(JsxFragment { children, span: ctx.span(start, ctx.pos) })
 }
 
-fn parse_fragment(ctx: &mut ParseContext) -> Result<String> {
-    let children = parse_children(ctx, "")?;
-    
-    let jsx_call = if children.is_empty() {
-        "__hook_jsx_runtime.jsx(__hook_jsx_runtime.Fragment, {})".to_string()
-    } else {
-        let runtime_fn = if children.len() > 1 {
-            "__hook_jsx_runtime.jsxs"
-        } else {
-            "__hook_jsx_runtime.jsx"
-        };
-        format!(
-            "{}(__hook_jsx_runtime.Fragment, {{ children: [{}] }})",
-            runtime_fn,
-            children.join(", ")
-        )
-    };
-    
-    Ok(jsx_call)
+/// Whether the closing tag `</tag>` follows at `ctx.pos`
+fn closes_immediately(ctx: &ParseContext, tag: &str) -> bool {
+    ctx.slice(ctx.pos, ctx.end)
+        .strip_prefix("</")
+        .and_then(|rest| rest.strip_prefix(tag))
+        .is_some_and(|rest| rest.trim_start().starts_with('>'))
 }
 
-fn parse_props(ctx: &mut ParseContext) -> Result<(String, Option<String>)> {
+fn parse_props(ctx: &mut ParseContext) -> Result<Vec<JsxAttributeItem>> {
     let mut props = Vec::new();
-    let mut key = None;
-    
-    while ctx.current_char() != Some('>') && ctx.current_char() != Some('/') {
-        ctx.skip_whitespace();
-        
+    let mut seen: Vec<String> = Vec::new();
+    while ctx.current_char().is_some() && ctx.current_char() != Some('>') && ctx.current_char() != Some('/') {
+        skip_tag_trivia(ctx);
+
         if ctx.current_char() == Some('>') || ctx.current_char() == Some('/') {
             break;
         }
-        
+
         // Handle spread props {...obj}
-        if ctx.current_char() == Some('{') && ctx.peek(1) == Some('.') && ctx.peek(2) == Some('.') {
+        if ctx.current_char() == Some('{') {
+            let spread_start = ctx.pos;
             ctx.advance(); // {
-            ctx.advance(); // .
-            ctx.advance(); // .
-            ctx.advance(); // .
-            
-            let expr = parse_js_expression(ctx, '}')?;
-            ctx.consume('}')?;
-            props.push(format!("...{}", expr.trim()));
+            ctx.skip_whitespace();
+            for _ in 0..3 {
+                ctx.consume('.')?;
+            }
+
+            let argument = parse_expression(ctx)?;
+            ctx.close_container(spread_start)?;
+            if let Some(argument) = argument {
+                props.push(JsxAttributeItem::Spread(JsxSpreadAttribute {
+                    argument,
+                    span: ctx.span(spread_start, ctx.pos),
+                }));
+            }
             continue;
         }
-        
+
         // Parse prop name
         let name_start = ctx.pos;
-        while let Some(ch) = ctx.current_char() {
-            if ch.is_alphanumeric() || ch == '-' || ch == '_' {
-                ctx.advance();
-            } else {
-                break;
-            }
-        }
-        let prop_name = ctx.slice(name_start, ctx.pos);
-        
-        ctx.skip_whitespace();
-        
+        let prop_name = parse_jsx_attr_name(ctx);
+        let name_end = ctx.pos;
+
+        skip_tag_trivia(ctx);
+
         // Check for prop value
-        if ctx.current_char() == Some('=') {
+        let mut string_range = name_end..name_end;
+        let value = if ctx.current_char() == Some('=') {
             ctx.advance();
             ctx.skip_whitespace();
-            
-            let value = if ctx.current_char() == Some('"') || ctx.current_char() == Some('\'') {
-                parse_string_literal(ctx)?
+
+            if ctx.current_char() == Some('"') || ctx.current_char() == Some('\'') {
+                let value_start = ctx.pos;
+                let value = parse_string_literal(ctx)?;
+                string_range = value_start..ctx.pos;
+                Some(JsxAttributeValue::String { value, span: ctx.span(value_start, ctx.pos) })
             } else if ctx.current_char() == Some('{') {
+                let container_start = ctx.pos;
                 ctx.advance();
-                let expr = parse_js_expression(ctx, '}')?;
-                ctx.consume('}')?;
-                // Recursively transpile any JSX that appears inside expressions
-                transpile_jsx(&expr, &TranspileOptions { is_typescript: ctx.is_typescript, target: crate::TranspileTarget::Web, ..Default::default() })?
-            } else {
-                return Err(anyhow!("Expected prop value at position {}", ctx.pos));
-            };
-            
-            if prop_name == "key" {
-                key = Some(value);
+                let expression = parse_expression(ctx)?;
+                ctx.close_container(container_start)?;
+                if expression.is_none() {
+                    let err = ctx.error_at(
+                        ErrorCode::ExpectedPropValue,
+                        &format!("Expected value for prop '{}'", prop_name),
+                        container_start,
+                        ctx.pos,
+                    );
+                    ctx.report(err)?;
+                    continue;
+                }
+                Some(JsxAttributeValue::ExpressionContainer(JsxExpressionContainer {
+                    expression,
+                    span: ctx.span(container_start, ctx.pos),
+                }))
+            } else if ctx.current_char() == Some('<') {
+                // Element-valued attribute: icon=<Icon />
+                Some(JsxAttributeValue::Element(Box::new(parse_jsx_element(ctx)?)))
             } else {
-                props.push(format!("{}: {}", prop_name, value));
+                let err = ctx.error(ErrorCode::ExpectedPropValue, &format!("Expected value for prop '{}'", prop_name));
+                ctx.report(err)?;
+                continue;
             }
-        } else {
-            if !prop_name.is_empty() {
-                if prop_name == "key" {
-                    key = Some("true".to_string());
-                } else {
-                    // Boolean prop (no value means true)
-                    props.push(format!("{}: true", prop_name));
-                }
-            } else if let Some(ch) = ctx.current_char() {
-                // Skip invalid character to avoid infinite loop
-                if ch != '>' && ch != '/' {
-                    ctx.advance();
-                }
+        } else if prop_name.is_empty() {
+            if let Some(ch) = ctx.current_char()
+                && ch != '>'
+                && ch != '/'
+            {
+                return Err(ctx.error(ErrorCode::UnexpectedChar, &format!("Unexpected character '{}' in tag", ch)));
             }
+            continue;
+        } else {
+            // Boolean prop
+            None
+        };
+        let (prop_name, value) = if ctx.html_compat {
+            html_attribute(ctx, prop_name, value, name_start..name_end, string_range)
+        } else {
+            (prop_name, value)
+        };
+
+        // The later value wins as in React; code generation still evaluates the earlier one
+        if seen.contains(&prop_name) {
+            ctx.warn_at(
+                ErrorCode::DuplicateProp,
+                &format!("Duplicate prop '{}'; the last value wins", prop_name),
+                name_start,
+                name_end,
+            );
+        } else {
+            seen.push(prop_name.clone());
         }
-        
-        ctx.skip_whitespace();
+        let span_end = if value.is_some() { ctx.pos } else { name_end };
+        props.push(JsxAttributeItem::Attribute(JsxAttribute {
+            name: prop_name,
+            value,
+            span: ctx.span(name_start, span_end),
+        }));
+
+        skip_tag_trivia(ctx);
     }
-    
-    let props_str = if props.is_empty() {
-        "{}".to_string()
+
+    Ok(props)
+}
+
+/// The JSX spelling of the HTML attribute `name` (at byte range `name_range`): the React
+/// prop name, a style object for a `style` string and a function for a string event handler
+/// (at `string_range`). Warns about each fix.
+fn html_attribute(
+    ctx: &mut ParseContext,
+    name: String,
+    value: Option<JsxAttributeValue>,
+    name_range: Range<usize>,
+    string_range: Range<usize>,
+) -> (String, Option<JsxAttributeValue>) {
+    let is_event = html_compat::react_event_name(&name.to_ascii_lowercase()).is_some();
+    let name = match html_compat::react_attribute_name(&name).or_else(|| html_compat::react_event_name(&name)) {
+        Some(prop) => {
+            ctx.warn_at(
+                ErrorCode::HtmlCompat,
+                &format!("Renamed HTML attribute '{}' to '{}'", name, prop),
+                name_range.start,
+                name_range.end,
+            );
+            prop.to_string()
+        }
+        None => name,
+    };
+
+    let Some(JsxAttributeValue::String { value: text, span }) = &value else {
+        return (name, value);
+    };
+    let (source, fix) = if name == "style" {
+        (html_compat::style_object(text), "Converted the style string to a style object")
+    } else if is_event {
+        (html_compat::event_handler_function(text), "Wrapped the string event handler in a function")
     } else {
-        format!("{{ {} }}", props.join(", "))
+        return (name, value);
     };
-    
-    Ok((props_str, key))
+    ctx.warn_at(ErrorCode::HtmlCompat, fix, string_range.start, string_range.end);
+    let expression = Expression {
+        source,
+        offset: string_range.start,
+        jsx: Vec::new(),
+        span: *span,
+        holes: Vec::new(),
+    };
+    let container = JsxExpressionContainer { expression: Some(expression), span: *span };
+    (name, Some(JsxAttributeValue::ExpressionContainer(container)))
 }
 
-fn parse_children(ctx: &mut ParseContext, parent_tag: &str) -> Result<Vec<String>> {
+/// Parse children up to the closing tag of `parent_tag`, whose opening tag (`<div` or `<>`)
+/// is at `opening`. An element left unclosed at the end of input is reported there and,
+/// in recovery mode, ends where the code around it evidently resumes: before the first `}`
+/// or `;` at a line end in its text, which JSX text does not contain.
+fn parse_children(ctx: &mut ParseContext, parent_tag: &str, opening: Range<usize>) -> Result<Vec<JsxChild>> {
     let mut children = Vec::new();
-    
+    // (the text run where the code resumes, how many children and diagnostics come before it)
+    let mut resume: Option<(Range<usize>, usize, usize)> = None;
+
     loop {
-        ctx.skip_whitespace();
-        
         // Check for closing tag
         if ctx.current_char() == Some('<') && ctx.peek(1) == Some('/') {
+            let tag_start = ctx.pos;
             ctx.advance(); // <
             ctx.advance(); // /
-            
+
             // Parse closing tag name
-            let close_start = ctx.pos;
-            while let Some(ch) = ctx.current_char() {
-                if ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == '.' {
-                    ctx.advance();
-                } else {
-                    break;
-                }
-            }
-            let close_name = ctx.slice(close_start, ctx.pos);
-            
+            ctx.skip_whitespace();
+            let close_name = parse_jsx_name(ctx);
+
             ctx.skip_whitespace();
             ctx.consume('>')?;
-            
+
             // Verify closing tag matches (or is fragment)
             if !parent_tag.is_empty() && close_name != parent_tag {
-                return Err(anyhow!(
-                    "Mismatched closing tag: expected </{}>  but got </{}> at position {}",
-                    parent_tag, close_name, ctx.pos
-                ));
+                let err = ctx.error_at(
+                    ErrorCode::MismatchedClosingTag,
+                    &format!("Mismatched closing tag: expected </{}> but got </{}>", parent_tag, close_name),
+                    tag_start,
+                    ctx.pos,
+                );
+                ctx.report(err)?;
+                // An ancestor's closing tag implicitly closes this element; leave it for the ancestor.
+                // Otherwise treat it as a misspelled closing tag for this element.
+                let ancestors = &ctx.open_tags[..ctx.open_tags.len().saturating_sub(1)];
+                if ancestors.contains(&close_name) {
+                    ctx.pos = tag_start;
+                }
             }
-            
+
             break;
         }
-        
-        // Check for nested JSX element
-        if ctx.current_char() == Some('<') && is_jsx_start(ctx) {
-            let child_jsx = parse_jsx_element(ctx)?;
-            children.push(child_jsx);
+
+        // Check for nested JSX element. JSX text cannot contain `<`, so unlike in
+        // expression context there is no generic-call ambiguity here (`Hello<b>`).
+        if ctx.current_char() == Some('<') {
+            let start = ctx.pos;
+            match parse_jsx_element(ctx) {
+                Ok(JsxNode::Element(element)) => children.push(JsxChild::Element(element)),
+                Ok(JsxNode::Fragment(fragment)) => children.push(JsxChild::Fragment(fragment)),
+                Err(e) => {
+                    ctx.report(e)?;
+                    ctx.resync_children(start);
+                }
+            }
             continue;
         }
-        
+
         // Check for JS expression {expr}
         if ctx.current_char() == Some('{') {
+            let container_start = ctx.pos;
             ctx.advance();
-            let expr = parse_js_expression(ctx, '}')?;
-            ctx.consume('}')?;
+            ctx.skip_whitespace();
+            let is_spread = ctx.current_char() == Some('.') && ctx.peek(1) == Some('.') && ctx.peek(2) == Some('.');
+            if is_spread {
+                ctx.advance();
+                ctx.advance();
+                ctx.advance();
+            }
+            let expression = parse_expression(ctx)?;
+            if ctx.current_char() == Some('}') {
+                ctx.advance();
+            } else {
+                let err = ctx.error_at(
+                    ErrorCode::ExpectedChar,
+                    "Expected '}' to close this expression container",
+                    container_start,
+                    container_start + 1,
+                );
+                ctx.report(err)?;
+            }
+            let span = ctx.span(container_start, ctx.pos);
+
+            if is_spread {
+                // Spread children: {...items}
+                if let Some(expression) = expression {
+                    children.push(JsxChild::Spread(JsxSpreadChild { expression, span }));
+                }
+                continue;
+            }
 
-            // Recursively transpile any JSX that appears inside expressions
-            let transpiled_expr = transpile_jsx(&expr, &TranspileOptions { is_typescript: ctx.is_typescript, target: crate::TranspileTarget::Web, ..Default::default() })?;
-            children.push(transpiled_expr);
+            // `{}` and `{/* comment */}` are JSX empty expressions and produce no child
+            children.push(JsxChild::ExpressionContainer(JsxExpressionContainer { expression, span }));
             continue;
         }
-        
+
         // Parse text content
         let text_start = ctx.pos;
         while let Some(ch) = ctx.current_char() {
@@ -1164,29 +1695,67 @@ fn parse_children(ctx: &mut ParseContext, parent_tag: &str) -> Result<Vec<String
             }
             ctx.advance();
         }
-        
-        let text_slice = ctx.slice(text_start, ctx.pos);
-        let text = text_slice.trim().to_string();
-        if !text.is_empty() {
-            children.push(format!("\"{}\"", escape_string(&text)));
+
+        if ctx.pos > text_start {
+            let text_end = ctx.pos;
+            if resume.is_none()
+                && let Some(end) = statement_boundary(ctx.slice(text_start, text_end))
+            {
+                resume = Some((text_start..text_start + end, children.len(), ctx.diagnostics.len()));
+            }
+            push_text(ctx, &mut children, text_start, text_end);
         }
-        
+
         // If we haven't moved, we're at end of input without proper closing
         if ctx.pos == text_start {
-            if ctx.pos >= ctx.source.len() {
-                return Err(anyhow!("Unexpected end of input while parsing children for tag <{}>. Current position: {}, Total length: {}", parent_tag, ctx.pos, ctx.source.len()));
+            if ctx.at_end() {
+                let err = ctx.error_at(
+                    ErrorCode::UnexpectedEof,
+                    &format!("Unclosed tag <{}>: input ended before its closing tag", parent_tag),
+                    opening.start,
+                    opening.end,
+                );
+                if let Some((text, count, diagnostics)) = resume.take() {
+                    children.truncate(count);
+                    ctx.diagnostics.truncate(diagnostics);
+                    if !text.is_empty() {
+                        push_text(ctx, &mut children, text.start, text.end);
+                    }
+                    ctx.pos = text.end;
+                }
+                ctx.report(err)?;
             }
             break;
         }
     }
-    
+
     Ok(children)
 }
 
+/// Add the JSX text in the byte range `start..end` as a child
+fn push_text(ctx: &mut ParseContext, children: &mut Vec<JsxChild>, start: usize, end: usize) {
+    let raw = ctx.slice(start, end).to_string();
+    let value = clean_jsx_text(&ctx.decode_entities_at(&raw, start));
+    children.push(JsxChild::Text(JsxText { value, raw, span: ctx.span(start, end) }));
+}
+
+/// Where code evidently resumes in the JSX text `text` of an unclosed element: before the
+/// first `}` or line-ending `;`, and the `)`, `;` and whitespace that lead up to it
+fn statement_boundary(text: &str) -> Option<usize> {
+    let brace = text.find('}');
+    let semicolon = text
+        .match_indices(';')
+        .map(|(i, _)| i)
+        .find(|&i| text[i + 1..].trim_start_matches([' ', '\t', '\r']).starts_with('\n'));
+    let at = brace.into_iter().chain(semicolon).min()?;
+    Some(text[..at].trim_end_matches(|c: char| c.is_whitespace() || matches!(c, ';' | ')' | ',')).len())
+}
+
+/// Contents of a quoted attribute value with character references decoded
 fn parse_string_literal(ctx: &mut ParseContext) -> Result<String> {
     let quote = ctx.current_char().unwrap();
     ctx.advance();
-    
+
     let start = ctx.pos;
     while let Some(ch) = ctx.current_char() {
         if ch == quote {
@@ -1197,71 +1766,488 @@ fn parse_string_literal(ctx: &mut ParseContext) -> Result<String> {
         }
         ctx.advance();
     }
-    
-    let content = ctx.slice(start, ctx.pos);
-    ctx.consume(quote)?;
-    
-    Ok(format!("\"{}\"", escape_string(&content)))
+
+    let content = ctx.slice(start, ctx.pos.min(ctx.end));
+    if ctx.current_char() != Some(quote) {
+        let msg = format!("Unterminated string: expected a closing {}", quote);
+        return Err(ctx.error_at(ErrorCode::ExpectedChar, &msg, start - 1, start));
+    }
+    ctx.advance();
+    Ok(ctx.decode_entities_at(content, start))
 }
 
-fn parse_js_expression(ctx: &mut ParseContext, terminator: char) -> Result<String> {
-    let start = ctx.pos;
+/// Advance to the `terminator` that closes the current expression, skipping nested
+/// brackets, strings, comments, regex literals and JSX elements
+fn skip_js_expression(ctx: &mut ParseContext, terminator: char) {
     let mut depth = 0;
-    let mut in_string = false;
-    let mut string_char = ' ';
-    
-    while let Some(ch) = ctx.current_char() {
-        if in_string {
-            if ch == '\\' {
-                ctx.advance();
-                ctx.advance();
-                continue;
+    ctx.regex_allowed = true;
+    while let Some(token) = ctx.next_token() {
+        if token.kind != TokenKind::Punct {
+            continue;
+        }
+        match lexer::char_at(ctx.source, token.start, token.end) {
+            Some('<') if classify_angle_bracket(ctx, &token) == AngleBracket::Jsx => {
+                ctx.pos = token.start;
+                if skip_jsx_element(ctx).is_ok() {
+                    ctx.regex_allowed = false;
+                } else {
+                    ctx.pos = token.end;
+                }
+            }
+            Some('{' | '[' | '(') => depth += 1,
+            Some(ch @ ('}' | ']' | ')')) => {
+                if depth == 0 && ch == terminator {
+                    ctx.pos = token.start;
+                    break;
+                }
+                depth -= 1;
+            }
+            Some(ch) if depth == 0 && ch == terminator => {
+                ctx.pos = token.start;
+                break;
             }
-            if ch == string_char {
-                in_string = false;
+            _ => {}
+        }
+    }
+}
+
+/// Apply React/Babel JSX text rules (`cleanJSXElementLiteralChild`).
+///
+/// Whitespace that contains a line break is collapsed: leading whitespace is
+/// trimmed on every line but the first, trailing whitespace on every line but
+/// the last, empty lines are dropped and the rest are joined with one space.
+/// Whitespace on a single line is kept as-is, so `Hello <b>x</b> world` keeps
+/// the spaces around `<b>`.
+fn clean_jsx_text(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<&str> = text.split('\n').collect();
+    let last_line = lines.len() - 1;
+    let last_non_empty = lines
+        .iter()
+        .rposition(|line| line.chars().any(|c| c != ' ' && c != '\t'))
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.replace('\t', " ");
+        let mut trimmed = line.as_str();
+        if i != 0 {
+            trimmed = trimmed.trim_start_matches(' ');
+        }
+        if i != last_line {
+            trimmed = trimmed.trim_end_matches(' ');
+        }
+        if !trimmed.is_empty() {
+            out.push_str(trimmed);
+            if i != last_non_empty {
+                out.push(' ');
             }
+        }
+    }
+    out
+}
+
+/// True for a JSX empty expression: `{}`, `{ }` or a container holding only comments.
+fn is_empty_expression(source: &str, start: usize, end: usize) -> bool {
+    lexer::Lexer::new(source, start, end).all(|token| match token.kind {
+        TokenKind::Whitespace | TokenKind::LineComment => true,
+        // An unterminated comment runs on into code
+        TokenKind::BlockComment => token.end - token.start >= 4 && token.text(source).ends_with("*/"),
+        _ => false,
+    })
+}
+
+/// Read a JavaScript identifier or keyword at the current position ("" if there is none)
+fn read_js_identifier(ctx: &mut ParseContext) -> String {
+    let start = ctx.pos;
+    if ctx.current_char().is_some_and(is_identifier_start) {
+        ctx.advance();
+        while ctx.current_char().is_some_and(is_identifier_char) {
             ctx.advance();
-            continue;
         }
-        
-        if ch == '"' || ch == '\'' || ch == '`' {
-            in_string = true;
-            string_char = ch;
+    }
+    ctx.slice(start, ctx.pos).to_string()
+}
+
+/// Read a quoted module specifier such as `"react"`
+fn read_module_source(ctx: &mut ParseContext) -> Option<String> {
+    let quote = ctx.current_char().filter(|&c| c == '"' || c == '\'')?;
+    ctx.advance();
+    let start = ctx.pos;
+    while let Some(ch) = ctx.current_char() {
+        if ch == quote {
+            let source = ctx.slice(start, ctx.pos).to_string();
             ctx.advance();
-            continue;
+            return Some(source);
         }
-        
-        if ch == '{' || ch == '[' || ch == '(' {
-            depth += 1;
+        if ch == '\n' {
+            return None;
+        }
+        ctx.advance();
+    }
+    None
+}
+
+/// `from "source"`
+fn read_from_clause(ctx: &mut ParseContext) -> Option<String> {
+    skip_tag_trivia(ctx);
+    if read_js_identifier(ctx) != "from" {
+        return None;
+    }
+    skip_tag_trivia(ctx);
+    read_module_source(ctx)
+}
+
+/// Read a `{ a, b as c }` list as `(name, alias)` pairs; `type` modifiers are dropped
+fn read_braced_specifiers(ctx: &mut ParseContext) -> Option<Vec<(String, String)>> {
+    if ctx.current_char() != Some('{') {
+        return None;
+    }
+    ctx.advance();
+    let mut specifiers = Vec::new();
+    loop {
+        skip_tag_trivia(ctx);
+        if ctx.current_char() == Some('}') {
             ctx.advance();
-            continue;
+            return Some(specifiers);
         }
-        
-        if ch == '}' || ch == ']' || ch == ')' {
-            if depth == 0 && ch == terminator {
-                break;
+        let mut name = match ctx.current_char() {
+            Some('"' | '\'') => read_module_source(ctx)?,
+            _ => read_js_identifier(ctx),
+        };
+        skip_tag_trivia(ctx);
+        if name == "type" && ctx.current_char().is_some_and(is_identifier_start) {
+            name = read_js_identifier(ctx);
+            skip_tag_trivia(ctx);
+        }
+        if name.is_empty() {
+            return None;
+        }
+        let mut alias = name.clone();
+        if ctx.current_char().is_some_and(is_identifier_start) {
+            if read_js_identifier(ctx) != "as" {
+                return None;
             }
-            depth -= 1;
+            skip_tag_trivia(ctx);
+            alias = match ctx.current_char() {
+                Some('"' | '\'') => read_module_source(ctx)?,
+                _ => read_js_identifier(ctx),
+            };
+            skip_tag_trivia(ctx);
+        }
+        specifiers.push((name, alias));
+        match ctx.current_char() {
+            Some(',') => ctx.advance(),
+            Some('}') => {}
+            _ => return None,
+        }
+    }
+}
+
+/// Parse the import declaration whose `import` keyword starts at `start`; `ctx.pos` is
+/// just after the keyword. `None` for `import(…)`, `import.meta` and malformed statements.
+fn parse_import_declaration(ctx: &mut ParseContext, start: usize) -> Option<ImportDeclaration> {
+    skip_tag_trivia(ctx);
+    let mut specifiers = Vec::new();
+    let mut type_only = false;
+
+    if ctx.current_char().is_some_and(|c| c == '"' || c == '\'') {
+        let source = read_module_source(ctx)?;
+        return Some(ImportDeclaration { source, specifiers, type_only, span: ctx.span(start, ctx.pos) });
+    }
+
+    let mut word_start = ctx.pos;
+    let mut word = read_js_identifier(ctx);
+    if word == "type" {
+        let after_type = ctx.pos;
+        skip_tag_trivia(ctx);
+        let next_start = ctx.pos;
+        let next = read_js_identifier(ctx);
+        // `import type from "x"` imports a default export named `type`
+        let is_modifier = if next.is_empty() { matches!(ctx.current_char(), Some('{' | '*')) } else { next != "from" };
+        if is_modifier {
+            type_only = true;
+            word_start = next_start;
+            word = next;
+        } else {
+            ctx.pos = after_type;
+        }
+    }
+    if word.is_empty() {
+        ctx.pos = word_start;
+    } else {
+        specifiers.push(ImportSpecifier::Default { local: word });
+        skip_tag_trivia(ctx);
+        if ctx.current_char() != Some(',') {
+            let source = read_from_clause(ctx)?;
+            return Some(ImportDeclaration { source, specifiers, type_only, span: ctx.span(start, ctx.pos) });
+        }
+        ctx.advance();
+        skip_tag_trivia(ctx);
+    }
+
+    match ctx.current_char()? {
+        '*' => {
             ctx.advance();
-            continue;
+            skip_tag_trivia(ctx);
+            if read_js_identifier(ctx) != "as" {
+                return None;
+            }
+            skip_tag_trivia(ctx);
+            let local = read_js_identifier(ctx);
+            if local.is_empty() {
+                return None;
+            }
+            specifiers.push(ImportSpecifier::Namespace { local });
         }
-        
-        if depth == 0 && ch == terminator {
+        '{' => {
+            for (imported, local) in read_braced_specifiers(ctx)? {
+                specifiers.push(ImportSpecifier::Named { imported, local });
+            }
+        }
+        _ => return None,
+    }
+    let source = read_from_clause(ctx)?;
+    Some(ImportDeclaration { source, specifiers, type_only, span: ctx.span(start, ctx.pos) })
+}
+
+/// Parse the export declaration whose `export` keyword starts at `start`; `ctx.pos` is
+/// just after the keyword. Type-only exports and unrecognized forms yield `None`.
+fn parse_export_declaration(ctx: &mut ParseContext, start: usize) -> Option<ExportDeclaration> {
+    skip_tag_trivia(ctx);
+    match ctx.current_char()? {
+        '{' => {
+            let specifiers = read_braced_specifiers(ctx)?
+                .into_iter()
+                .map(|(local, exported)| ExportSpecifier { local, exported })
+                .collect();
+            let end = ctx.pos;
+            let source = read_from_clause(ctx);
+            if source.is_none() {
+                ctx.pos = end;
+            }
+            return Some(ExportDeclaration::Named { specifiers, source, span: ctx.span(start, ctx.pos) });
+        }
+        '*' => {
+            ctx.advance();
+            skip_tag_trivia(ctx);
+            let mut exported = None;
+            let saved = ctx.pos;
+            if read_js_identifier(ctx) == "as" {
+                skip_tag_trivia(ctx);
+                exported = Some(read_js_identifier(ctx)).filter(|name| !name.is_empty());
+            } else {
+                ctx.pos = saved;
+            }
+            let source = read_from_clause(ctx)?;
+            return Some(ExportDeclaration::All { exported, source, span: ctx.span(start, ctx.pos) });
+        }
+        _ => {}
+    }
+
+    let kind_start = ctx.pos;
+    let mut kind = read_js_identifier(ctx);
+    if kind == "default" {
+        let keyword_end = ctx.pos;
+        skip_tag_trivia(ctx);
+        let name = read_declared_name(ctx);
+        if name.is_none() {
+            ctx.pos = keyword_end;
+        }
+        return Some(ExportDeclaration::Default { name, span: ctx.span(start, ctx.pos) });
+    }
+
+    let names = match kind.as_str() {
+        "const" | "let" | "var" => read_declarators(ctx),
+        "async" | "function" | "class" => {
+            ctx.pos = kind_start;
+            let name = read_declared_name(ctx)?;
+            kind = if kind == "class" { kind } else { "function".to_string() };
+            vec![name]
+        }
+        _ => Vec::new(),
+    };
+    if names.is_empty() {
+        return None;
+    }
+    Some(ExportDeclaration::Declaration { kind, names, span: ctx.span(start, ctx.pos) })
+}
+
+/// The name after `function`, `async function`, `function*` or `class`, leaving `ctx.pos`
+/// after it. `None` when the next token is not one of these or the declaration is anonymous.
+fn read_declared_name(ctx: &mut ParseContext) -> Option<String> {
+    let saved = ctx.pos;
+    let mut keyword = read_js_identifier(ctx);
+    if keyword == "async" {
+        skip_tag_trivia(ctx);
+        keyword = read_js_identifier(ctx);
+    }
+    if keyword != "function" && keyword != "class" {
+        ctx.pos = saved;
+        return None;
+    }
+    skip_tag_trivia(ctx);
+    if keyword == "function" && ctx.current_char() == Some('*') {
+        ctx.advance();
+        skip_tag_trivia(ctx);
+    }
+    let name = read_js_identifier(ctx);
+    (!name.is_empty() && name != "extends").then_some(name)
+}
+
+/// Names bound by `a = 1, { b, c: d } = obj, [e, ...f] = list`, leaving `ctx.pos` after
+/// the last name read
+fn read_declarators(ctx: &mut ParseContext) -> Vec<String> {
+    let mut names = Vec::new();
+    loop {
+        skip_tag_trivia(ctx);
+        if !read_binding_names(ctx, &mut names) {
+            break;
+        }
+        let last_name_end = ctx.pos;
+        skip_tag_trivia(ctx);
+        if ctx.current_char() == Some('=') {
+            ctx.advance();
+            skip_initializer(ctx);
+        }
+        if ctx.current_char() != Some(',') {
+            ctx.pos = last_name_end;
             break;
         }
-        
         ctx.advance();
     }
-    
-    Ok(ctx.slice(start, ctx.pos))
+    names
+}
+
+/// Collect the names bound by one binding pattern; false when there is none
+fn read_binding_names(ctx: &mut ParseContext, names: &mut Vec<String>) -> bool {
+    let (close, is_object) = match ctx.current_char() {
+        Some('{') => ('}', true),
+        Some('[') => (']', false),
+        _ => {
+            let name = read_js_identifier(ctx);
+            if name.is_empty() {
+                return false;
+            }
+            names.push(name);
+            return true;
+        }
+    };
+    ctx.advance();
+    loop {
+        skip_tag_trivia(ctx);
+        match ctx.current_char() {
+            None => return false,
+            Some(c) if c == close => {
+                ctx.advance();
+                return true;
+            }
+            // Array holes
+            Some(',') => {
+                ctx.advance();
+                continue;
+            }
+            _ => {}
+        }
+        if ctx.current_char() == Some('.') {
+            while ctx.current_char() == Some('.') {
+                ctx.advance();
+            }
+            if !read_binding_names(ctx, names) {
+                return false;
+            }
+        } else if is_object {
+            // `key`, `key: pattern`, `"key": pattern` or `[computed]: pattern`
+            let key = match ctx.current_char() {
+                Some('[') => {
+                    ctx.advance();
+                    skip_js_expression(ctx, ']');
+                    ctx.advance();
+                    None
+                }
+                Some('"' | '\'') => {
+                    read_module_source(ctx);
+                    None
+                }
+                _ => Some(read_js_identifier(ctx)).filter(|key| !key.is_empty()),
+            };
+            skip_tag_trivia(ctx);
+            if ctx.current_char() == Some(':') {
+                ctx.advance();
+                skip_tag_trivia(ctx);
+                if !read_binding_names(ctx, names) {
+                    return false;
+                }
+            } else if let Some(key) = key {
+                names.push(key);
+            } else {
+                return false;
+            }
+        } else if !read_binding_names(ctx, names) {
+            return false;
+        }
+        skip_tag_trivia(ctx);
+        if ctx.current_char() == Some('=') {
+            ctx.advance();
+            skip_initializer(ctx);
+        }
+        match ctx.current_char() {
+            Some(',') => ctx.advance(),
+            Some(c) if c == close => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Skip an initializer up to the `,`, `;` or closing bracket that ends it, or a line
+/// break after which a new statement clearly starts
+fn skip_initializer(ctx: &mut ParseContext) {
+    let mut depth = 0usize;
+    // Whether the last significant token may end an expression
+    let mut may_end = false;
+    ctx.regex_allowed = true;
+    while let Some(token) = ctx.next_token() {
+        match token.kind {
+            TokenKind::Whitespace if depth == 0 && may_end => {
+                if let Some(newline) = token.text(ctx.source).find('\n').map(|n| token.start + n)
+                    && ctx.current_char().is_some_and(is_identifier_start)
+                {
+                    ctx.pos = newline;
+                    return;
+                }
+                continue;
+            }
+            _ if token.is_trivia() => continue,
+            TokenKind::Punct => match token.text(ctx.source) {
+                "<" if classify_angle_bracket(ctx, &token) == AngleBracket::Jsx => {
+                    ctx.pos = token.start;
+                    if skip_jsx_element(ctx).is_err() {
+                        ctx.pos = token.start + 1;
+                    }
+                    ctx.regex_allowed = false;
+                    may_end = true;
+                    continue;
+                }
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" | "," | ";" if depth == 0 => {
+                    ctx.pos = token.start;
+                    return;
+                }
+                ")" | "]" | "}" => depth -= 1,
+                _ => {}
+            },
+            _ => {}
+        }
+        may_end = ends_statement(&token, ctx.source);
+    }
 }
 
-fn escape_string(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-        .replace('\t', "\\t")
+/// Whether an expression may end with `token`, so a following line can start a new statement
+fn ends_statement(token: &Token, source: &str) -> bool {
+    match token.kind {
+        TokenKind::Punct => matches!(token.text(source), ")" | "]" | "}" | ">"),
+        _ => true,
+    }
 }
 
 /// Transform ES6 modules (import/export) to CommonJS (require/module.exports)
@@ -1601,57 +2587,61 @@ pub fn transform_es6_modules(source: &str) -> String {
 /// Transform dynamic import() calls to __hook_import() calls
 /// This allows code like: import('./module').then(m => ...) to work
 pub fn transform_dynamic_imports(source: &str) -> String {
-    let mut output = String::new();
-    let chars: Vec<char> = source.chars().collect();
-    let mut i = 0;
-    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
-    
-    while i < chars.len() {
-        // Look for 'import' keyword that is not part of a longer identifier (e.g., __hook_import)
-        if i + 6 <= chars.len()
-            && &chars[i..i+6].iter().collect::<String>() == "import"
-            && (i == 0 || !is_ident_char(chars[i - 1]))
-        {
-            // Check if it's followed by '('
-            let mut j = i + 6;
-            while j < chars.len() && chars[j].is_whitespace() {
-                j += 1;
-            }
-            
-            if j < chars.len() && chars[j] == '(' {
-                // This is a dynamic import!
-                // Find the closing parenthesis
-                let mut paren_count = 1;
-                j += 1;
-                let import_start = j;
-                
-                while j < chars.len() && paren_count > 0 {
-                    if chars[j] == '(' { paren_count += 1; }
-                    else if chars[j] == ')' { paren_count -= 1; }
-                    j += 1;
+    transform_dynamic_imports_with_map(source).0
+}
+
+/// [`transform_dynamic_imports`], also returning where each part of the output came from
+pub fn transform_dynamic_imports_with_map(source: &str) -> (String, OffsetMap) {
+    rewrite_dynamic_imports(source, 0..source.len()).into_parts()
+}
+
+/// `transform_dynamic_imports` over the byte range `range`; strings, comments and regex
+/// literals are copied untouched, template interpolations are rewritten too
+fn rewrite_dynamic_imports(source: &str, range: Range<usize>) -> MappedText {
+    let mut output = MappedText::default();
+    let mut tokens = lexer::Lexer::new(source, range.start, range.end);
+
+    while let Some(token) = tokens.next() {
+        match token.kind {
+            TokenKind::Template => {
+                let mut copied = token.start;
+                for code in lexer::template_interpolations(source, &token) {
+                    output.copy(&source[copied..code.start], copied);
+                    copied = code.end;
+                    output.append(&rewrite_dynamic_imports(source, code));
                 }
-                
-                if paren_count == 0 {
-                    // Extract the module path
-                    let module_expr = chars[import_start..j-1].iter().collect::<String>();
-                    
-                    // Replace: import(...) -> __hook_import(...)
-                    // This uses the bridge's dynamic import handler which returns the full module object
-                    output.push_str("__hook_import(");
-                    output.push_str(module_expr.trim());
-                    output.push_str(")");
-                    
-                    i = j;
-                    continue;
+                output.copy(&source[copied..token.end], copied);
+                continue;
+            }
+            // A whole `import` word (so not `__hook_import`) that is not a property access
+            TokenKind::Identifier
+                if token.text(source) == "import" && !source[..token.start].ends_with('.') =>
+            {
+                if let Some(open) = tokens.peek_significant()
+                    && open.is_punct(source, '(')
+                {
+                    let close = lexer::closing_bracket(source, open.end, range.end);
+                    if close < range.end && source.as_bytes()[close] == b')' {
+                        // Replace: import(...) -> __hook_import(...)
+                        // This uses the bridge's dynamic import handler which returns the full module object
+                        let argument = &source[open.end..close];
+                        let start = open.end + (argument.len() - argument.trim_start().len());
+                        let end = open.end + argument.trim_end().len();
+                        output.generate("__hook_import(", token.start);
+                        output.append(&rewrite_dynamic_imports(source, start..end.max(start)));
+                        output.push(')');
+                        tokens.seek(close + 1, false);
+                        continue;
+                    }
                 }
             }
+            _ => {}
         }
-        
-        // Not a dynamic import, copy character as-is
-        output.push(chars[i]);
-        i += 1;
+
+        // Not a dynamic import, copy as-is
+        output.copy(token.text(source), token.start);
     }
-    
+
     output
 }
 
@@ -1673,6 +2663,66 @@ mod tests {
         assert_eq!(output, "__hook_jsx_runtime.jsx(\"div\", {})");
     }
 
+    #[test]
+    fn test_inline_whitespace_is_preserved() {
+        let output = transpile_jsx("<p>Hello <b>x</b> world</p>", &TranspileOptions::default()).unwrap();
+        assert!(output.contains("\"Hello \", __hook_jsx_runtime.jsx(\"b\""), "output: {}", output);
+        assert!(output.contains("\" world\""), "output: {}", output);
+
+        let output = transpile_jsx("<p>{a} {b}</p>", &TranspileOptions::default()).unwrap();
+        assert!(output.contains("children: [a, \" \", b]"), "output: {}", output);
+    }
+
+    #[test]
+    fn test_multiline_text_is_collapsed() {
+        let input = "<div>\n  first line\n    second   line  \n\n  <span />\n</div>";
+        let output = transpile_jsx(input, &TranspileOptions::default()).unwrap();
+        assert!(output.contains("children: [\"first line second   line\", __hook_jsx_runtime.jsx(\"span\""), "output: {}", output);
+    }
+
+    #[test]
+    fn test_clean_jsx_text() {
+        assert_eq!(clean_jsx_text("  a  "), "  a  ");
+        assert_eq!(clean_jsx_text("\n   \n"), "");
+        assert_eq!(clean_jsx_text("a\n  b"), "a b");
+        assert_eq!(clean_jsx_text("\ta \r\n\t b\n"), " a b");
+    }
+
+    #[test]
+    fn test_empty_expressions_are_dropped() {
+        let input = "<div>{/* don't render */}<span />{ }{// line\n}</div>";
+        let output = transpile_jsx(input, &TranspileOptions::default()).unwrap();
+        assert_eq!(output, "__hook_jsx_runtime.jsx(\"div\", { children: __hook_jsx_runtime.jsx(\"span\", {}) })");
+    }
+
+    #[test]
+    fn test_deeply_nested_expressions() {
+        let depth = 40;
+        let input = format!("{}x{}", "<b>{".repeat(depth), "}</b>".repeat(depth));
+        let output = transpile_jsx(&input, &TranspileOptions::default()).unwrap();
+        let expected = format!("{}x{}", "__hook_jsx_runtime.jsx(\"b\", { children: ".repeat(depth), " })".repeat(depth));
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_entities_are_decoded() {
+        let input = "<p title=\"Tom &amp; Jerry\">&copy; 2024&nbsp;&mdash; &#x2014;&#8212; a &lt; b</p>";
+        let output = transpile_jsx(input, &TranspileOptions::default()).unwrap();
+        assert!(output.contains("title: \"Tom & Jerry\""), "output: {}", output);
+        assert!(output.contains("\"\u{00A9} 2024\u{00A0}\u{2014} \u{2014}\u{2014} a < b\""), "output: {}", output);
+    }
+
+    #[test]
+    fn test_unknown_entity_warns_without_failing() {
+        let input = "<p>&bogus; &amp;</p>";
+        let (output, warnings) = transpile_jsx_with_warnings(input, &TranspileOptions::default()).unwrap();
+        let output = output.as_str();
+        assert!(output.contains("\"&bogus; &\""), "output: {}", output);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, ErrorCode::UnknownEntity);
+        assert_eq!(warnings[0].span.as_deref().map(|s| (s.start, s.end)), Some((3, 10)));
+    }
+
     #[test]
     fn test_with_props() {
         let input = r#"<div className="foo" id="bar"></div>"#;
@@ -1744,21 +2794,21 @@ const element = <div user={user as any} />;"#;
     fn test_with_children() {
         let input = "<div>Hello World</div>";
         let output = transpile_jsx(input, &TranspileOptions::default()).unwrap();
-        assert_eq!(output, "__hook_jsx_runtime.jsx(\"div\", { children: [\"Hello World\"] })");
+        assert_eq!(output, "__hook_jsx_runtime.jsx(\"div\", { children: \"Hello World\" })");
     }
 
     #[test]
     fn test_nested_elements() {
         let input = "<div><span>Nested</span></div>";
         let output = transpile_jsx(input, &TranspileOptions::default()).unwrap();
-        assert_eq!(output, "__hook_jsx_runtime.jsx(\"div\", { children: [__hook_jsx_runtime.jsx(\"span\", { children: [\"Nested\"] })] })");
+        assert_eq!(output, "__hook_jsx_runtime.jsx(\"div\", { children: __hook_jsx_runtime.jsx(\"span\", { children: \"Nested\" }) })");
     }
 
     #[test]
     fn test_fragment() {
         let input = "<>Fragment content</>";
         let output = transpile_jsx(input, &TranspileOptions::default()).unwrap();
-        assert_eq!(output, "__hook_jsx_runtime.jsx(__hook_jsx_runtime.Fragment, { children: [\"Fragment content\"] })");
+        assert_eq!(output, "__hook_jsx_runtime.jsx(__hook_jsx_runtime.Fragment, { children: \"Fragment content\" })");
     }
 
     #[test]
@@ -2085,4 +3135,22 @@ export { Helper };
         assert_eq!(output.trim(), "const m = __hook_import('./spaced.js');");
     }
 
+    #[test]
+    fn test_transform_dynamic_imports_skips_strings_comments_and_regexes() {
+        let source = "const s = \"import('./a.js')\"; // import('./b.js')\nconst r = /import\\('/; const m = x.import('./c.js');";
+        assert_eq!(transform_dynamic_imports(source), source);
+
+        let source = "const m = `${await import('./d.js')}`; const n = import(/* c */ `./${name}.js`);";
+        assert_eq!(
+            transform_dynamic_imports(source),
+            "const m = `${await __hook_import('./d.js')}`; const n = __hook_import(/* c */ `./${name}.js`);"
+        );
+    }
+
+    #[test]
+    fn test_strip_typescript_after_regex_with_quotes() {
+        let source = "const quote = /'/;\nconst n: number = s.match(/\"[^\"]*\"/g).length / 2;";
+        let (stripped, _) = strip_typescript(source, false).unwrap();
+        assert_eq!(stripped, "const quote = /'/;\nconst n = s.match(/\"[^\"]*\"/g).length / 2;");
+    }
 }
@@ -0,0 +1,502 @@
+//! JavaScript tokenizer shared by every pass that walks source text.
+//!
+//! The passes (JSX scanning, TypeScript stripping and checking, downleveling, dynamic
+//! import rewriting) only need to know where strings, comments, regex literals and
+//! template literals start and end so they never treat their contents as code. The lexer
+//! works on byte offsets into the `&str` each pass already has, and lexes one token at a
+//! time so passes can step past constructs it does not know (JSX, TypeScript types) and
+//! resume. Every delimiter it looks for is ASCII, and UTF-8 never uses ASCII bytes inside
+//! a multi-byte char, so only identifiers and whitespace decode chars.
+//!
+//! A `/` is a regex literal in expression position and division after an operand, which
+//! is decided from the previous significant token the same way JavaScript engines do.
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+    /// `'…'` or `"…"`
+    String,
+    /// A whole template literal, interpolations included
+    Template,
+    /// `/pattern/flags`
+    Regex,
+    Number,
+    /// An identifier or keyword
+    Identifier,
+    /// Any other char, plus `++` and `--`
+    Punct,
+}
+
+/// A token covering the byte range `start..end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+    /// Whether an expression may start here (after an operator, opening bracket or
+    /// keyword) rather than an operator following an operand
+    pub expression_position: bool,
+}
+
+/// Keywords after which an expression (and so a regex literal) may start
+const EXPRESSION_KEYWORDS: &[&str] = &[
+    "await", "case", "default", "delete", "do", "else", "in", "instanceof", "new", "of",
+    "return", "throw", "typeof", "void", "yield",
+];
+
+impl Token {
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment)
+    }
+
+    /// The token's text in `source`
+    pub fn text<'s>(&self, source: &'s str) -> &'s str {
+        &source[self.start..self.end]
+    }
+
+    /// Whether this is the punctuator `ch`
+    pub fn is_punct(&self, source: &str, ch: char) -> bool {
+        self.kind == TokenKind::Punct && self.text(source) == ch.encode_utf8(&mut [0; 4])
+    }
+
+    /// Whether a `/` after this token starts a regex literal; `regex_allowed` is the state
+    /// before it, which trivia leaves unchanged
+    pub fn regex_allowed_after(&self, source: &str, regex_allowed: bool) -> bool {
+        match self.kind {
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment => regex_allowed,
+            TokenKind::String | TokenKind::Template | TokenKind::Regex | TokenKind::Number => false,
+            // Keywords only count as such when they are not property names (`x.default`)
+            TokenKind::Identifier => {
+                EXPRESSION_KEYWORDS.contains(&self.text(source)) && !source[..self.start].ends_with('.')
+            }
+            // `<` is excluded so a JSX closing tag `</a>` never reads as a regex
+            TokenKind::Punct => !matches!(self.text(source), ")" | "]" | "}" | "<" | "++" | "--"),
+        }
+    }
+}
+
+pub fn is_identifier_start(ch: char) -> bool {
+    ch.is_alphabetic() || ch == '_' || ch == '$'
+}
+
+pub fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '$' | '\u{200C}' | '\u{200D}')
+}
+
+/// The char starting at byte offset `pos` of `source`, if `pos` is below `end`
+pub fn char_at(source: &str, pos: usize, end: usize) -> Option<char> {
+    if pos < end { source.get(pos..)?.chars().next() } else { None }
+}
+
+/// Byte offset of the first char from `pos` on that `keep` rejects, or `end`
+fn scan_chars(source: &str, pos: usize, end: usize, keep: impl Fn(char) -> bool) -> usize {
+    source[pos..end].find(|c| !keep(c)).map_or(end, |n| pos + n)
+}
+
+/// Lex the token starting at byte offset `pos` (which must be below `end`), never reading
+/// at or past `end`. Unterminated strings and comments run to the end of their line or of
+/// the text; a `/` that cannot start a complete regex literal is lexed as punctuation.
+pub fn token_at(source: &str, pos: usize, end: usize, regex_allowed: bool) -> Token {
+    let bytes = source.as_bytes();
+    let at = |i: usize| if i < end { bytes.get(i).copied() } else { None };
+    let ch = char_at(source, pos, end).expect("a token starts below `end`");
+
+    let (kind, stop) = match ch {
+        c if c.is_whitespace() => (TokenKind::Whitespace, scan_chars(source, pos, end, char::is_whitespace)),
+        '/' if at(pos + 1) == Some(b'/') => (TokenKind::LineComment, scan_chars(source, pos, end, |c| c != '\n')),
+        '/' if at(pos + 1) == Some(b'*') => {
+            let stop = source[pos + 2..end].find("*/").map_or(end, |n| pos + 2 + n + 2);
+            (TokenKind::BlockComment, stop)
+        }
+        '/' if regex_allowed => match regex_end(source, pos, end) {
+            Some(stop) => (TokenKind::Regex, stop),
+            None => (TokenKind::Punct, pos + 1),
+        },
+        '"' | '\'' => (TokenKind::String, string_end(bytes, pos, end)),
+        '`' => (TokenKind::Template, template_end(source, pos, end, &mut |_| {})),
+        c if c.is_ascii_digit() => (TokenKind::Number, number_end(bytes, pos, end)),
+        '.' if at(pos + 1).is_some_and(|b| b.is_ascii_digit()) => (TokenKind::Number, number_end(bytes, pos, end)),
+        c if is_identifier_start(c) => {
+            (TokenKind::Identifier, scan_chars(source, pos + c.len_utf8(), end, is_identifier_char))
+        }
+        '+' | '-' if at(pos + 1) == Some(ch as u8) => (TokenKind::Punct, pos + 2),
+        c => (TokenKind::Punct, pos + c.len_utf8()),
+    };
+    Token { kind, start: pos, end: stop, expression_position: regex_allowed }
+}
+
+/// End of the string literal opening at `pos`. Escapes step over one byte only, which at
+/// worst lands inside a multi-byte char whose bytes never match a delimiter.
+fn string_end(bytes: &[u8], pos: usize, end: usize) -> usize {
+    let quote = bytes[pos];
+    let mut i = pos + 1;
+    while i < end {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' => return i,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    end
+}
+
+/// End of the regex literal opening at `pos`, or `None` if the line ends first
+fn regex_end(source: &str, pos: usize, end: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let mut i = pos + 1;
+    let mut in_class = false;
+    while i < end {
+        match bytes[i] {
+            b'\\' if i + 1 < end && bytes[i + 1] != b'\n' => i += 2,
+            b'\n' | b'\r' => return None,
+            b'[' => {
+                in_class = true;
+                i += 1;
+            }
+            b']' => {
+                in_class = false;
+                i += 1;
+            }
+            b'/' if !in_class => {
+                // The pattern may not be empty: `//` is a comment
+                if i == pos + 1 {
+                    return None;
+                }
+                return Some(scan_chars(source, i + 1, end, is_identifier_char));
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// End of the numeric literal starting at `pos`: decimal with fraction and exponent,
+/// `0x`/`0o`/`0b` integers, `_` separators and the `n` BigInt suffix
+fn number_end(bytes: &[u8], pos: usize, end: usize) -> usize {
+    let at = |i: usize| if i < end { bytes.get(i).copied() } else { None };
+    let digits = |mut i: usize| {
+        while at(i).is_some_and(|b| b.is_ascii_digit() || b == b'_') {
+            i += 1;
+        }
+        i
+    };
+
+    if bytes[pos] == b'0' && at(pos + 1).is_some_and(|b| matches!(b, b'x' | b'X' | b'o' | b'O' | b'b' | b'B')) {
+        let mut i = pos + 2;
+        while at(i).is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_') {
+            i += 1;
+        }
+        return i;
+    }
+
+    let mut i = digits(pos);
+    if at(i) == Some(b'.') {
+        i = digits(i + 1);
+    }
+    if at(i).is_some_and(|b| b == b'e' || b == b'E') {
+        let sign = usize::from(at(i + 1).is_some_and(|b| b == b'+' || b == b'-'));
+        if at(i + 1 + sign).is_some_and(|b| b.is_ascii_digit()) {
+            i = digits(i + 1 + sign);
+        }
+    }
+    if at(i) == Some(b'n') {
+        i += 1;
+    }
+    i
+}
+
+/// End of the template literal opening at `pos`, calling `on_interpolation` with the
+/// byte range of the code inside each `${…}`
+fn template_end(source: &str, pos: usize, end: usize, on_interpolation: &mut dyn FnMut(Range<usize>)) -> usize {
+    let bytes = source.as_bytes();
+    let mut i = pos + 1;
+    while i < end {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => return i + 1,
+            b'$' if i + 1 < end && bytes[i + 1] == b'{' => {
+                let code_end = closing_bracket(source, i + 2, end);
+                on_interpolation(i + 2..code_end);
+                i = code_end + 1;
+            }
+            _ => i += 1,
+        }
+    }
+    end
+}
+
+/// The byte ranges of the code inside each `${…}` of a template literal token. The last
+/// range ends at the token's end if the literal is cut off inside an interpolation.
+pub fn template_interpolations(source: &str, token: &Token) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    template_end(source, token.start, token.end, &mut |range| ranges.push(range));
+    ranges
+}
+
+/// The value of a string or template literal body, escapes resolved
+pub fn cooked_string(raw: &str) -> Option<String> {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\r' {
+            // Templates normalize line terminators
+            chars.next_if_eq(&'\n');
+            value.push('\n');
+            continue;
+        }
+        if ch != '\\' {
+            value.push(ch);
+            continue;
+        }
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            'b' => value.push('\u{8}'),
+            'f' => value.push('\u{c}'),
+            'v' => value.push('\u{b}'),
+            '0' if !chars.peek().is_some_and(char::is_ascii_digit) => value.push('\0'),
+            '0'..='9' => return None,
+            'x' => {
+                let hex: String = [chars.next()?, chars.next()?].iter().collect();
+                value.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+            }
+            'u' => {
+                let code = unicode_escape(&mut chars)?;
+                // A surrogate pair is spelled as two escapes
+                if (0xD800..0xDC00).contains(&code) && chars.next_if_eq(&'\\').is_some() {
+                    chars.next_if_eq(&'u')?;
+                    let low = unicode_escape(&mut chars)?;
+                    value.push(char::from_u32(0x10000 + ((code - 0xD800) << 10) + (low.checked_sub(0xDC00)?))?);
+                } else {
+                    value.push(char::from_u32(code)?);
+                }
+            }
+            '\r' => {
+                chars.next_if_eq(&'\n');
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => {}
+            other => value.push(other),
+        }
+    }
+    Some(value)
+}
+
+/// The code point of a `\u` escape after the `u`: `XXXX` or `{X…}`
+fn unicode_escape(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Option<u32> {
+    let hex: String = if chars.next_if_eq(&'{').is_some() {
+        let hex: String = chars.by_ref().take_while(|&c| c != '}').collect();
+        hex
+    } else {
+        chars.by_ref().take(4).collect()
+    };
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+/// Byte offset of the bracket that closes a `(…)`, `[…]` or `{…}` group whose contents
+/// start at `start`, or `end` if it is never closed
+pub fn closing_bracket(source: &str, start: usize, end: usize) -> usize {
+    let mut depth = 0usize;
+    for token in Lexer::new(source, start, end) {
+        if token.kind != TokenKind::Punct {
+            continue;
+        }
+        match source.as_bytes()[token.start] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' if depth == 0 => return token.start,
+            b')' | b']' | b'}' => depth -= 1,
+            _ => {}
+        }
+    }
+    end
+}
+
+/// Byte offset just past the directive prologue (`'use strict';` and other string
+/// statements) at the start of the statements in `start..end`, or `start` if there is none
+pub fn directive_prologue_end(source: &str, start: usize, end: usize) -> usize {
+    let mut prologue_end = start;
+    let mut tokens = Lexer::new(source, start, end);
+    while let Some(string) = tokens.find(|token| !token.is_trivia()).filter(|token| token.kind == TokenKind::String) {
+        let mut line_break = false;
+        let next = tokens.clone().find(|token| {
+            line_break |= token.is_trivia() && token.text(source).contains('\n');
+            !token.is_trivia()
+        });
+        match next {
+            Some(semicolon) if semicolon.is_punct(source, ';') => {
+                prologue_end = semicolon.end;
+                tokens.seek(semicolon.end, true);
+            }
+            // Ended by automatic semicolon insertion
+            None => return string.end,
+            Some(next) if next.is_punct(source, '}') || (line_break && next.kind != TokenKind::Punct) => {
+                prologue_end = string.end;
+            }
+            // `'a' + b` is an expression, not a directive
+            Some(_) => break,
+        }
+    }
+    prologue_end
+}
+
+/// Tokens of the byte range `pos..end`, starting in expression position
+#[derive(Clone)]
+pub struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+    end: usize,
+    regex_allowed: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str, start: usize, end: usize) -> Self {
+        Self { source, pos: start, end: end.min(source.len()), regex_allowed: true }
+    }
+
+    /// Continue from `pos` after the caller stepped over something itself
+    pub fn seek(&mut self, pos: usize, regex_allowed: bool) {
+        self.pos = pos;
+        self.regex_allowed = regex_allowed;
+    }
+
+    /// The next token that is not whitespace or a comment, without consuming anything
+    pub fn peek_significant(&self) -> Option<Token> {
+        self.clone().find(|token| !token.is_trivia())
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if self.pos >= self.end {
+            return None;
+        }
+        let token = token_at(self.source, self.pos, self.end, self.regex_allowed);
+        self.regex_allowed = token.regex_allowed_after(self.source, self.regex_allowed);
+        self.pos = token.end;
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lex(source: &str) -> Vec<(TokenKind, String)> {
+        Lexer::new(source, 0, source.len())
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, token.text(source).to_string()))
+            .collect()
+    }
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        lex(source).into_iter().map(|(kind, _)| kind).collect()
+    }
+
+    #[test]
+    fn string_escapes_are_cooked() {
+        assert_eq!(cooked_string(r"a\n\t\x41B\u{43}\'\\").as_deref(), Some("a\n\tABC'\\"));
+        assert_eq!(cooked_string(r"😀 \
+x").as_deref(), Some("😀 x"));
+        assert_eq!(cooked_string(r"\uD83D\uDE00").as_deref(), Some("😀"));
+        assert_eq!(cooked_string(r"\101"), None);
+    }
+
+    #[test]
+    fn regex_in_expression_position() {
+        assert_eq!(
+            lex(r#"x = /"[^"]*"/g;"#),
+            vec![
+                (TokenKind::Identifier, "x".to_string()),
+                (TokenKind::Punct, "=".to_string()),
+                (TokenKind::Regex, r#"/"[^"]*"/g"#.to_string()),
+                (TokenKind::Punct, ";".to_string()),
+            ]
+        );
+        assert_eq!(lex("return /'/.test(s)")[1], (TokenKind::Regex, "/'/".to_string()));
+        assert_eq!(lex("f(/[/]/)")[2], (TokenKind::Regex, "/[/]/".to_string()));
+        assert_eq!(lex(r"s.split(/\//)")[4], (TokenKind::Regex, r"/\//".to_string()));
+    }
+
+    #[test]
+    fn division_after_operands() {
+        use TokenKind::*;
+        assert_eq!(kinds("a / b / c"), vec![Identifier, Punct, Identifier, Punct, Identifier]);
+        assert_eq!(kinds("(a) / 2 / x"), vec![Punct, Identifier, Punct, Punct, Number, Punct, Identifier]);
+        assert_eq!(kinds("arr[0] / 'x'"), vec![Identifier, Punct, Number, Punct, Punct, String]);
+        assert_eq!(kinds("i++ / 2"), vec![Identifier, Punct, Punct, Number]);
+        assert_eq!(kinds("x.return / 2"), vec![Identifier, Punct, Identifier, Punct, Number]);
+        // A `/` that cannot start a complete regex on this line is division
+        assert_eq!(kinds("= / 2\n/"), vec![Punct, Punct, Number, Punct]);
+        assert_eq!(kinds("<a>x</a>"), vec![Punct, Identifier, Punct, Identifier, Punct, Punct, Identifier, Punct]);
+    }
+
+    #[test]
+    fn comments_are_not_regexes() {
+        use TokenKind::*;
+        assert_eq!(kinds("x = // c\n/* d */ /r/"), vec![Identifier, Punct, LineComment, BlockComment, Regex]);
+    }
+
+    #[test]
+    fn numbers() {
+        for number in ["0", "1.5", ".5", "1e10", "1.5E-3", "0x1F", "0b1010", "1_000_000", "10n"] {
+            assert_eq!(lex(number), vec![(TokenKind::Number, number.to_string())], "{}", number);
+        }
+        assert_eq!(lex("1.5.toFixed")[0], (TokenKind::Number, "1.5".to_string()));
+    }
+
+    #[test]
+    fn strings_stop_at_line_end() {
+        assert_eq!(lex(r#"'it\'s' "a\"b""#).len(), 2);
+        assert_eq!(lex("'open\nx")[0], (TokenKind::String, "'open".to_string()));
+    }
+
+    #[test]
+    fn offsets_are_bytes_of_non_ascii_text() {
+        let source = "const café = '\\😀'; /* ü */ x\u{a0}= `${ñ}` / ï";
+        let texts: Vec<String> = lex(source).into_iter().map(|(_, text)| text).collect();
+        assert_eq!(texts, ["const", "café", "=", "'\\😀'", ";", "/* ü */", "x", "=", "`${ñ}`", "/", "ï"]);
+
+        let template = Lexer::new(source, 0, source.len()).find(|token| token.kind == TokenKind::Template).unwrap();
+        let ranges = template_interpolations(source, &template);
+        assert_eq!(ranges.iter().map(|range| &source[range.clone()]).collect::<Vec<_>>(), ["ñ"]);
+    }
+
+    #[test]
+    fn templates_with_nested_interpolations() {
+        let source = "`a ${ {b: `c ${d}`}.b } e ${'}'} ${/}/.source}` + 1";
+        let token = Lexer::new(source, 0, source.len()).next().unwrap();
+        assert_eq!(token.kind, TokenKind::Template);
+        assert_eq!(token.end, source.find(" + 1").unwrap());
+
+        let parts: Vec<&str> = template_interpolations(source, &token).into_iter().map(|range| &source[range]).collect();
+        assert_eq!(parts, vec![" {b: `c ${d}`}.b ", "'}'", "/}/.source"]);
+        assert_eq!(kinds(source)[1..], [TokenKind::Punct, TokenKind::Number]);
+    }
+
+    #[test]
+    fn directive_prologues_end_after_their_last_string() {
+        let end = |source: &str| directive_prologue_end(source, 0, source.len());
+        let source = "// header\n'use strict';\n\"use client\"\nconst a = 'b';";
+        assert_eq!(end(source), source.find("\nconst").unwrap());
+        assert_eq!(end("'use strict'"), 12);
+        assert_eq!(end("'a' + b;"), 0);
+        assert_eq!(end("const a = 1;"), 0);
+    }
+
+    #[test]
+    fn closing_bracket_skips_nested_code() {
+        let source = "a = { b: ')' } / (2); /)/ ) x";
+        assert_eq!(closing_bracket(source, 0, source.len()), source.len() - 3);
+        assert_eq!(closing_bracket(source, 0, 5), 5);
+    }
+}
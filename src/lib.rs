@@ -1,6 +1,17 @@
 mod jsx_parser;
+mod jsx_codegen;
+mod lexer;
+mod source_map;
 mod swc_transformer;
+pub mod ast;
 pub mod debug;
+pub mod error;
+pub mod html_prerender;
+pub mod position;
+pub mod ui_tree;
+mod element_map;
+mod entities;
+mod html_compat;
 #[cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
 mod swc_native;
 
@@ -8,6 +19,9 @@ mod swc_native;
 use serde::{Deserialize, Serialize};
 
 pub use debug::{DebugLevel, DebugContext, DebugEntry};
+pub use element_map::{ElementMap, ElementRule};
+pub use error::{ErrorCode, ErrorKind, Severity, Span, TranspileError};
+pub use position::{LineIndex, Position};
 
 /// Target platform for transpilation
 #[cfg_attr(feature = "wasm", derive(Serialize, Deserialize))]
@@ -25,6 +39,120 @@ impl Default for TranspileTarget {
     }
 }
 
+impl TranspileTarget {
+    /// The built-in element map for the target's renderer; the web renders React DOM
+    /// directly and needs none
+    pub fn element_map(&self) -> Option<&'static ElementMap> {
+        match self {
+            Self::Web => None,
+            Self::Android => Some(ElementMap::android()),
+        }
+    }
+}
+
+/// Default runtime object / import source for the automatic JSX runtime
+pub const DEFAULT_JSX_RUNTIME: &str = "__hook_jsx_runtime";
+
+/// How JSX elements are compiled into function calls
+#[cfg_attr(feature = "wasm", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsxRuntime {
+    /// React 17+ automatic runtime (`jsx`, `jsxs`, `Fragment`).
+    /// The custom parser calls these on the global `object`, or on a namespace import of
+    /// `{import_source}/jsx-runtime` bound to `object` when an import source is set.
+    /// SWC always imports them from `{import_source}/jsx-runtime`.
+    Automatic {
+        object: String,
+        import_source: Option<String>,
+    },
+    /// Classic runtime: `pragma(type, props, ...children)`, e.g. `React.createElement` or Preact's `h`
+    Classic {
+        pragma: String,
+        pragma_frag: String,
+    },
+}
+
+impl Default for JsxRuntime {
+    fn default() -> Self {
+        Self::Automatic {
+            object: DEFAULT_JSX_RUNTIME.to_string(),
+            import_source: None,
+        }
+    }
+}
+
+impl JsxRuntime {
+    /// Classic runtime with the given factory, e.g. `JsxRuntime::classic("h", "Fragment")`
+    pub fn classic(pragma: &str, pragma_frag: &str) -> Self {
+        Self::Classic {
+            pragma: pragma.to_string(),
+            pragma_frag: pragma_frag.to_string(),
+        }
+    }
+
+    /// Apply per-file `@jsx`, `@jsxFrag`, `@jsxImportSource` and `@jsxRuntime` pragma comments.
+    ///
+    /// As in Babel and SWC, `@jsx`/`@jsxFrag` switch to the classic runtime and
+    /// `@jsxImportSource` to the automatic one unless `@jsxRuntime` says otherwise.
+    pub fn with_pragmas(&self, source: &str) -> Self {
+        let mut runtime = None;
+        let mut pragma = None;
+        let mut pragma_frag = None;
+        let mut import_source = None;
+        for (name, value) in jsx_parser::jsx_pragmas(source) {
+            match name.as_str() {
+                "jsx" => pragma = Some(value),
+                "jsxFrag" => pragma_frag = Some(value),
+                "jsxImportSource" => import_source = Some(value),
+                "jsxRuntime" => runtime = Some(value),
+                _ => {}
+            }
+        }
+
+        let classic = match runtime.as_deref() {
+            Some("classic") => true,
+            Some("automatic") => false,
+            _ if pragma.is_some() || pragma_frag.is_some() => true,
+            _ if import_source.is_some() => false,
+            _ => matches!(self, Self::Classic { .. }),
+        };
+
+        match (classic, self) {
+            (true, Self::Classic { pragma: p, pragma_frag: f }) => Self::Classic {
+                pragma: pragma.unwrap_or_else(|| p.clone()),
+                pragma_frag: pragma_frag.unwrap_or_else(|| f.clone()),
+            },
+            (true, Self::Automatic { .. }) => Self::Classic {
+                pragma: pragma.unwrap_or_else(|| "React.createElement".to_string()),
+                pragma_frag: pragma_frag.unwrap_or_else(|| "React.Fragment".to_string()),
+            },
+            (false, Self::Automatic { object, import_source: source }) => Self::Automatic {
+                object: object.clone(),
+                import_source: import_source.or_else(|| source.clone()),
+            },
+            (false, Self::Classic { .. }) => Self::Automatic {
+                object: DEFAULT_JSX_RUNTIME.to_string(),
+                import_source,
+            },
+        }
+    }
+}
+
+/// What the custom parser compiles JSX to
+#[cfg_attr(feature = "wasm", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsxBackend {
+    /// Calls into the JSX runtime chosen by [`JsxRuntime`], creating elements for React
+    #[default]
+    Runtime,
+    /// Imperative DOM construction with no virtual DOM: each intrinsic element becomes a
+    /// `document.createElement` call followed by `setAttribute` and `appendChild`, components
+    /// are called with their props, and fragments become `DocumentFragment`s. Dynamic values
+    /// go through small helpers declared on the first line of the output.
+    Dom,
+}
+
+#[derive(Debug, Clone)]
 pub struct TranspileOptions {
     pub is_typescript: bool,
     /// Target platform - determines which features need transpilation
@@ -33,14 +161,32 @@ pub struct TranspileOptions {
     pub filename: Option<String>,
     /// Whether to emit CommonJS-compatible output (native targets only)
     pub to_commonjs: bool,
-    /// Emit source maps (native SWC path only)
+    /// Emit a source map mapping the output back to the original file
     pub source_maps: bool,
-    /// Inline source maps as data URLs (native SWC path only)
+    /// Also append the source map to the code as a data URL comment
     pub inline_source_map: bool,
     /// Apply compat downlevel transforms for older engines (native SWC path only)
     pub compat_for_jsc: bool,
     /// Debug level for transpilation logging
     pub debug_level: DebugLevel,
+    /// JSX runtime used by both the custom parser and SWC; pragma comments in the file override it
+    pub jsx_runtime: JsxRuntime,
+    /// Development output: `jsxDEV(type, props, key, isStaticChildren, source, this)` for the
+    /// automatic runtime, `__self`/`__source` props for the classic one
+    pub development: bool,
+    /// Hoist JSX whose type, props and children are all constants to module-level constants,
+    /// so it is created once instead of on every render. Ignored in development output.
+    pub hoist_static_jsx: bool,
+    /// Accept HTML pasted into JSX (custom parser only): close void elements such as `<br>`,
+    /// rename `class`, `for` and other HTML attributes to their React props, and turn `style`
+    /// strings and string event handlers into objects and functions, warning about each fix
+    pub html_compat: bool,
+    /// Intrinsic element table replacing the target's built-in one
+    /// ([`TranspileTarget::element_map`]); `Some(ElementMap::default())` turns mapping off
+    pub element_map: Option<ElementMap>,
+    /// What JSX compiles to (custom parser only). [`JsxBackend::Dom`] ignores `jsx_runtime`,
+    /// `development` and element maps; hoisted static JSX is cloned with `cloneNode(true)`.
+    pub jsx_backend: JsxBackend,
 }
 
 impl Default for TranspileOptions {
@@ -54,10 +200,23 @@ impl Default for TranspileOptions {
             inline_source_map: false,
             compat_for_jsc: true,
             debug_level: DebugLevel::default(),
+            jsx_runtime: JsxRuntime::default(),
+            development: false,
+            hoist_static_jsx: false,
+            html_compat: false,
+            element_map: None,
+            jsx_backend: JsxBackend::Runtime,
         }
     }
 }
 
+impl TranspileOptions {
+    /// The element map in effect: the configured one, else the target's built-in table
+    pub(crate) fn resolved_element_map(&self) -> Option<&ElementMap> {
+        self.element_map.as_ref().or_else(|| self.target.element_map())
+    }
+}
+
 /// Describes a single import statement
 #[cfg_attr(feature = "wasm", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq)]
@@ -110,12 +269,32 @@ pub fn version() -> &'static str {
 
 /// Simple JSX to JS transpiler using custom parser
 /// Outputs direct calls to __hook_jsx_runtime.jsx(...)
-pub fn transpile_jsx_simple(source: &str) -> Result<String, String> {
+pub fn transpile_jsx_simple(source: &str) -> Result<String, TranspileError> {
     transpile_jsx_with_options(source, &TranspileOptions::default())
 }
 
 /// Transpile JSX with options (e.g. TypeScript support)
-pub fn transpile_jsx_with_options(source: &str, opts: &TranspileOptions) -> Result<String, String> {
+///
+/// Errors carry a stable code and a span resolved against `source`, with `opts.filename` attached.
+pub fn transpile_jsx_with_options(source: &str, opts: &TranspileOptions) -> Result<String, TranspileError> {
+    transpile_jsx_with_source_map(source, opts).map(|output| output.code)
+}
+
+/// Transpiled code together with its source map and warnings
+#[cfg_attr(feature = "wasm", derive(Serialize))]
+#[derive(Debug, Clone)]
+pub struct TranspileOutput {
+    pub code: String,
+    /// v3 source map JSON when `source_maps` is set; `inline_source_map` also embeds it in `code`
+    pub source_map: Option<String>,
+    /// Non-fatal problems (unknown entities, elements or props the target can't map), with
+    /// spans resolved against the source
+    pub warnings: Vec<TranspileError>,
+}
+
+/// Transpile JSX with options, also returning the source map requested by
+/// `opts.source_maps` so it can be served separately from the code
+pub fn transpile_jsx_with_source_map(source: &str, opts: &TranspileOptions) -> Result<TranspileOutput, TranspileError> {
     let debug_ctx = DebugContext::new(opts.debug_level);
     
     debug_ctx.info(format!("Starting transpilation for target: {:?}", opts.target));
@@ -129,11 +308,17 @@ pub fn transpile_jsx_with_options(source: &str, opts: &TranspileOptions) -> Resu
     #[cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
     if opts.target == TranspileTarget::Android {
         debug_ctx.trace("Using SWC transpiler for Android target");
-        let mut code = swc_native::transpile_with_swc(source, opts)
+        let swc_native::SwcOutput { mut code, source_map, warnings } = swc_native::transpile_with_swc(source, opts)
             .map_err(|e| {
-                debug_ctx.error(format!("SWC transpile error: {}", e));
-                format!("SWC transpile failed: {e}")
+                let err = TranspileError::from_anyhow(e, ErrorCode::SwcParse)
+                    .with_source(source, opts.filename.as_deref());
+                log_error(&debug_ctx, "SWC transpile error", &err);
+                err
             })?;
+        let warnings = with_sources(warnings, source, opts);
+        for warning in &warnings {
+            log_error(&debug_ctx, "SWC warning", warning);
+        }
         
         // Apply dynamic import transformation (import() -> __hook_import())
         // Always run this since SWC preserves import() calls
@@ -141,37 +326,174 @@ pub fn transpile_jsx_with_options(source: &str, opts: &TranspileOptions) -> Resu
         code = jsx_parser::transform_dynamic_imports(&code);
         
         debug_ctx.info("Transpilation completed successfully");
-        return Ok(code);
+        return Ok(with_source_map(code, source_map, warnings, opts));
     }
 
     debug_ctx.trace("Using JSX parser for transpilation");
-    let jsx_output = jsx_parser::transpile_jsx(source, opts).map_err(|e| {
-        debug_ctx.error(format!("JSX parse error: {}", e));
-        e.to_string()
+    let (jsx_output, warnings) = jsx_parser::transpile_jsx_with_warnings(source, opts).map_err(|e| {
+        let err = TranspileError::from_anyhow(e, ErrorCode::Internal)
+            .with_source(source, opts.filename.as_deref());
+        log_error(&debug_ctx, "JSX parse error", &err);
+        err
     })?;
+    let warnings = with_sources(warnings, source, opts);
+    for warning in &warnings {
+        log_error(&debug_ctx, "JSX warning", warning);
+    }
     debug_ctx.trace("JSX transformation complete");
+    let (mut code, jsx_map) = jsx_output.into_parts();
+    // Each pass's map leads back to the previous pass's output, the first to `source`
+    let mut maps = vec![jsx_map];
     
     // For Android/iOS targets, apply ES5 downleveling for JavaScriptCore
     // Web target doesn't need transformation (modern browsers support ES2020+)
     if opts.target == TranspileTarget::Android {
         debug_ctx.trace("Applying ES5 downleveling for Android JavaScriptCore");
-        let downleveled = swc_transformer::downlevel_for_jsc(&jsx_output)
+        let (downleveled, downlevel_map) = swc_transformer::downlevel_for_jsc_with_map(&code)
             .map_err(|e| {
-                debug_ctx.error(format!("ES5 downlevel error: {}", e));
-                format!("ES5 transformation failed: {}", e)
+                let mut err = TranspileError::from_anyhow(e, ErrorCode::Downlevel);
+                err.message = format!("ES5 transformation failed: {}", err.message);
+                err.filename = opts.filename.clone();
+                log_error(&debug_ctx, "ES5 downlevel error", &err);
+                err
             })?;
         
         // CRITICAL: Transform dynamic imports after downleveling
         // This ensures import() calls become __hook_import() calls
         debug_ctx.trace("Applying dynamic import transformation");
-        let transformed = jsx_parser::transform_dynamic_imports(&downleveled);
-        
-        debug_ctx.info("Transpilation completed successfully");
-        return Ok(transformed);
+        let (transformed, import_map) = jsx_parser::transform_dynamic_imports_with_map(&downleveled);
+        code = transformed;
+        maps.extend([downlevel_map, import_map]);
     }
     
+    let source_map = opts.source_maps.then(|| {
+        debug_ctx.trace("Building source map");
+        let filename = opts.filename.as_deref().unwrap_or("hook.jsx");
+        source_map::source_map(source, &code, &maps.iter().collect::<Vec<_>>(), filename)
+    });
     debug_ctx.info("Transpilation completed successfully");
-    Ok(jsx_output)
+    Ok(with_source_map(code, source_map, warnings, opts))
+}
+
+/// Package code with its source map, embedding the map when `inline_source_map` is set
+fn with_source_map(
+    mut code: String,
+    source_map: Option<String>,
+    warnings: Vec<TranspileError>,
+    opts: &TranspileOptions,
+) -> TranspileOutput {
+    if opts.inline_source_map
+        && let Some(map) = &source_map
+    {
+        code.push_str(&source_map::inline_source_map_comment(map));
+    }
+    TranspileOutput { code, source_map, warnings }
+}
+
+/// Attach `source` and the filename to each diagnostic so its span and code frame resolve
+fn with_sources(diagnostics: Vec<TranspileError>, source: &str, opts: &TranspileOptions) -> Vec<TranspileError> {
+    diagnostics
+        .into_iter()
+        .map(|d| d.with_source(source, opts.filename.as_deref()))
+        .collect()
+}
+
+/// Best-effort output and every diagnostic from an error-recovering transpile
+#[cfg_attr(feature = "wasm", derive(Serialize))]
+#[derive(Debug, Clone)]
+pub struct RecoveryResult {
+    pub code: String,
+    pub diagnostics: Vec<TranspileError>,
+}
+
+/// Transpile in error-recovery mode using the custom JSX parser
+///
+/// Instead of stopping at the first error, the parser resyncs at closing tags, `}` and
+/// statement boundaries and reports every problem at once. Broken elements become `null`
+/// in the output. Android targets still get ES5 downleveling of the best-effort code.
+pub fn transpile_jsx_recovering(source: &str, opts: &TranspileOptions) -> RecoveryResult {
+    let debug_ctx = DebugContext::new(opts.debug_level);
+    debug_ctx.trace("Using JSX parser in recovery mode");
+
+    let (mut code, diagnostics) = jsx_parser::transpile_jsx_recovering(source, opts);
+    let diagnostics = with_sources(diagnostics, source, opts);
+    for err in &diagnostics {
+        log_error(&debug_ctx, "JSX parse error", err);
+    }
+
+    if opts.target == TranspileTarget::Android
+        && let Ok(downleveled) = swc_transformer::downlevel_for_jsc(&code)
+    {
+        code = jsx_parser::transform_dynamic_imports(&downleveled);
+    }
+
+    debug_ctx.info(format!("Recovery transpile finished with {} diagnostics", diagnostics.len()));
+    RecoveryResult { code, diagnostics }
+}
+
+/// Parse a file into its typed tree without generating code
+///
+/// Uses the custom parser in both JavaScript and TypeScript mode (type syntax is removed
+/// first, so expression text is the stripped code). Node spans and errors refer to
+/// `source`; warnings are logged as in [`transpile_jsx_with_options`].
+pub fn parse_module(source: &str, opts: &TranspileOptions) -> Result<ast::Module, TranspileError> {
+    let debug_ctx = DebugContext::new(opts.debug_level);
+    let parsed = jsx_parser::parse_module(source, opts, false).map_err(|e| {
+        let err = TranspileError::from_anyhow(e, ErrorCode::Internal)
+            .with_source(source, opts.filename.as_deref());
+        log_error(&debug_ctx, "JSX parse error", &err);
+        err
+    })?;
+    for warning in parsed.diagnostics {
+        log_error(&debug_ctx, "JSX warning", &warning.with_source(source, opts.filename.as_deref()));
+    }
+    Ok(parsed.module)
+}
+
+/// [`parse_module`] serialized to JSON, for hosts that analyze hooks without re-parsing them
+#[cfg(feature = "ast-json")]
+pub fn parse_module_json(source: &str, opts: &TranspileOptions) -> Result<String, TranspileError> {
+    let module = parse_module(source, opts)?;
+    serde_json::to_string(&module).map_err(|e| TranspileError::new(ErrorCode::Internal, e.to_string()))
+}
+
+/// Compile every outermost JSX element or fragment of a file to data a native renderer can
+/// inflate without evaluating JavaScript (see [`ui_tree`])
+///
+/// Parses like [`parse_module`]; intrinsic tags and props are renamed by the element map in
+/// effect for `opts`, as in the generated code.
+pub fn compile_ui_tree(source: &str, opts: &TranspileOptions) -> Result<ui_tree::UiTree, TranspileError> {
+    let module = parse_module(source, opts)?;
+    Ok(ui_tree::UiTree::from_module(&module, opts.resolved_element_map()))
+}
+
+/// [`compile_ui_tree`] serialized to JSON, for hosts that ship the tree instead of code
+#[cfg(feature = "ast-json")]
+pub fn compile_ui_tree_json(source: &str, opts: &TranspileOptions) -> Result<String, TranspileError> {
+    let tree = compile_ui_tree(source, opts)?;
+    serde_json::to_string(&tree).map_err(|e| TranspileError::new(ErrorCode::Internal, e.to_string()))
+}
+
+/// Render every outermost JSX element or fragment of a file to HTML for first paint, with
+/// the dynamic parts marked for hydration (see [`html_prerender`])
+///
+/// Parses like [`parse_module`]; the markup is what React DOM renders on the web.
+pub fn prerender_html(source: &str, opts: &TranspileOptions) -> Result<html_prerender::PrerenderedHtml, TranspileError> {
+    let module = parse_module(source, opts)?;
+    Ok(html_prerender::PrerenderedHtml::from_module(&module, source))
+}
+
+/// Record a structured error or warning in the debug log, keeping its position
+fn log_error(debug_ctx: &DebugContext, context: &str, err: &TranspileError) {
+    let level = match err.severity {
+        Severity::Error => DebugLevel::Error,
+        Severity::Warning => DebugLevel::Warn,
+    };
+    debug_ctx.log_at(
+        level,
+        format!("{} [{}]: {}", context, err.code, err.message),
+        err.span.as_deref().map(|span| span.start_position()),
+    );
 }
 
 /// Transform ES6 modules to CommonJS
@@ -218,7 +540,7 @@ pub struct TranspileResult {
 
 /// Transpile JSX with metadata extraction
 /// This is the primary entry point for web clients needing full analysis
-pub fn transpile_jsx_with_metadata(source: &str, _filename: Option<&str>, is_typescript: bool) -> Result<TranspileResult, String> {
+pub fn transpile_jsx_with_metadata(source: &str, _filename: Option<&str>, is_typescript: bool) -> Result<TranspileResult, TranspileError> {
     let debug_ctx = DebugContext::new(DebugLevel::default());
     
     debug_ctx.trace("Extracting metadata from source");
@@ -360,6 +682,12 @@ mod tests {
             inline_source_map: true,
             compat_for_jsc: true,
             debug_level: Default::default(),
+            jsx_runtime: Default::default(),
+            development: false,
+            hoist_static_jsx: false,
+            html_compat: false,
+            element_map: None,
+            jsx_backend: JsxBackend::Runtime,
         };
 
         let code = r#"
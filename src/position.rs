@@ -0,0 +1,136 @@
+//! Positions in source text, counted in every unit a consumer may need.
+//!
+//! The parser and Rust tooling slice `str`s by byte, editors often count chars, and
+//! JavaScript and Java strings (and with them browser devtools and the Android overlay)
+//! count UTF-16 code units. The three only agree on ASCII text, so a [`Position`] carries all of them
+//! and each host reads the one its strings use.
+
+#[cfg(any(feature = "wasm", feature = "ast-json"))]
+use serde::{Deserialize, Serialize};
+
+/// A position in source text
+#[cfg_attr(any(feature = "wasm", feature = "ast-json"), derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    /// Byte offset, for slicing a Rust `str`
+    pub offset: usize,
+    /// Char (Unicode scalar value) offset
+    pub char_offset: usize,
+    /// UTF-16 code unit offset, for slicing a JavaScript or Java string
+    pub utf16_offset: usize,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column in chars
+    pub column: usize,
+    /// 1-based column in UTF-16 code units
+    pub utf16_column: usize,
+}
+
+/// Converts between the position units of one text
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    /// Byte offset of each char, followed by the text's length
+    char_bytes: Vec<usize>,
+    /// UTF-16 offset of each char, followed by the text's UTF-16 length
+    char_utf16: Vec<usize>,
+    /// Char index at which each line starts
+    line_starts: Vec<usize>,
+}
+
+impl Default for LineIndex {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut char_bytes = Vec::with_capacity(text.len() + 1);
+        let mut char_utf16 = Vec::with_capacity(text.len() + 1);
+        let mut line_starts = vec![0];
+        let mut utf16 = 0;
+        for (i, (byte, ch)) in text.char_indices().enumerate() {
+            char_bytes.push(byte);
+            char_utf16.push(utf16);
+            utf16 += ch.len_utf16();
+            if ch == '\n' {
+                line_starts.push(i + 1);
+            }
+        }
+        char_bytes.push(text.len());
+        char_utf16.push(utf16);
+        Self { char_bytes, char_utf16, line_starts }
+    }
+
+    /// Length of the text in chars
+    pub fn len_chars(&self) -> usize {
+        self.char_bytes.len() - 1
+    }
+
+    /// Position of char index `pos`, clamped to the end of the text
+    pub fn position(&self, pos: usize) -> Position {
+        let pos = pos.min(self.len_chars());
+        let line = self.line_starts.partition_point(|&start| start <= pos);
+        let line_start = self.line_starts[line - 1];
+        Position {
+            offset: self.char_bytes[pos],
+            char_offset: pos,
+            utf16_offset: self.char_utf16[pos],
+            line,
+            column: pos - line_start + 1,
+            utf16_column: self.char_utf16[pos] - self.char_utf16[line_start] + 1,
+        }
+    }
+
+    /// Char index of byte offset `byte`, rounding down to the start of its char
+    pub fn char_at_byte(&self, byte: usize) -> usize {
+        self.char_bytes.partition_point(|&b| b <= byte).saturating_sub(1)
+    }
+
+    /// Char index of UTF-16 offset `utf16`, rounding down out of a surrogate pair
+    pub fn char_at_utf16(&self, utf16: usize) -> usize {
+        self.char_utf16.partition_point(|&u| u <= utf16).saturating_sub(1)
+    }
+
+    /// Position of byte offset `byte`
+    pub fn position_at_byte(&self, byte: usize) -> Position {
+        self.position(self.char_at_byte(byte))
+    }
+
+    /// Position of UTF-16 offset `utf16`, as a JavaScript or Java host counts
+    pub fn position_at_utf16(&self, utf16: usize) -> Position {
+        self.position(self.char_at_utf16(utf16))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units_diverge_after_non_ascii_text() {
+        // `é` is 2 bytes, `😀` is 4 bytes and a surrogate pair, `中` is 3 bytes
+        let text = "aé\n😀中x";
+        let index = LineIndex::new(text);
+        let x = index.position(5);
+        assert_eq!(&text[x.offset..], "x");
+        assert_eq!(
+            x,
+            Position { offset: 11, char_offset: 5, utf16_offset: 6, line: 2, column: 3, utf16_column: 4 }
+        );
+        assert_eq!(index.position(99), index.position(6));
+        assert_eq!(index.position(0), Position { line: 1, column: 1, utf16_column: 1, ..Default::default() });
+    }
+
+    #[test]
+    fn conversions_round_down_inside_a_char() {
+        let index = LineIndex::new("a😀b");
+        assert_eq!(index.char_at_byte(1), 1);
+        assert_eq!(index.char_at_byte(3), 1);
+        assert_eq!(index.char_at_byte(5), 2);
+        assert_eq!(index.char_at_utf16(2), 1);
+        assert_eq!(index.char_at_utf16(3), 2);
+        assert_eq!(index.position_at_utf16(3).offset, 5);
+        assert_eq!(index.position_at_byte(5).utf16_offset, 3);
+    }
+}
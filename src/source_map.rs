@@ -0,0 +1,328 @@
+//! Position tracking through the custom pipeline, and v3 source maps built from it.
+//!
+//! Every pass that rewrites text (TypeScript stripping, JSX code generation, downleveling,
+//! dynamic import rewriting) builds its output in a [`MappedText`], which records where each
+//! run of output came from in the pass's input. A run is either copied, so positions advance
+//! in lockstep with the input, or generated for a construct, so all of it maps to where that
+//! construct starts. Following the passes' [`OffsetMap`]s from last to first gives the
+//! original position of any output position, which is all [`source_map`] needs.
+
+use base64::engine::general_purpose::STANDARD as Base64;
+use base64::Engine;
+
+use crate::lexer::Lexer;
+use crate::position::LineIndex;
+
+/// Start of a run of output: output byte offset, input byte offset, and whether the run was
+/// copied from the input (positions advance together) or generated (all of it maps to `orig`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Anchor {
+    out: usize,
+    orig: usize,
+    copied: bool,
+}
+
+/// Maps byte offsets in rewritten text (e.g. after TypeScript stripping) back to the text
+/// it was produced from
+#[derive(Debug, Clone, Default)]
+pub struct OffsetMap {
+    /// Runs in increasing `out` order; text before the first one maps to itself
+    anchors: Vec<Anchor>,
+}
+
+impl OffsetMap {
+    pub fn original_pos(&self, pos: usize) -> usize {
+        let idx = self.anchors.partition_point(|anchor| anchor.out <= pos);
+        match idx.checked_sub(1).map(|i| self.anchors[i]) {
+            Some(anchor) if anchor.copied => anchor.orig + (pos - anchor.out),
+            Some(anchor) => anchor.orig,
+            None => pos,
+        }
+    }
+}
+
+/// Output text of a pass together with the map back to the pass's input
+#[derive(Debug, Clone, Default)]
+pub struct MappedText {
+    text: String,
+    map: OffsetMap,
+}
+
+impl MappedText {
+    /// `text` generated for the input construct at byte offset `orig`
+    pub fn generated(text: &str, orig: usize) -> Self {
+        let mut out = Self::default();
+        out.generate(text, orig);
+        out
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Continue the current run with `ch`
+    pub fn push(&mut self, ch: char) {
+        self.text.push(ch);
+    }
+
+    /// Continue the current run with `s`
+    pub fn push_str(&mut self, s: &str) {
+        self.text.push_str(s);
+    }
+
+    /// The next output byte is copied from input byte offset `orig`
+    pub fn anchor(&mut self, orig: usize) {
+        self.map.anchors.push(Anchor { out: self.text.len(), orig, copied: true });
+    }
+
+    /// Copy `s` from input byte offset `orig`, starting a new run only if the current one
+    /// does not already continue there
+    pub fn copy(&mut self, s: &str, orig: usize) {
+        let continues = match self.map.anchors.last() {
+            Some(anchor) => anchor.copied && anchor.orig + (self.text.len() - anchor.out) == orig,
+            None => false,
+        };
+        if !continues {
+            self.anchor(orig);
+        }
+        self.push_str(s);
+    }
+
+    /// Copy `s`, which starts at byte offset `start` of a text that `map` maps back to this
+    /// pass's input (e.g. code out of the TypeScript-stripped text)
+    pub fn copy_mapped(&mut self, s: &str, start: usize, map: &OffsetMap) {
+        let first = map.anchors.partition_point(|anchor| anchor.out <= start);
+        let (mut rest, mut pos) = (s, start);
+        for anchor in &map.anchors[first..] {
+            let split = anchor.out - pos;
+            if split >= rest.len() {
+                break;
+            }
+            if split > 0 {
+                self.copy(&rest[..split], map.original_pos(pos));
+            }
+            rest = &rest[split..];
+            pos = anchor.out;
+        }
+        if !rest.is_empty() {
+            self.copy(rest, map.original_pos(pos));
+        }
+    }
+
+    /// Add `s` generated for the input construct at byte offset `orig`
+    pub fn generate(&mut self, s: &str, orig: usize) {
+        let continues = self.map.anchors.last().is_some_and(|anchor| !anchor.copied && anchor.orig == orig);
+        if !continues {
+            self.map.anchors.push(Anchor { out: self.text.len(), orig, copied: false });
+        }
+        self.push_str(s);
+    }
+
+    /// Append output built separately for the same input. Text before its first run
+    /// continues the current run, and a generated run resumes after it.
+    pub fn append(&mut self, other: &MappedText) {
+        let resume = self.map.anchors.last().copied().filter(|anchor| !anchor.copied);
+        let base = self.text.len();
+        self.map.anchors.extend(other.map.anchors.iter().map(|anchor| Anchor { out: base + anchor.out, ..*anchor }));
+        self.push_str(&other.text);
+        if let Some(anchor) = resume
+            && !other.map.anchors.is_empty()
+        {
+            self.map.anchors.push(Anchor { out: self.text.len(), ..anchor });
+        }
+    }
+
+    /// Drop the output from byte offset `len` on
+    pub fn truncate(&mut self, len: usize) {
+        self.text.truncate(len);
+        while self.map.anchors.last().is_some_and(|anchor| anchor.out >= len) {
+            self.map.anchors.pop();
+        }
+    }
+
+    /// Input byte offset of the output at byte offset `index`
+    pub fn original_pos_of(&self, index: usize) -> usize {
+        self.map.original_pos(index)
+    }
+
+    pub fn into_parts(self) -> (String, OffsetMap) {
+        (self.text, self.map)
+    }
+}
+
+/// Text with no runs of its own, which continues whatever run it is appended after
+impl From<&str> for MappedText {
+    fn from(text: &str) -> Self {
+        Self { text: text.to_string(), map: OffsetMap::default() }
+    }
+}
+
+const BASE64_DIGITS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Append `value` as a base64 VLQ, the number encoding of the `mappings` field
+fn push_vlq(out: &mut String, value: i64) {
+    let mut rest = if value < 0 { ((-value as u64) << 1) | 1 } else { (value as u64) << 1 };
+    loop {
+        let digit = (rest & 31) as usize;
+        rest >>= 5;
+        out.push(BASE64_DIGITS[digit | if rest > 0 { 32 } else { 0 }] as char);
+        if rest == 0 {
+            break;
+        }
+    }
+}
+
+/// A v3 source map (JSON) for `generated`, the output of passes whose maps are given in
+/// pipeline order and lead back to `original`. Every token of the output gets a segment.
+pub fn source_map(original: &str, generated: &str, maps: &[&OffsetMap], filename: &str) -> String {
+    let original_lines = LineIndex::new(original);
+
+    let mut mappings = String::new();
+    let mut column = 0;
+    let mut scanned = 0;
+    // The previous segment: generated column, original line and column
+    let (mut prev_column, mut prev_line, mut prev_orig_column) = (0, 0, 0);
+    let mut prev_orig = None;
+    for token in Lexer::new(generated, 0, generated.len()).filter(|token| !token.is_trivia()) {
+        for ch in generated[scanned..token.start].chars() {
+            if ch == '\n' {
+                mappings.push(';');
+                column = 0;
+                prev_column = 0;
+                prev_orig = None;
+            } else {
+                column += ch.len_utf16();
+            }
+        }
+        scanned = token.start;
+
+        let orig = maps.iter().rev().fold(token.start, |pos, map| map.original_pos(pos));
+        // Later tokens of a generated run add nothing
+        if prev_orig == Some(orig) {
+            continue;
+        }
+        prev_orig = Some(orig);
+        // Source maps count 0-based lines and UTF-16 columns
+        let position = original_lines.position_at_byte(orig);
+        let (orig_line, orig_column) = (position.line - 1, position.utf16_column - 1);
+        if !mappings.is_empty() && !mappings.ends_with(';') {
+            mappings.push(',');
+        }
+        push_vlq(&mut mappings, column as i64 - prev_column as i64);
+        push_vlq(&mut mappings, 0);
+        push_vlq(&mut mappings, orig_line as i64 - prev_line as i64);
+        push_vlq(&mut mappings, orig_column as i64 - prev_orig_column as i64);
+        (prev_column, prev_line, prev_orig_column) = (column, orig_line, orig_column);
+    }
+
+    serde_json::json!({
+        "version": 3,
+        "sources": [filename],
+        "sourcesContent": [original],
+        "names": [],
+        "mappings": mappings,
+    })
+    .to_string()
+}
+
+/// The comment that embeds `map` (JSON) in the code it describes
+pub fn inline_source_map_comment(map: &str) -> String {
+    format!("\n//# sourceMappingURL=data:application/json;base64,{}", Base64.encode(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vlq_encoding() {
+        let encode = |value| {
+            let mut out = String::new();
+            push_vlq(&mut out, value);
+            out
+        };
+        assert_eq!(encode(0), "A");
+        assert_eq!(encode(1), "C");
+        assert_eq!(encode(-1), "D");
+        assert_eq!(encode(15), "e");
+        assert_eq!(encode(16), "gB");
+        assert_eq!(encode(-1000), "x+B");
+    }
+
+    #[test]
+    fn copied_and_generated_runs() {
+        // `const x = a ?? b;` with the `??` expression rewritten
+        let mut out = MappedText::default();
+        out.copy("var x = ", 0);
+        out.generate("(a != null ? a : ", 8);
+        out.copy("b", 15);
+        out.generate(")", 8);
+        out.copy(";", 16);
+        let (text, map) = out.into_parts();
+        assert_eq!(text, "var x = (a != null ? a : b);");
+        assert_eq!(map.original_pos(4), 4);
+        assert_eq!(map.original_pos(12), 8);
+        assert_eq!(map.original_pos(25), 15);
+        assert_eq!(map.original_pos(26), 8);
+        assert_eq!(map.original_pos(27), 16);
+    }
+
+    #[test]
+    fn appended_pieces_and_truncation() {
+        let mut out = MappedText::generated("f(", 3);
+        let mut piece = MappedText::default();
+        piece.copy("x + y", 10);
+        out.append(&piece);
+        out.push_str(")");
+        assert_eq!(out.as_str(), "f(x + y)");
+        assert_eq!(out.original_pos_of(6), 14);
+        assert_eq!(out.original_pos_of(7), 3);
+
+        out.truncate(2);
+        out.copy("z)", 20);
+        let (text, map) = out.into_parts();
+        assert_eq!(text, "f(z)");
+        assert_eq!(map.original_pos(3), 21);
+    }
+
+    #[test]
+    fn copy_through_an_earlier_map() {
+        // Stripped text `let a = 1;` came from `let a: number = 1;`
+        let mut stripped = MappedText::default();
+        stripped.copy("let a", 0);
+        stripped.copy(" = 1;", 13);
+        let (text, strip_map) = stripped.into_parts();
+
+        let mut out = MappedText::default();
+        out.copy_mapped(&text[4..], 4, &strip_map);
+        let (text, map) = out.into_parts();
+        assert_eq!(text, "a = 1;");
+        assert_eq!(map.original_pos(0), 4);
+        assert_eq!(map.original_pos(2), 14);
+    }
+
+    #[test]
+    fn mappings_follow_lines_and_passes() {
+        let original = "const a = 1;\nconst b = <i/>;";
+        let mut first = MappedText::default();
+        first.copy("const a = 1;\nconst b = ", 0);
+        first.generate("h(\"i\")", 23);
+        first.copy(";", 27);
+        let (text, first_map) = first.into_parts();
+
+        let mut second = MappedText::default();
+        second.generate("var", 0);
+        second.copy(&text[5..13], 5);
+        second.generate("var", 13);
+        second.copy(&text[18..], 18);
+        let (generated, second_map) = second.into_parts();
+        assert_eq!(generated, "var a = 1;\nvar b = h(\"i\");");
+
+        let map = source_map(original, &generated, &[&first_map, &second_map], "a.jsx");
+        let json: serde_json::Value = serde_json::from_str(&map).unwrap();
+        assert_eq!(json["sources"][0], "a.jsx");
+        assert_eq!(json["sourcesContent"][0], original);
+        // var, a, =, 1, ; on line 1; var, b, =, h(…) (one run), ; on line 2
+        assert_eq!(json["mappings"], "AAAA,IAAM,EAAE,EAAE,CAAC;AACX,IAAM,EAAE,EAAE,MAAI");
+    }
+}
@@ -3,11 +3,13 @@ use std::panic::catch_unwind;
 use std::panic::AssertUnwindSafe;
 
 use anyhow::{Context, Result};
-use base64::engine::general_purpose::STANDARD as Base64;
-use base64::Engine;
-use swc_core::common::{comments::{Comments, NoopComments}, sync::Lrc, FileName, Globals, Mark, SourceMap, GLOBALS};
+use swc_core::common::{comments::{Comments, NoopComments}, sync::Lrc, util::take::Take, BytePos, FileName, Globals, Mark, SourceMap, Spanned, DUMMY_SP, GLOBALS};
 use swc_core::ecma::transforms::base::{feature::FeatureFlag, helpers::HELPERS};
-use swc_core::ecma::ast::{EsVersion, Program};
+use swc_core::ecma::ast::{
+    BindingIdent, Decl, EsVersion, Expr, Ident, IdentName, JSXAttrName, JSXAttrOrSpread, JSXAttrValue,
+    JSXElement, JSXElementChild, JSXElementName, JSXExpr, JSXExprContainer, Lit, Module, ModuleDecl,
+    ModuleItem, Program, Stmt, Str, UnaryOp, VarDecl, VarDeclKind, VarDeclarator,
+};
 use swc_core::ecma::codegen::{text_writer::JsWriter, Emitter, Config as CodegenConfig};
 use swc_core::ecma::parser::{EsConfig, Syntax, TsConfig, lexer::Lexer, Parser, StringInput};
 use swc_core::ecma::transforms::base::{fixer::fixer, resolver};
@@ -16,16 +18,21 @@ use swc_core::ecma::transforms::compat::es2020::{es2020, Config as Es2020Config}
 use swc_core::ecma::transforms::module::common_js::{common_js, Config as CjsConfig};
 use swc_core::ecma::transforms::react::{self, Runtime};
 use swc_core::ecma::transforms::typescript::strip;
-use swc_core::ecma::visit::FoldWith;
+use swc_core::ecma::visit::{FoldWith, VisitMut, VisitMutWith};
 
-use crate::TranspileOptions;
+use crate::{ElementMap, JsxRuntime, TranspileOptions, DEFAULT_JSX_RUNTIME};
+use crate::error::{ErrorCode, TranspileError};
+use crate::jsx_codegen::HOISTED_JSX_PREFIX;
 
 /// SWC-based transpilation pipeline for native targets (Android/iOS/desktop).
 /// - Parses JSX/TSX with SWC
-/// - Runs the React transform (automatic runtime)
+/// - Runs the React transform for the configured runtime (pragma comments applied)
 /// - Strips TypeScript when requested
 /// - Applies hygiene/fixer and emits ES2020+ JS (module format handled by caller)
-pub fn transpile_with_swc(source: &str, opts: &TranspileOptions) -> Result<String> {
+///
+/// Returns the code, with `opts.source_maps` its source map as JSON, and warnings about JSX
+/// the target renderer does not support.
+pub fn transpile_with_swc(source: &str, opts: &TranspileOptions) -> Result<SwcOutput> {
     // Wrap in catch_unwind to prevent panics from crashing JNI
     match catch_unwind(AssertUnwindSafe(|| transpile_with_swc_inner(source, opts))) {
         Ok(result) => result,
@@ -37,12 +44,39 @@ pub fn transpile_with_swc(source: &str, opts: &TranspileOptions) -> Result<Strin
             } else {
                 "Unknown panic in SWC transpiler".to_string()
             };
-            Err(anyhow::anyhow!("SWC panic: {}", panic_msg))
+            Err(TranspileError::new(ErrorCode::SwcPanic, format!("SWC panic: {}", panic_msg)).into())
         }
     }
 }
 
-fn transpile_with_swc_inner(source: &str, opts: &TranspileOptions) -> Result<String> {
+/// Output of [`transpile_with_swc`]
+pub struct SwcOutput {
+    pub code: String,
+    pub source_map: Option<String>,
+    /// Non-fatal diagnostics with byte spans into the source
+    pub warnings: Vec<TranspileError>,
+}
+
+/// SWC React transform options for a resolved runtime
+fn react_options(runtime: &JsxRuntime, development: bool) -> react::Options {
+    match runtime {
+        JsxRuntime::Automatic { import_source, .. } => react::Options {
+            runtime: Some(Runtime::Automatic),
+            development: Some(development),
+            import_source: Some(import_source.clone().unwrap_or_else(|| DEFAULT_JSX_RUNTIME.into())),
+            ..Default::default()
+        },
+        JsxRuntime::Classic { pragma, pragma_frag } => react::Options {
+            runtime: Some(Runtime::Classic),
+            development: Some(development),
+            pragma: Some(pragma.clone()),
+            pragma_frag: Some(pragma_frag.clone()),
+            ..Default::default()
+        },
+    }
+}
+
+fn transpile_with_swc_inner(source: &str, opts: &TranspileOptions) -> Result<SwcOutput> {
     let filename = opts
         .filename
         .as_deref()
@@ -67,10 +101,13 @@ fn transpile_with_swc_inner(source: &str, opts: &TranspileOptions) -> Result<Str
     let lexer = Lexer::new(syntax, EsVersion::Es2020, StringInput::from(&*fm), None);
     let mut parser = Parser::new_from(lexer);
 
-    let module = parser
-        .parse_module()
-        .map_err(|e| anyhow::anyhow!(e.kind().msg().to_string()))
-        .context("failed to parse module with SWC")?;
+    let module = parser.parse_module().map_err(|e| {
+        let span = e.span();
+        let start = (span.lo.0 - fm.start_pos.0) as usize;
+        let end = (span.hi.0.max(span.lo.0) - fm.start_pos.0) as usize;
+        TranspileError::new(ErrorCode::SwcParse, format!("SWC parse error: {}", e.kind().msg()))
+            .at_offsets(start, end)
+    })?;
 
     GLOBALS.set(&Globals::new(), || {
         HELPERS.set(&Default::default(), || {
@@ -93,21 +130,35 @@ fn transpile_with_swc_inner(source: &str, opts: &TranspileOptions) -> Result<Str
                 };
             }
 
+            // Map intrinsic elements for the target renderer while they are still JSX
+            let mut element_mapper = opts.resolved_element_map().map(|map| ElementMapper::new(map, fm.start_pos));
+            if let Some(mapper) = element_mapper.as_mut() {
+                module.visit_mut_with(mapper);
+            }
+
+            // Hoist static JSX while it is still JSX, so the React transform compiles the constants too
+            if opts.hoist_static_jsx && !opts.development {
+                module.visit_mut_with(&mut StaticJsxHoister::default());
+            }
+
             // Now run React transform on clean JavaScript
             module = module
                 .fold_with(&mut react::react(
                 cm.clone(),
                 comments.clone(),
-                react::Options {
-                    runtime: Some(Runtime::Automatic),
-                    development: Some(false),
-                    import_source: Some("__hook_jsx_runtime".into()),
-                    ..Default::default()
-                },
+                react_options(&opts.jsx_runtime.with_pragmas(source), opts.development),
                 top_level,
                 unresolved,
             ));
 
+            let warnings = match element_mapper {
+                Some(mut mapper) => {
+                    module.visit_mut_with(&mut mapper);
+                    mapper.warnings
+                }
+                None => Vec::new(),
+            };
+
             if opts.to_commonjs {
             module = module.fold_with(&mut common_js(
                 unresolved,
@@ -153,8 +204,9 @@ fn transpile_with_swc_inner(source: &str, opts: &TranspileOptions) -> Result<Str
             emitter.emit_module(&module)?;
         }
 
-        let mut code = String::from_utf8(buf).context("failed to encode SWC output as UTF-8")?;
+        let code = String::from_utf8(buf).context("failed to encode SWC output as UTF-8")?;
 
+        let mut source_map = None;
         if opts.source_maps {
             if !sm_buf.is_empty() {
                 let sm = cm.build_source_map(&sm_buf);
@@ -172,16 +224,241 @@ fn transpile_with_swc_inner(source: &str, opts: &TranspileOptions) -> Result<Str
                     map_obj["sourcesContent"] = serde_json::Value::Array(contents);
                 }
                 
-                let sm_json_final = serde_json::to_vec(&map_obj).context("failed to re-serialize source map")?;
-                let encoded = Base64.encode(sm_json_final);
-                if opts.inline_source_map {
-                    code.push_str("\n//# sourceMappingURL=data:application/json;base64,");
-                    code.push_str(&encoded);
-                }
+                source_map = Some(serde_json::to_string(&map_obj).context("failed to re-serialize source map")?);
             }
         }
 
-        Ok(code)
+        Ok(SwcOutput { code, source_map, warnings })
         })
     })
 }
+
+/// Moves JSX whose type, props and children are all constants into module-level `const`
+/// declarations after the imports and the directive prologue, mirroring the custom code generator's hoisting
+#[derive(Default)]
+struct StaticJsxHoister {
+    declarations: Vec<ModuleItem>,
+}
+
+impl StaticJsxHoister {
+    /// Declare a constant holding `init` and return a reference to it
+    fn hoist(&mut self, init: Expr) -> Box<Expr> {
+        let name = Ident::new_no_ctxt(format!("{}{}", HOISTED_JSX_PREFIX, self.declarations.len() + 1).into(), DUMMY_SP);
+        self.declarations.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            kind: VarDeclKind::Const,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: BindingIdent::from(name.clone()).into(),
+                init: Some(Box::new(init)),
+                definite: false,
+            }],
+            ..Default::default()
+        })))));
+        Box::new(Expr::Ident(name))
+    }
+
+    /// `{constant}` in place of a static child or attribute value
+    fn hoist_into_container(&mut self, init: Expr) -> JSXExprContainer {
+        JSXExprContainer { span: DUMMY_SP, expr: JSXExpr::Expr(self.hoist(init)) }
+    }
+}
+
+impl VisitMut for StaticJsxHoister {
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        module.visit_mut_children_with(self);
+        let after_imports = module
+            .body
+            .iter()
+            .rposition(|item| matches!(item, ModuleItem::ModuleDecl(ModuleDecl::Import(_))))
+            .map_or(0, |index| index + 1);
+        // `'use strict'` and other directives only apply while they come first
+        let after_directives = module.body.iter().take_while(|item| is_directive(item)).count();
+        let at = after_imports.max(after_directives);
+        module.body.splice(at..at, self.declarations.drain(..));
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        let is_static = match expr {
+            Expr::JSXElement(element) => is_static_element(element),
+            Expr::JSXFragment(fragment) => fragment.children.iter().all(is_static_child),
+            _ => false,
+        };
+        if is_static {
+            *expr = *self.hoist(expr.take());
+        } else {
+            expr.visit_mut_children_with(self);
+        }
+    }
+
+    fn visit_mut_jsx_element_child(&mut self, child: &mut JSXElementChild) {
+        let init = match child {
+            JSXElementChild::JSXElement(element) if is_static_element(element) => Expr::JSXElement(element.take()),
+            JSXElementChild::JSXFragment(fragment) if fragment.children.iter().all(is_static_child) => {
+                Expr::JSXFragment(fragment.take())
+            }
+            _ => return child.visit_mut_children_with(self),
+        };
+        *child = JSXElementChild::JSXExprContainer(self.hoist_into_container(init));
+    }
+
+    fn visit_mut_jsx_attr_value(&mut self, value: &mut JSXAttrValue) {
+        let init = match value {
+            JSXAttrValue::JSXElement(element) if is_static_element(element) => Expr::JSXElement(element.take()),
+            JSXAttrValue::JSXFragment(fragment) if fragment.children.iter().all(is_static_child) => {
+                Expr::JSXFragment(fragment.take())
+            }
+            _ => return value.visit_mut_children_with(self),
+        };
+        *value = JSXAttrValue::JSXExprContainer(self.hoist_into_container(init));
+    }
+}
+
+/// A statement of the directive prologue, such as `'use strict';`
+fn is_directive(item: &ModuleItem) -> bool {
+    matches!(item, ModuleItem::Stmt(Stmt::Expr(statement)) if matches!(*statement.expr, Expr::Lit(Lit::Str(_))))
+}
+
+/// An intrinsic element (string type) with literal props, no spreads and no `ref`, whose
+/// children are static
+fn is_static_element(element: &JSXElement) -> bool {
+    let intrinsic = match &element.opening.name {
+        JSXElementName::Ident(ident) => ident.sym.starts_with(|c: char| c.is_ascii_lowercase()) || ident.sym.contains('-'),
+        JSXElementName::JSXNamespacedName(_) => true,
+        JSXElementName::JSXMemberExpr(_) => false,
+    };
+    intrinsic
+        && element.opening.attrs.iter().all(|attr| match attr {
+            JSXAttrOrSpread::JSXAttr(attr) => {
+                !matches!(&attr.name, JSXAttrName::Ident(name) if &*name.sym == "ref")
+                    && match &attr.value {
+                        None | Some(JSXAttrValue::Lit(_)) => true,
+                        Some(JSXAttrValue::JSXExprContainer(container)) => is_static_container(container),
+                        Some(JSXAttrValue::JSXElement(element)) => is_static_element(element),
+                        Some(JSXAttrValue::JSXFragment(fragment)) => fragment.children.iter().all(is_static_child),
+                    }
+            }
+            JSXAttrOrSpread::SpreadElement(_) => false,
+        })
+        && element.children.iter().all(is_static_child)
+}
+
+fn is_static_child(child: &JSXElementChild) -> bool {
+    match child {
+        JSXElementChild::JSXText(_) => true,
+        JSXElementChild::JSXExprContainer(container) => is_static_container(container),
+        JSXElementChild::JSXSpreadChild(_) => false,
+        JSXElementChild::JSXElement(element) => is_static_element(element),
+        JSXElementChild::JSXFragment(fragment) => fragment.children.iter().all(is_static_child),
+    }
+}
+
+/// `{}` or a literal: a string, a template without interpolations, a possibly negated
+/// number, a boolean or `null`
+fn is_static_container(container: &JSXExprContainer) -> bool {
+    let JSXExpr::Expr(expr) = &container.expr else {
+        return true;
+    };
+    match &**expr {
+        Expr::Lit(lit) => !matches!(lit, Lit::Regex(_) | Lit::JSXText(_)),
+        Expr::Tpl(tpl) => tpl.exprs.is_empty(),
+        Expr::Unary(unary) => unary.op == UnaryOp::Minus && matches!(&*unary.arg, Expr::Lit(Lit::Num(_))),
+        _ => false,
+    }
+}
+
+/// Prefix of the placeholder tags standing in for renamed elements during the React
+/// transform; lowercase and hyphenated, so it is a string type and no JavaScript identifier
+const MAPPED_ELEMENT_PREFIX: &str = "hook-mapped-element-";
+
+/// Applies an [`ElementMap`] to intrinsic JSX elements and collects its warnings, mirroring
+/// the custom code generator. The React transform only passes lowercase tags as strings, so
+/// a tag renamed to e.g. `View` is a lowercase placeholder until a second visit, after the
+/// transform, replaces the placeholder string with the target's name.
+struct ElementMapper<'a> {
+    map: &'a ElementMap,
+    /// Position of the source file's first byte
+    base: BytePos,
+    /// Target names of the placeholder tags, by index
+    renamed: Vec<String>,
+    warnings: Vec<TranspileError>,
+}
+
+impl<'a> ElementMapper<'a> {
+    fn new(map: &'a ElementMap, base: BytePos) -> Self {
+        Self { map, base, renamed: Vec::new(), warnings: Vec::new() }
+    }
+
+    fn offset(&self, pos: BytePos) -> usize {
+        (pos.0 - self.base.0) as usize
+    }
+
+    /// The element name to write for the target's `name`
+    fn element_name(&mut self, name: &str, span: swc_core::common::Span) -> JSXElementName {
+        let name = if name.starts_with(|c: char| c.is_ascii_lowercase()) {
+            name.to_string()
+        } else {
+            self.renamed.push(name.to_string());
+            format!("{}{}", MAPPED_ELEMENT_PREFIX, self.renamed.len() - 1)
+        };
+        JSXElementName::Ident(Ident::new_no_ctxt(name.into(), span))
+    }
+}
+
+impl VisitMut for ElementMapper<'_> {
+    fn visit_mut_jsx_element(&mut self, element: &mut JSXElement) {
+        element.visit_mut_children_with(self);
+        let tag = match &element.opening.name {
+            JSXElementName::Ident(ident) if ident.sym.starts_with(|c: char| c.is_ascii_lowercase()) => ident.sym.to_string(),
+            JSXElementName::JSXNamespacedName(name) => format!("{}:{}", name.ns.sym, name.name.sym),
+            _ => return,
+        };
+
+        let props: Vec<(String, usize)> = element
+            .opening
+            .attrs
+            .iter()
+            .filter_map(|attr| match attr {
+                JSXAttrOrSpread::JSXAttr(attr) => Some((attr_name(&attr.name), self.offset(attr.span.lo))),
+                JSXAttrOrSpread::SpreadElement(_) => None,
+            })
+            .collect();
+        let props: Vec<(&str, usize)> = props.iter().map(|(name, at)| (name.as_str(), *at)).collect();
+        let warnings = self.map.check(&tag, self.offset(element.opening.name.span().lo), &props);
+        self.warnings.extend(warnings);
+
+        for attr in &mut element.opening.attrs {
+            if let JSXAttrOrSpread::JSXAttr(attr) = attr {
+                let name = attr_name(&attr.name);
+                let prop = self.map.prop(&tag, &name);
+                if prop != name {
+                    attr.name = JSXAttrName::Ident(IdentName::new(prop.into(), attr.name.span()));
+                }
+            }
+        }
+
+        let target = self.map.tag(&tag);
+        if target != tag {
+            let name = self.element_name(target, element.opening.name.span());
+            if let Some(closing) = element.closing.as_mut() {
+                closing.name = name.clone();
+            }
+            element.opening.name = name;
+        }
+    }
+
+    fn visit_mut_str(&mut self, str: &mut Str) {
+        if let Some(index) = str.value.strip_prefix(MAPPED_ELEMENT_PREFIX).and_then(|i| i.parse::<usize>().ok())
+            && let Some(name) = self.renamed.get(index)
+        {
+            *str = Str { span: str.span, value: name.as_str().into(), raw: None };
+        }
+    }
+}
+
+/// `name` or `ns:name` for an attribute
+fn attr_name(name: &JSXAttrName) -> String {
+    match name {
+        JSXAttrName::Ident(ident) => ident.sym.to_string(),
+        JSXAttrName::JSXNamespacedName(name) => format!("{}:{}", name.ns.sym, name.name.sym),
+    }
+}
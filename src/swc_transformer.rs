@@ -1,19 +1,38 @@
-//! Proper state machine transpiler for downleveling modern JavaScript to ES5
+//! Token-based transpiler for downleveling modern JavaScript to ES5
 //! Used for Android/iOS JavaScriptCore targets which lack ES2020+ support
 //!
 //! This module is only compiled for non-WASM targets to keep the WASM bundle small.
-//! Uses character-by-character parsing with full context awareness (strings, template literals, comments)
+//! Walks the shared lexer's tokens, so strings, comments and regex literals are never
+//! rewritten; template literals become string concatenation, and the code inside their
+//! interpolations is downleveled on its own
 
+use crate::jsx_codegen::escape_string;
+use crate::lexer::{self, cooked_string, TokenKind};
+use crate::source_map::{MappedText, OffsetMap};
 use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
-/// Transform modern JS (optional chaining, nullish coalescing, etc.)
+/// Transform modern JS (optional chaining, nullish coalescing, arrow functions, etc.)
 /// into ES5-compatible code for older JavaScriptCore engines.
 ///
 /// # Strategy
-/// 1. Parse character-by-character with full state tracking
-/// 2. Never transform code inside strings, template literals, or comments
+/// 1. Walk the source token by token with the shared lexer
+/// 2. Never transform code inside strings, comments or regex literals
 /// 3. Handle optional chaining (?.) → null checks
 /// 4. Handle nullish coalescing (??) → logical OR with typeof check
+/// 5. Handle arrow functions → `function` expressions, with `this` and `arguments` read
+///    through `_this` / `_arguments` aliases declared by the enclosing function (numbered
+///    like Babel's `generateUid`, `_this2`, when the source already uses the name)
+/// 6. Handle template literals → `"a" + String(x)`; tagged templates → calls with a frozen
+///    strings array (and its `raw`) created once per call site at the top of the output
+/// 7. Handle destructuring in declarations, `for … of`/`for … in` heads, assignments and
+///    parameters → plain assignments through `_ref` temporaries, default parameters →
+///    `=== void 0` checks and rest parameters → `arguments` slices. Array patterns index
+///    the value (loosely: arrays and array-likes, not arbitrary iterables)
+/// 8. Handle object literal shorthand properties and methods in place, object spread and
+///    computed keys → `__hook_assign(…)`, array spread → `concat` and call spread → `apply`
+///    (spread values, too, are arrays or array-likes)
 ///
 /// # Examples
 /// ```ignore
@@ -22,268 +41,1584 @@ use anyhow::Result;
 /// assert!(!result.contains("?."));
 /// ```
 pub fn downlevel_for_jsc(source: &str) -> Result<String> {
-    let mut transpiler = Transpiler::new(source);
-    Ok(transpiler.transpile())
+    downlevel_for_jsc_with_map(source).map(|(code, _)| code)
 }
 
-/// Full-context parser with state tracking
-struct Transpiler {
-    input: Vec<char>,
-    output: String,
-    pos: usize,
+/// [`downlevel_for_jsc`], also returning where each part of the output came from
+pub fn downlevel_for_jsc_with_map(source: &str) -> Result<(String, OffsetMap)> {
+    // Helpers go after `'use strict'` and other directives, which only apply while first
+    let prologue_end = lexer::directive_prologue_end(source, 0, source.len());
+    let mut transpiler = Transpiler::new(source, prologue_end..source.len(), false);
+    transpiler.shared = Shared::new(source);
+    transpiler.statements = true;
+    transpiler.transpile();
+    let shared = &transpiler.shared;
+    let mut header = String::new();
+    for (used, helper) in [
+        (shared.object_rest, OBJECT_REST_HELPER),
+        (shared.object_assign, OBJECT_ASSIGN_HELPER),
+        (shared.property, PROPERTY_HELPER),
+        (shared.spread, SPREAD_HELPER),
+    ] {
+        if used {
+            header.push_str(helper);
+        }
+    }
+    if !shared.templates.is_empty() {
+        header.push_str(TEMPLATE_OBJECT_HELPER);
+        for (i, strings) in shared.templates.iter().enumerate() {
+            header.push_str(&format!("var __hook_template_{} = {}; ", i + 1, strings));
+        }
+    }
+    // `this` at the top level, captured by an arrow function outside any other function
+    header.push_str(&transpiler.hoisted.declaration(shared));
+
+    let mut output = MappedText::default();
+    output.copy(&source[..prologue_end], 0);
+    if prologue_end > 0 && !header.is_empty() {
+        // The code after the directives starts with its own whitespace
+        output.generate(&format!(" {}", header.trim_end()), prologue_end);
+    } else if !header.is_empty() {
+        output.generate(&header, prologue_end);
+    }
+    output.append(&transpiler.output);
+    Ok(output.into_parts())
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum ParserState {
-    Normal,
-    InString(char),     // char is the quote type (' " `)
-    InLineComment,
-    InBlockComment,
+/// Keywords followed by a parenthesized head and a block that is not a function body
+const BLOCK_KEYWORDS: &[&str] = &["catch", "for", "if", "switch", "while", "with"];
+
+/// Builds the strings array a tag function receives: frozen, with the frozen raw strings as
+/// its non-enumerable `raw` property
+const TEMPLATE_OBJECT_HELPER: &str = "function __hook_template_object(cooked, raw) { return Object.freeze(Object.defineProperty(cooked, \"raw\", { value: Object.freeze(raw) })); } ";
+
+/// Copies the own enumerable properties of `source` other than the `excluded` keys, for
+/// `...rest` in an object pattern
+const OBJECT_REST_HELPER: &str = "function __hook_object_rest(source, excluded) { var rest = {}; for (var key in source) { if (Object.prototype.hasOwnProperty.call(source, key) && excluded.indexOf(key) < 0) { rest[key] = source[key]; } } return rest; } ";
+
+/// Copies the own enumerable properties of each source onto `target`, for object spread
+const OBJECT_ASSIGN_HELPER: &str = "function __hook_assign(target) { for (var i = 1; i < arguments.length; i++) { var source = arguments[i]; if (source != null) { for (var key in source) { if (Object.prototype.hasOwnProperty.call(source, key)) { target[key] = source[key]; } } } } return target; } ";
+
+/// Builds an object with one computed property, for computed keys
+const PROPERTY_HELPER: &str = "function __hook_property(key, value) { var object = {}; object[key] = value; return object; } ";
+
+/// The array a spread value stands for, so `concat` adds its elements
+const SPREAD_HELPER: &str = "function __hook_spread(value) { return Array.isArray(value) ? value : Array.prototype.slice.call(value); } ";
+
+/// What code inside a function needs declared at the function's start: aliases for the
+/// `this` and `arguments` that arrow functions read, and temporaries of destructuring
+/// assignments
+#[derive(Debug, Clone, Default)]
+struct Hoisted {
+    this: bool,
+    arguments: bool,
+    temps: Vec<String>,
 }
 
-impl Transpiler {
-    fn new(input: &str) -> Self {
+impl Hoisted {
+    fn merge(&mut self, other: Hoisted) {
+        self.this |= other.this;
+        self.arguments |= other.arguments;
+        self.temps.extend(other.temps);
+    }
+
+    /// The declarations (`var _this = this, _ref; `) to put at the start of the function
+    /// body, or nothing
+    fn declaration(&self, shared: &Shared) -> String {
+        let mut declarators = Vec::new();
+        if self.this {
+            declarators.push(format!("{} = this", shared.this_alias));
+        }
+        if self.arguments {
+            declarators.push(format!("{} = arguments", shared.arguments_alias));
+        }
+        declarators.extend(self.temps.iter().cloned());
+        if declarators.is_empty() {
+            return String::new();
+        }
+        format!("var {}; ", declarators.join(", "))
+    }
+}
+
+/// State of the whole source, shared by every part of it downleveled on its own
+#[derive(Debug, Default)]
+struct Shared {
+    /// The strings array expression of each tagged template, numbered from 1
+    templates: Vec<String>,
+    /// Whether an object pattern has a rest element, so the output needs its helper
+    object_rest: bool,
+    /// Whether an object literal is built with `__hook_assign`
+    object_assign: bool,
+    /// Whether an object literal has a computed key, built with `__hook_property`
+    property: bool,
+    /// Whether an array literal or call spreads a value
+    spread: bool,
+    /// Every identifier in the source and every name generated so far
+    names: HashSet<String>,
+    /// The next numeric suffix to try for each base of [`Shared::unique_name`]
+    suffixes: HashMap<String, usize>,
+    /// The names arrow functions read `this` and `arguments` through
+    this_alias: String,
+    arguments_alias: String,
+}
+
+impl Shared {
+    fn new(source: &str) -> Self {
+        let mut shared = Shared::default();
+        collect_identifiers(source, 0..source.len(), &mut shared.names);
+        shared.this_alias = shared.unique_name("this");
+        shared.arguments_alias = shared.unique_name("arguments");
+        shared
+    }
+
+    /// A name no identifier in the source uses, as Babel's `generateUid` picks it: `_base`,
+    /// then `_base2`, `_base3`, …
+    fn unique_name(&mut self, base: &str) -> String {
+        // Suffixes only grow, so each name is checked once however many are generated
+        let next = self.suffixes.entry(base.to_string()).or_insert(1);
+        loop {
+            let name = if *next == 1 { format!("_{}", base) } else { format!("_{}{}", base, next) };
+            *next += 1;
+            if self.names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+}
+
+/// The identifiers in `range` of `source`, including those in template interpolations
+fn collect_identifiers(source: &str, range: Range<usize>, names: &mut HashSet<String>) {
+    for token in lexer::Lexer::new(source, range.start, range.end) {
+        match token.kind {
+            TokenKind::Identifier => {
+                names.insert(token.text(source).to_string());
+            }
+            TokenKind::Template => {
+                for code in lexer::template_interpolations(source, &token) {
+                    collect_identifiers(source, code, names);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A destructuring pattern: `{ a, b: [c] = d, ...e }` or `[a, , b = c, ...d]`
+#[derive(Debug)]
+struct Pattern {
+    object: bool,
+    /// `None` for a hole in an array pattern
+    bindings: Vec<Option<Binding>>,
+}
+
+/// An element of a pattern or a parameter list
+#[derive(Debug)]
+struct Binding {
+    /// The property read, in an object pattern
+    key: Option<PropertyKey>,
+    target: Target,
+    /// The byte range of the default value's code
+    default: Option<Range<usize>>,
+    rest: bool,
+}
+
+#[derive(Debug)]
+enum PropertyKey {
+    /// `name`, read as `.name`
+    Name(String),
+    /// A string or number literal, read as `[literal]`
+    Literal(String),
+    /// `[code]`, with the byte range of the code
+    Computed(Range<usize>),
+}
+
+#[derive(Debug)]
+enum Target {
+    /// The byte range of a name, or of any assignable expression in an assignment
+    Expression(Range<usize>),
+    Pattern(Pattern),
+}
+
+/// A member of an object literal that needs lowering, or is kept as it is
+#[derive(Debug)]
+enum Member {
+    /// `key: value`, with the byte range of the value
+    Property(Range<usize>),
+    /// `name`
+    Shorthand,
+    /// `key(…) { … }`, with the byte offset where the key ends, the `(`, the index of the
+    /// `)` and the `{`
+    Method { key_end: usize, open: lexer::Token, close: usize, body: lexer::Token },
+    /// `[key]: value` or `[key](…) { … }`
+    Computed { key: Range<usize>, value: Box<Member> },
+    /// `...value`
+    Spread(Range<usize>),
+    /// Accessors, async and generator methods, left as they are
+    Other,
+}
+
+/// One assignment a lowered pattern makes, in order
+#[derive(Debug)]
+struct Step {
+    target: String,
+    value: String,
+    /// Whether `target` is a generated temporary
+    temp: bool,
+}
+
+/// Token-walking transpiler; `output` is built up as `input` is consumed
+struct Transpiler<'a> {
+    input: &'a str,
+    /// Char index where the code being downleveled ends
+    end: usize,
+    output: MappedText,
+    pos: usize,
+    /// Whether a `/` at `pos` starts a regex literal rather than a division
+    regex_allowed: bool,
+    /// The last significant token before `pos`, if it is still the one before the output's end
+    prev: Option<lexer::Token>,
+    /// Whether the code is inside an arrow function (and no regular function within it), so
+    /// `this` and `arguments` belong to the function around the arrow
+    in_arrow: bool,
+    /// What the code needs declared at the start of the enclosing function
+    hoisted: Hoisted,
+    /// Whether the code is a list of statements (a function body or the whole source), so a
+    /// `{` at its start opens a block rather than an object literal
+    statements: bool,
+    /// How many conditionals (`a ? b : c`) have seen their `?` but not their `:`
+    conditionals: usize,
+    /// Whether the last `:` was a conditional's rather than a label's or a `case`'s
+    conditional_colon: bool,
+    shared: Shared,
+}
+
+impl<'a> Transpiler<'a> {
+    fn new(input: &'a str, range: Range<usize>, in_arrow: bool) -> Self {
         Self {
-            input: input.chars().collect(),
-            output: String::new(),
-            pos: 0,
+            input,
+            end: range.end,
+            output: MappedText::default(),
+            pos: range.start,
+            regex_allowed: true,
+            prev: None,
+            in_arrow,
+            hoisted: Hoisted::default(),
+            statements: false,
+            conditionals: 0,
+            conditional_colon: false,
+            shared: Shared::default(),
+        }
+    }
+
+    /// Downlevel the byte range `range` on its own (template interpolations, call
+    /// arguments, operands), as part of the current function
+    fn downlevel(&mut self, range: Range<usize>) -> MappedText {
+        self.nested(range, self.in_arrow, false)
+    }
+
+    /// [`Self::downlevel`] as text
+    fn downleveled(&mut self, range: Range<usize>) -> String {
+        self.downlevel(range).as_str().to_string()
+    }
+
+    /// Downlevel the byte range `range` on its own, inside an arrow function if `in_arrow`,
+    /// as statements if `statements`. What it needs declared belongs to the current
+    /// function as well.
+    fn nested(&mut self, range: Range<usize>, in_arrow: bool, statements: bool) -> MappedText {
+        let (output, hoisted) = self.run(range, in_arrow, statements);
+        self.hoisted.merge(hoisted);
+        output
+    }
+
+    /// Downlevel the byte range `range` on its own, returning it with what it needs declared
+    /// at the start of the enclosing function
+    fn run(&mut self, range: Range<usize>, in_arrow: bool, statements: bool) -> (MappedText, Hoisted) {
+        let mut nested = Transpiler::new(self.input, range, in_arrow);
+        nested.statements = statements;
+        nested.shared = std::mem::take(&mut self.shared);
+        nested.transpile();
+        self.shared = nested.shared;
+        (nested.output, nested.hoisted)
+    }
+
+    /// Run `f` as code of a function of its own, or of an arrow function if `in_arrow`,
+    /// returning what it needs declared at the function's start separately
+    fn scoped<T>(&mut self, in_arrow: bool, f: impl FnOnce(&mut Self) -> T) -> (T, Hoisted) {
+        let in_arrow = std::mem::replace(&mut self.in_arrow, in_arrow);
+        let hoisted = std::mem::take(&mut self.hoisted);
+        let value = f(self);
+        self.in_arrow = in_arrow;
+        (value, std::mem::replace(&mut self.hoisted, hoisted))
+    }
+
+    /// A new temporary name: `_ref`, `_ref2`, …, skipping names the source uses
+    fn temp(&mut self) -> String {
+        self.shared.unique_name("ref")
+    }
+
+    fn transpile(&mut self) {
+        while self.pos < self.end {
+            let token = lexer::token_at(self.input, self.pos, self.end, self.regex_allowed);
+            self.regex_allowed = token.regex_allowed_after(self.input, self.regex_allowed);
+            let prev = if token.is_trivia() { self.prev } else { self.prev.replace(token) };
+
+            match token.kind {
+                TokenKind::Template => self.template_literal(token, prev),
+                // `x => …`
+                TokenKind::Identifier if self.arrow_after(token.end).is_some() => self.arrow_function(token),
+                TokenKind::Identifier => {
+                    // const/let -> var
+                    let word = token.text(self.input);
+                    let property = prev.is_some_and(|prev| prev.is_punct(self.input, '.'));
+                    // `this` and `arguments` of the function around an arrow, not `obj.this`
+                    let lexical = self.in_arrow && !property;
+                    let declaration =
+                        matches!(word, "const" | "let" | "var");
+                    if declaration && !property && self.destructuring_declaration(token) {
+                        continue;
+                    }
+                    if let Some(clause_end) = self.specifier_list_end(token).filter(|_| !property) {
+                        // `{ a as b }` of `import`/`export` is not an object literal
+                        self.output.copy(&self.input[token.start..clause_end], token.start);
+                        self.pos = clause_end;
+                        self.prev = lexer::Lexer::new(self.input, token.start, clause_end).filter(|t| !t.is_trivia()).last();
+                        continue;
+                    }
+                    if word == "const" || word == "let" {
+                        self.output.generate("var", token.start);
+                    } else if lexical && word == "this" {
+                        self.output.generate(&self.shared.this_alias, token.start);
+                        self.hoisted.this = true;
+                    } else if lexical && word == "arguments" {
+                        self.output.generate(&self.shared.arguments_alias, token.start);
+                        self.hoisted.arguments = true;
+                    } else {
+                        self.output.copy(word, token.start);
+                    }
+                    self.pos = token.end;
+                }
+                TokenKind::Punct if token.is_punct(self.input, '(') => self.handle_paren(token, prev),
+                TokenKind::Punct if self.is_destructuring_assignment(&token) => self.destructuring_assignment(token),
+                TokenKind::Punct if token.is_punct(self.input, '{') && self.starts_object_literal(&token, prev) => {
+                    self.object_literal(token)
+                }
+                TokenKind::Punct if token.is_punct(self.input, '[') && token.expression_position => self.array_literal(token),
+                TokenKind::Punct if token.is_punct(self.input, '?') => self.handle_question_mark(),
+                TokenKind::Punct if token.is_punct(self.input, ':') => {
+                    self.conditional_colon = self.conditionals > 0;
+                    self.conditionals = self.conditionals.saturating_sub(1);
+                    self.output.copy(":", token.start);
+                    self.pos = token.end;
+                }
+                _ => {
+                    self.output.copy(token.text(self.input), token.start);
+                    self.pos = token.end;
+                }
+            }
         }
     }
 
-    fn transpile(&mut self) -> String {
-        while self.pos < self.input.len() {
-            let state = self.current_state();
-            
-            match state {
-                ParserState::Normal => self.handle_normal(),
-                ParserState::InString(quote) => self.handle_string(quote),
-                ParserState::InLineComment => self.handle_line_comment(),
-                ParserState::InBlockComment => self.handle_block_comment(),
+    /// The `=` of an arrow's `=>` if it is the next significant token from byte offset `from`
+    fn arrow_after(&self, from: usize) -> Option<usize> {
+        let token = lexer::Lexer::new(self.input, from, self.end).peek_significant()?;
+        (token.is_punct(self.input, '=') && self.input[token.end..].starts_with('>') && token.end < self.end)
+            .then_some(token.start)
+    }
+
+    /// An opening parenthesis: the parameters of an arrow or regular function, whose body is
+    /// then converted or scoped, or any other group, which is walked as usual
+    fn handle_paren(&mut self, open: lexer::Token, prev: Option<lexer::Token>) {
+        let close = lexer::closing_bracket(self.input, open.end, self.end);
+        if close < self.end && self.arrow_after(close + 1).is_some() {
+            self.arrow_function(open);
+            return;
+        }
+        // `function (…) {`, `function name(…) {` and methods `name(…) {`
+        let after_head = lexer::Lexer::new(self.input, (close + 1).min(self.end), self.end).peek_significant();
+        let is_function_head = prev.is_some_and(|prev| {
+            let word = prev.text(self.input);
+            prev.kind == TokenKind::Identifier && !BLOCK_KEYWORDS.contains(&word)
+        }) && after_head.is_some_and(|token| token.is_punct(self.input, '{'));
+        let is_catch = prev.is_some_and(|prev| prev.kind == TokenKind::Identifier && prev.text(self.input) == "catch");
+        if let Some(body) = after_head.filter(|token| is_catch && close < self.end && token.is_punct(self.input, '{'))
+            && self.catch_parameter_pattern(open, close, body)
+        {
+            return;
+        }
+        let Some(body) = after_head.filter(|_| close < self.end && is_function_head) else {
+            let is_call = prev.is_some_and(|prev| match prev.kind {
+                TokenKind::Identifier => !prev.regex_allowed_after(self.input, true),
+                TokenKind::Punct => matches!(self.char_at(prev.start), ')' | ']'),
+                _ => false,
+            });
+            if !(is_call && close < self.end && self.spread_call(open, close)) {
+                self.output.copy("(", open.start);
+                self.pos = open.end;
             }
+            return;
+        };
+        self.function(open, close, body);
+    }
+
+    /// The parameters in `open`…`close` and the body opening at `body` of a regular
+    /// function, whose `function` keyword or name is already in the output
+    fn function(&mut self, open: lexer::Token, close: usize, body: lexer::Token) {
+        self.output.copy("(", open.start);
+        let ((params, prologue), hoisted) = self.scoped(false, |this| this.parameters(open.end..close));
+        self.output.append(&params);
+        self.output.copy(&self.input[close..body.start], close);
+        self.function_body(body.start, hoisted, &prologue);
+        self.regex_allowed = true;
+    }
+
+    /// The block of a regular function, opening at `open`, which has its own `this` and
+    /// `arguments`: arrow functions inside read them through aliases declared at its start.
+    /// `hoisted` is what its parameters need declared, `prologue` their statements.
+    fn function_body(&mut self, open: usize, mut hoisted: Hoisted, prologue: &str) {
+        let close = lexer::closing_bracket(self.input, open + 1, self.end);
+        // The function's own `'use strict'` stays first
+        let directives_end = lexer::directive_prologue_end(self.input, open + 1, close);
+        let (body, body_hoisted) = self.run(directives_end..close, false, true);
+        hoisted.merge(body_hoisted);
+        self.output.copy("{", open);
+        self.output.copy(&self.input[open + 1..directives_end], open + 1);
+        let declaration = hoisted.declaration(&self.shared);
+        if !declaration.is_empty() {
+            self.output.generate(&format!(" {}", declaration.trim_end()), directives_end);
         }
-        
-        self.output.clone()
+        if !prologue.is_empty() {
+            self.output.generate(&format!(" {}", prologue), directives_end);
+        }
+        self.output.append(&body);
+        if close < self.end {
+            self.output.copy("}", close);
+        }
+        self.pos = (close + 1).min(self.end);
+        self.prev = None;
     }
 
-    fn current_state(&self) -> ParserState {
-        if self.pos >= self.input.len() {
-            return ParserState::Normal;
+    /// `params => body` as a function expression, with `start` the single parameter or the
+    /// opening parenthesis of the list. An expression body is returned from a block, so an
+    /// object literal body (`() => ({ … })`) stays an expression.
+    fn arrow_function(&mut self, start: lexer::Token) {
+        let params_end = if start.kind == TokenKind::Identifier {
+            start.end
+        } else {
+            lexer::closing_bracket(self.input, start.end, self.end) + 1
+        };
+        let arrow = self.arrow_after(params_end).expect("arrow follows the parameters");
+        self.output.generate("function ", start.start);
+        let mut prologue = String::new();
+        if start.kind == TokenKind::Identifier {
+            self.output.push('(');
+            self.output.copy(&self.input[start.start..start.end], start.start);
+            self.output.push(')');
+        } else {
+            self.output.copy("(", start.start);
+            let (params, hoisted) = self.scoped(true, |this| this.parameters(start.end..params_end - 1));
+            self.hoisted.merge(hoisted);
+            self.output.append(&params.0);
+            prologue = params.1;
+            self.output.copy(")", params_end - 1);
+        }
+
+        let Some(body) = lexer::Lexer::new(self.input, arrow + 2, self.end).peek_significant() else {
+            self.pos = self.end;
+            return;
+        };
+        // Keep line breaks and comments around `=>`; plain spaces collapse to one
+        let gap = format!("{}{}", &self.input[params_end..arrow], &self.input[arrow + 2..body.start]);
+        if gap.trim().is_empty() && !gap.contains('\n') {
+            self.output.push(' ');
+        } else {
+            self.output.copy(&self.input[params_end..arrow], params_end);
+            self.output.copy(&self.input[arrow + 2..body.start], arrow + 2);
+        }
+
+        if body.is_punct(self.input, '{') {
+            let close = lexer::closing_bracket(self.input, body.end, self.end);
+            self.output.copy("{", body.start);
+            if !prologue.is_empty() {
+                self.output.generate(&format!(" {}", prologue), body.start);
+            }
+            let code = self.nested(body.end..close, true, true);
+            self.output.append(&code);
+            if close < self.end {
+                self.output.copy("}", close);
+            }
+            self.pos = (close + 1).min(self.end);
+        } else {
+            let end = self.expression_end(body.start);
+            self.output.generate("{ ", body.start);
+            if !prologue.is_empty() {
+                self.output.generate(&format!("{} ", prologue), body.start);
+            }
+            self.output.generate("return ", body.start);
+            let code = self.nested(body.start..end, true, false);
+            self.output.append(&code);
+            // Keep a trailing `//` comment from swallowing the end of the block
+            if code.as_str().lines().last().is_some_and(|line| line.contains("//")) {
+                self.output.push('\n');
+            }
+            self.output.push_str("; }");
+            self.pos = end;
         }
+        self.regex_allowed = false;
+        self.prev = None;
+    }
 
-        let ch = self.current_char();
-        
-        // Check for comment starts
-        if ch == Some('/') {
-            if self.peek(1) == Some('/') {
-                return ParserState::InLineComment;
+    /// End of the expression starting at `start` (an arrow function's body, an initializer
+    /// or an assigned value): the end of its last token before a `,`, `;`, unmatched closing
+    /// bracket, the `:` of an enclosing conditional, or a line break where a new statement
+    /// begins
+    fn expression_end(&self, start: usize) -> usize {
+        let (mut depth, mut conditionals) = (0usize, 0usize);
+        let mut last: Option<lexer::Token> = None;
+        let mut tokens = lexer::Lexer::new(self.input, start, self.end);
+        while let Some(token) = tokens.next() {
+            if token.kind == TokenKind::Whitespace {
+                let next = tokens.peek_significant();
+                if depth == 0
+                    && token.text(self.input).contains('\n')
+                    && last.is_some_and(|last| self.ends_operand(&last))
+                    && next.is_some_and(|next| self.starts_statement(&next))
+                {
+                    break;
+                }
+                continue;
+            }
+            if token.is_trivia() {
+                continue;
             }
-            if self.peek(1) == Some('*') {
-                return ParserState::InBlockComment;
+            if token.kind == TokenKind::Punct && token.end - token.start == 1 {
+                let at = |offset: isize| self.input.as_bytes().get(token.start.wrapping_add_signed(offset)).copied();
+                // A `?` that is not part of `??` or `?.` (but `?.5` is a conditional)
+                let conditional = at(-1) != Some(b'?')
+                    && at(1) != Some(b'?')
+                    && (at(1) != Some(b'.') || at(2).is_some_and(|c| c.is_ascii_digit()));
+                match self.char_at(token.start) {
+                    '(' | '[' | '{' => depth += 1,
+                    ')' | ']' | '}' if depth == 0 => break,
+                    ')' | ']' | '}' => depth -= 1,
+                    ';' | ',' if depth == 0 => break,
+                    '?' if depth == 0 && conditional => conditionals += 1,
+                    ':' if depth == 0 && conditionals == 0 => break,
+                    ':' if depth == 0 => conditionals -= 1,
+                    _ => {}
+                }
             }
+            last = Some(token);
         }
+        last.map_or(start, |last| last.end)
+    }
 
-        // Check for string starts
-        if ch == Some('"') || ch == Some('\'') || ch == Some('`') {
-            return ParserState::InString(ch.unwrap());
+    /// Whether `token` can end an expression, so a line break after it ends the statement
+    fn ends_operand(&self, token: &lexer::Token) -> bool {
+        match token.kind {
+            TokenKind::Identifier => !token.regex_allowed_after(self.input, true),
+            TokenKind::Punct => matches!(self.char_at(token.start), ')' | ']' | '}') || token.end - token.start == 2,
+            _ => true,
         }
+    }
 
-        ParserState::Normal
+    /// Whether `token` cannot continue the expression before it
+    fn starts_statement(&self, token: &lexer::Token) -> bool {
+        match token.kind {
+            TokenKind::Identifier => {
+                let word = token.text(self.input);
+                !matches!(word, "in" | "instanceof" | "of")
+            }
+            TokenKind::Punct => token.end - token.start == 2,
+            _ => true,
+        }
     }
 
-    fn handle_normal(&mut self) {
-        let ch = self.current_char();
-        
-        // Check for const/let -> var
-        if self.has_valid_word_prefix() {
-            if ch == Some('c') && self.peek(1) == Some('o') && self.peek(2) == Some('n') && self.peek(3) == Some('s') && self.peek(4) == Some('t') && self.is_word_boundary_at(self.pos + 5) {
-                self.output.push_str("var");
-                self.pos += 5;
-                return;
+    /// A parameter list in `range` as ES5: the names in the list, and the statements starting
+    /// the body that apply defaults, destructure patterns and collect the rest parameter
+    fn parameters(&mut self, range: Range<usize>) -> (MappedText, String) {
+        let bindings = self.bindings(range.clone(), false);
+        let plain = bindings
+            .iter()
+            .flatten()
+            .all(|binding| !binding.rest && binding.default.is_none() && matches!(binding.target, Target::Expression(_)));
+        if plain {
+            return (self.downlevel(range), String::new());
+        }
+        let (mut names, mut prologue) = (Vec::new(), Vec::new());
+        for (i, binding) in bindings.into_iter().flatten().enumerate() {
+            let mut steps = Vec::new();
+            if binding.rest {
+                let value = format!("Array.prototype.slice.call(arguments, {})", i);
+                self.bind(&binding.target, None, value, &mut steps);
+            } else if let Target::Expression(name) = &binding.target {
+                let name = self.input[name.clone()].to_string();
+                if let Some(default) = binding.default {
+                    let default = self.downleveled(default);
+                    prologue.push(format!("if ({0} === void 0) {0} = {1};", name, default));
+                }
+                names.push(name);
+            } else {
+                let temp = self.temp();
+                self.bind(&binding.target, binding.default, temp.clone(), &mut steps);
+                names.push(temp);
             }
-            if ch == Some('l') && self.peek(1) == Some('e') && self.peek(2) == Some('t') && self.is_word_boundary_at(self.pos + 3) {
-                self.output.push_str("var");
-                self.pos += 3;
-                return;
+            if !steps.is_empty() {
+                prologue.push(format!("var {};", declarators(&steps)));
             }
         }
+        (MappedText::generated(&names.join(", "), range.start), prologue.join(" "))
+    }
 
-        // Check for optional chaining
-        if ch == Some('?') && self.peek(1) == Some('.') {
-            if self.is_optional_chaining_context() {
-                self.transform_optional_chaining();
-                return;
+    /// A `var`, `let` or `const` declaration at `keyword` that destructures, with each
+    /// pattern lowered to plain declarators; `false`, with nothing consumed, if it has none
+    fn destructuring_declaration(&mut self, keyword: lexer::Token) -> bool {
+        // (target, its end, the initializer's range)
+        let mut list = Vec::new();
+        let mut from = keyword.end;
+        while let Some(target) = lexer::Lexer::new(self.input, from, self.end).peek_significant() {
+            let target_end = match target.kind {
+                TokenKind::Identifier => target.end,
+                TokenKind::Punct if matches!(self.char_at(target.start), '{' | '[') => {
+                    lexer::closing_bracket(self.input, target.end, self.end) + 1
+                }
+                _ => break,
+            };
+            let init = lexer::Lexer::new(self.input, target_end.min(self.end), self.end)
+                .peek_significant()
+                .filter(|eq| eq.is_punct(self.input, '='))
+                .map(|eq| self.trimmed(eq.end..self.expression_end(eq.end)));
+            let end = init.as_ref().map_or(target_end, |init| init.end);
+            list.push((target, target_end, init));
+            match lexer::Lexer::new(self.input, end.min(self.end), self.end).peek_significant() {
+                Some(comma) if comma.is_punct(self.input, ',') => from = comma.end,
+                _ => break,
             }
         }
+        let is_pattern = |target: &lexer::Token| target.kind == TokenKind::Punct;
+        if let [(target, target_end, None)] = list[..]
+            && is_pattern(&target)
+        {
+            return self.destructuring_loop_head(keyword, target.start, target_end);
+        }
+        if !list.iter().any(|(target, ..)| is_pattern(target))
+            || list.iter().any(|(target, target_end, init)| is_pattern(target) && init.is_none() || *target_end > self.end)
+        {
+            return false;
+        }
 
-        // Check for nullish coalescing
-        if ch == Some('?') && self.peek(1) == Some('?') {
-            self.transform_nullish_coalescing();
-            return;
+        self.output.generate("var", keyword.start);
+        let mut copied = keyword.end;
+        for (target, target_end, init) in list {
+            self.output.copy(&self.input[copied..target.start], copied);
+            let Some(init) = init else {
+                self.output.copy(&self.input[target.start..target_end], target.start);
+                copied = target_end;
+                continue;
+            };
+            copied = init.end;
+            if !is_pattern(&target) {
+                self.output.copy(&self.input[target.start..init.start], target.start);
+                let code = self.downlevel(init);
+                self.output.append(&code);
+                continue;
+            }
+            let pattern = self.pattern(target.start);
+            let code = self.downlevel(init);
+            let mut steps = Vec::new();
+            if is_simple(code.as_str()) && pattern.bindings.iter().any(Option::is_some) {
+                self.destructure(&pattern, code.as_str(), &mut steps);
+                self.output.generate(&declarators(&steps), target.start);
+            } else {
+                let temp = self.temp();
+                self.output.generate(&format!("{} = ", temp), target.start);
+                self.output.append(&code);
+                self.destructure(&pattern, &temp, &mut steps);
+                if !steps.is_empty() {
+                    self.output.generate(&format!(", {}", declarators(&steps)), target.start);
+                }
+            }
         }
+        self.pos = copied;
+        self.prev = None;
+        self.regex_allowed = false;
+        true
+    }
 
-        // Normal character
-        if let Some(c) = ch {
-            self.output.push(c);
+    /// `for (const [key, value] of entries) {` as `for (var _ref of entries) { var key =
+    /// _ref[0], value = _ref[1];`, the keyword at `keyword` and the pattern in
+    /// `pattern_start..pattern_end`. A body that is not a block is wrapped in one. `false`,
+    /// with nothing consumed, if it is not such a loop head.
+    fn destructuring_loop_head(&mut self, keyword: lexer::Token, pattern_start: usize, pattern_end: usize) -> bool {
+        let Some(operator) = lexer::Lexer::new(self.input, pattern_end, self.end).peek_significant().filter(|token| {
+            let word = token.text(self.input);
+            token.kind == TokenKind::Identifier && matches!(word, "of" | "in")
+        }) else {
+            return false;
+        };
+        let close = lexer::closing_bracket(self.input, operator.end, self.end);
+        let Some(body) = lexer::Lexer::new(self.input, (close + 1).min(self.end), self.end)
+            .peek_significant()
+            .filter(|_| close < self.end)
+        else {
+            return false;
+        };
+        let pattern = self.pattern(pattern_start);
+        let temp = self.temp();
+        self.output.generate(&format!("var {}", temp), keyword.start);
+        self.output.copy(&self.input[pattern_end..operator.end], pattern_end);
+        let iterated = self.downlevel(operator.end..close);
+        self.output.append(&iterated);
+        if body.is_punct(self.input, '{') {
+            self.output.copy(&self.input[close..body.end], close);
+            self.pattern_declarations(&pattern, &temp, body.start);
+            self.pos = body.end;
+        } else {
+            let end = self.statement_end(body.start);
+            self.output.copy(&self.input[close..body.start], close);
+            self.output.generate("{", body.start);
+            self.pattern_declarations(&pattern, &temp, body.start);
+            self.output.push(' ');
+            let code = self.nested(body.start..end, self.in_arrow, true);
+            self.output.append(&code);
+            self.output.push_str(" }");
+            self.pos = end;
         }
-        self.pos += 1;
+        self.prev = None;
+        self.regex_allowed = true;
+        true
     }
 
-    fn handle_string(&mut self, quote: char) {
-        let _start = self.pos;
-        self.output.push(quote);
-        self.pos += 1;
+    /// `catch ({ message }) {` as `catch (_ref) { var message = _ref.message;`, with the
+    /// parentheses at `open` and `close` and the block at `body`; `false`, with nothing
+    /// consumed, if the parameter is not a pattern
+    fn catch_parameter_pattern(&mut self, open: lexer::Token, close: usize, body: lexer::Token) -> bool {
+        let parameter = self.trimmed(open.end..close);
+        if parameter.is_empty() || !matches!(self.target(parameter.clone()), Target::Pattern(_)) {
+            return false;
+        }
+        let pattern = self.pattern(parameter.start);
+        let temp = self.temp();
+        self.output.copy(&self.input[open.start..parameter.start], open.start);
+        self.output.generate(&temp, parameter.start);
+        self.output.copy(&self.input[parameter.end..body.end], parameter.end);
+        self.pattern_declarations(&pattern, &temp, body.start);
+        self.pos = body.end;
+        self.prev = None;
+        self.regex_allowed = true;
+        true
+    }
 
-        // Special handling for template literals
-        let is_template = quote == '`';
+    /// ` var a = _ref.a, …;` declaring the bindings of `pattern` from `source`, for the start
+    /// of the block at `at`
+    fn pattern_declarations(&mut self, pattern: &Pattern, source: &str, at: usize) {
+        let mut steps = Vec::new();
+        self.destructure(pattern, source, &mut steps);
+        if !steps.is_empty() {
+            self.output.generate(&format!(" var {};", declarators(&steps)), at);
+        }
+    }
 
-        while self.pos < self.input.len() {
-            let ch = self.current_char();
+    /// End of the statement starting at `start`, with its `;`: a block, a statement with a
+    /// parenthesized head (and its `else`), or expressions separated by commas
+    fn statement_end(&self, start: usize) -> usize {
+        let significant = |from: usize| lexer::Lexer::new(self.input, from.min(self.end), self.end).peek_significant();
+        let Some(first) = significant(start) else { return start };
+        let word = first.text(self.input);
+        if first.is_punct(self.input, '{') {
+            return (lexer::closing_bracket(self.input, first.end, self.end) + 1).min(self.end);
+        }
+        if first.kind == TokenKind::Identifier && matches!(word, "if" | "for" | "while" | "with") {
+            let Some(open) = significant(first.end).filter(|open| open.is_punct(self.input, '(')) else {
+                return self.end;
+            };
+            let close = lexer::closing_bracket(self.input, open.end, self.end);
+            let end = self.statement_end(close + 1);
+            return match significant(end) {
+                Some(other) if word == "if" && other.kind == TokenKind::Identifier && other.text(self.input) == "else" => {
+                    self.statement_end(other.end)
+                }
+                _ => end,
+            };
+        }
+        let mut end = self.expression_end(first.start);
+        loop {
+            match significant(end) {
+                Some(comma) if comma.is_punct(self.input, ',') => end = self.expression_end(comma.end),
+                Some(semicolon) if semicolon.is_punct(self.input, ';') => return semicolon.end,
+                _ => return end,
+            }
+        }
+    }
 
-            if ch == Some('\\') && self.peek(1).is_some() {
-                // Escape sequence
-                self.output.push(ch.unwrap());
-                self.pos += 1;
-                if let Some(next) = self.current_char() {
-                    self.output.push(next);
-                    self.pos += 1;
+    /// Whether the `{` or `[` token `open` starts a pattern being assigned to: it is in
+    /// expression position and its group is followed by `=`
+    fn is_destructuring_assignment(&self, open: &lexer::Token) -> bool {
+        if !open.expression_position || !matches!(self.char_at(open.start), '{' | '[') {
+            return false;
+        }
+        let close = lexer::closing_bracket(self.input, open.end, self.end);
+        close < self.end
+            && lexer::Lexer::new(self.input, close + 1, self.end).peek_significant().is_some_and(|eq| {
+                eq.is_punct(self.input, '=') && !self.input[eq.end..].starts_with(['=', '>'])
+            })
+    }
+
+    /// `[a, b] = value` as `(_ref = value, a = _ref[0], b = _ref[1], _ref)`, the temporaries
+    /// declared at the start of the enclosing function
+    fn destructuring_assignment(&mut self, open: lexer::Token) {
+        let close = lexer::closing_bracket(self.input, open.end, self.end);
+        let eq = lexer::Lexer::new(self.input, close + 1, self.end).peek_significant().expect("`=` follows the pattern");
+        let pattern = self.pattern(open.start);
+        let value = self.trimmed(eq.end..self.expression_end(eq.end));
+        let temp = self.temp();
+        self.hoisted.temps.push(temp.clone());
+        self.output.generate(&format!("({} = ", temp), open.start);
+        let code = self.downlevel(value.clone());
+        self.output.append(&code);
+        let mut steps = Vec::new();
+        self.destructure(&pattern, &temp, &mut steps);
+        for step in &steps {
+            if step.temp {
+                self.hoisted.temps.push(step.target.clone());
+            }
+            self.output.push_str(&format!(", {} = {}", step.target, step.value));
+        }
+        self.output.push_str(&format!(", {})", temp));
+        self.pos = value.end;
+        self.prev = None;
+        self.regex_allowed = false;
+    }
+
+    /// The steps assigning `target` from the expression `source`, or from `default` where
+    /// `source` is `undefined`
+    fn bind(&mut self, target: &Target, default: Option<Range<usize>>, source: String, steps: &mut Vec<Step>) {
+        let source = match default {
+            Some(default) => {
+                let default = self.downleveled(default);
+                let value = self.simple_source(source, steps);
+                format!("{0} === void 0 ? {1} : {0}", value, default)
+            }
+            None => source,
+        };
+        match target {
+            Target::Expression(range) => {
+                let target = self.downleveled(range.clone());
+                steps.push(Step { target, value: source, temp: false });
+            }
+            Target::Pattern(pattern) => {
+                let source = self.simple_source(source, steps);
+                self.destructure(pattern, &source, steps);
+            }
+        }
+    }
+
+    /// `source` if it is a name, or a temporary it is stored in first, so it can be read
+    /// more than once
+    fn simple_source(&mut self, source: String, steps: &mut Vec<Step>) -> String {
+        if is_simple(&source) {
+            return source;
+        }
+        let temp = self.temp();
+        steps.push(Step { target: temp.clone(), value: source, temp: true });
+        temp
+    }
+
+    /// The steps assigning each binding of `pattern` from the name `source`
+    fn destructure(&mut self, pattern: &Pattern, source: &str, steps: &mut Vec<Step>) {
+        let has_rest = pattern.bindings.iter().flatten().any(|binding| binding.rest);
+        // Keys an object rest element leaves out
+        let mut excluded = Vec::new();
+        for (i, binding) in pattern.bindings.iter().enumerate() {
+            let Some(binding) = binding else { continue };
+            if binding.rest && pattern.object {
+                self.shared.object_rest = true;
+                let value = format!("__hook_object_rest({}, [{}])", source, excluded.join(", "));
+                self.bind(&binding.target, None, value, steps);
+                continue;
+            }
+            if binding.rest {
+                self.bind(&binding.target, None, format!("{}.slice({})", source, i), steps);
+                continue;
+            }
+            let value = match &binding.key {
+                None => format!("{}[{}]", source, i),
+                Some(PropertyKey::Name(name)) => {
+                    excluded.push(string_literal(name));
+                    format!("{}.{}", source, name)
+                }
+                Some(PropertyKey::Literal(literal)) => {
+                    excluded.push(if literal.starts_with(['"', '\'']) { literal.clone() } else { string_literal(literal) });
+                    format!("{}[{}]", source, literal)
+                }
+                Some(PropertyKey::Computed(code)) => {
+                    let mut key = self.downleveled(code.clone());
+                    if has_rest {
+                        key = self.simple_source(key, steps);
+                        excluded.push(format!("{} + \"\"", key));
+                    }
+                    format!("{}[{}]", source, key)
                 }
+            };
+            self.bind(&binding.target, binding.default.clone(), value, steps);
+        }
+    }
+
+    /// The pattern whose opening `{` or `[` is at byte offset `open`
+    fn pattern(&self, open: usize) -> Pattern {
+        let close = lexer::closing_bracket(self.input, open + 1, self.end);
+        let object = self.char_at(open) == '{';
+        Pattern { object, bindings: self.bindings(open + 1..close, object) }
+    }
+
+    /// The comma-separated bindings of a pattern (with property keys if `object`) or a
+    /// parameter list in `range`
+    fn bindings(&self, range: Range<usize>, object: bool) -> Vec<Option<Binding>> {
+        self.elements(range)
+            .into_iter()
+            .map(|element| element.map(|element| self.binding(element, object)))
+            .collect()
+    }
+
+    /// The comma-separated elements in `range` without surrounding trivia, `None` for an
+    /// empty one (a hole)
+    fn elements(&self, range: Range<usize>) -> Vec<Option<Range<usize>>> {
+        let mut elements = Vec::new();
+        let (mut depth, mut first, mut last) = (0usize, None::<usize>, range.start);
+        for token in lexer::Lexer::new(self.input, range.start, range.end) {
+            if token.is_trivia() {
                 continue;
             }
+            if token.kind == TokenKind::Punct {
+                match self.char_at(token.start) {
+                    '(' | '[' | '{' => depth += 1,
+                    ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                    ',' if depth == 0 => {
+                        elements.push(first.map(|first| first..last));
+                        first = None;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            first.get_or_insert(token.start);
+            last = token.end;
+        }
+        // A trailing comma ends the list without a hole after it
+        if first.is_some() {
+            elements.push(first.map(|first| first..last));
+        }
+        elements
+    }
 
-            if ch == Some(quote) {
-                self.output.push(quote);
-                self.pos += 1;
-                break;
+    /// The range of the value an element spreads (`...value`), if it is a spread element
+    fn spread_value(&self, element: &Range<usize>) -> Option<Range<usize>> {
+        let mut tokens = lexer::Lexer::new(self.input, element.start, element.end).filter(|token| !token.is_trivia());
+        let dots = tokens.by_ref().take(3).filter(|token| token.is_punct(self.input, '.')).count();
+        let value = tokens.next().filter(|_| dots == 3)?;
+        Some(value.start..element.end)
+    }
+
+    /// One element of a pattern or parameter list, in `range` without surrounding trivia
+    fn binding(&self, range: Range<usize>, object: bool) -> Binding {
+        let tokens: Vec<lexer::Token> =
+            lexer::Lexer::new(self.input, range.start, range.end).filter(|token| !token.is_trivia()).collect();
+        let rest = tokens.len() > 3 && tokens[..3].iter().all(|token| token.is_punct(self.input, '.'));
+        let tokens = if rest { &tokens[3..] } else { &tokens[..] };
+        let start = tokens[0].start;
+        let (mut depth, mut colon, mut equals) = (0usize, None, None);
+        for token in tokens.iter().filter(|token| token.kind == TokenKind::Punct) {
+            match self.char_at(token.start) {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                ':' if depth == 0 && colon.is_none() && equals.is_none() => colon = Some(*token),
+                '=' if depth == 0 && equals.is_none() => equals = Some(*token),
+                _ => {}
             }
+        }
+        let target_end = equals.map_or(range.end, |equals| equals.start);
+        let default = equals.map(|equals| self.trimmed(equals.end..range.end));
+        let (key, target_start) = match colon {
+            _ if !object || rest => (None, start),
+            Some(colon) => (Some(self.property_key(start..colon.start)), colon.end),
+            None => (Some(self.property_key(start..target_end)), start),
+        };
+        Binding { key, target: self.target(target_start..target_end), default, rest }
+    }
 
-            // Template literal interpolation - transform code inside ${}
-            if is_template && ch == Some('$') && self.peek(1) == Some('{') {
-                self.output.push('$');
-                self.output.push('{');
-                self.pos += 2;
-
-                // Parse expression inside ${ } and apply transformations
-                let mut brace_depth = 1;
-                while brace_depth > 0 && self.pos < self.input.len() {
-                    let inner_state = self.current_state();
-                    
-                    match inner_state {
-                        ParserState::Normal => {
-                            let inner_ch = self.current_char();
-                            
-                            if inner_ch == Some('{') {
-                                brace_depth += 1;
-                                self.output.push(inner_ch.unwrap());
-                                self.pos += 1;
-                            } else if inner_ch == Some('}') {
-                                brace_depth -= 1;
-                                if brace_depth > 0 {
-                                    self.output.push(inner_ch.unwrap());
-                                }
-                                self.pos += 1;
-                            } else if inner_ch == Some('?') && self.peek(1) == Some('.') && brace_depth > 0 {
-                                // Transform optional chaining inside template expression
-                                if self.is_optional_chaining_context() {
-                                    self.transform_optional_chaining();
-                                }
-                            } else if inner_ch == Some('?') && self.peek(1) == Some('?') && brace_depth > 0 {
-                                // Transform nullish coalescing inside template expression
-                                self.transform_nullish_coalescing();
-                            } else {
-                                if let Some(c) = inner_ch {
-                                    self.output.push(c);
-                                }
-                                self.pos += 1;
-                            }
-                        }
-                        ParserState::InString(quote_char) => {
-                            // Handle nested strings in template expression
-                            self.handle_string(quote_char);
-                        }
-                        ParserState::InLineComment => {
-                            // Handle comments in template expression
-                            self.handle_line_comment();
+    fn property_key(&self, range: Range<usize>) -> PropertyKey {
+        let range = self.trimmed(range);
+        let text = self.input[range.clone()].to_string();
+        match lexer::token_at(self.input, range.start, range.end, false).kind {
+            TokenKind::String | TokenKind::Number => PropertyKey::Literal(text),
+            TokenKind::Punct if text.starts_with('[') => PropertyKey::Computed(range.start + 1..range.end - 1),
+            _ => PropertyKey::Name(text),
+        }
+    }
+
+    /// A nested pattern, or the expression assigned to, in `range`
+    fn target(&self, range: Range<usize>) -> Target {
+        let range = self.trimmed(range);
+        let nested = range.len() > 1
+            && matches!(self.char_at(range.start), '{' | '[')
+            && lexer::closing_bracket(self.input, range.start + 1, range.end) + 1 == range.end;
+        if nested { Target::Pattern(self.pattern(range.start)) } else { Target::Expression(range) }
+    }
+
+    /// `range` without the trivia around its tokens
+    fn trimmed(&self, range: Range<usize>) -> Range<usize> {
+        let mut tokens = lexer::Lexer::new(self.input, range.start, range.end).filter(|token| !token.is_trivia());
+        let Some(first) = tokens.next() else { return range.start..range.start };
+        first.start..tokens.last().map_or(first.end, |last| last.end)
+    }
+
+    /// Byte offset just past the `{ … }` specifier list of the `import` or `export` keyword
+    /// `keyword`: `import D, { a }`, `import { a }` or `export { a }`
+    fn specifier_list_end(&self, keyword: lexer::Token) -> Option<usize> {
+        let word = keyword.text(self.input);
+        if !matches!(word, "import" | "export") {
+            return None;
+        }
+        let tokens = lexer::Lexer::new(self.input, keyword.end, self.end).filter(|token| !token.is_trivia());
+        for token in tokens {
+            if token.is_punct(self.input, '{') {
+                return Some((lexer::closing_bracket(self.input, token.end, self.end) + 1).min(self.end));
+            }
+            // Only a default import may come first
+            let default_import =
+                word == "import" && (token.kind == TokenKind::Identifier || token.is_punct(self.input, ','));
+            if !default_import {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Whether the `{` token `open`, after the significant token `prev`, starts an object
+    /// literal rather than a block
+    fn starts_object_literal(&self, open: &lexer::Token, prev: Option<lexer::Token>) -> bool {
+        if !open.expression_position {
+            return false;
+        }
+        let Some(prev) = prev else { return !self.statements };
+        match prev.kind {
+            TokenKind::Punct if self.char_at(prev.start) == ':' => self.conditional_colon,
+            TokenKind::Punct => !matches!(self.char_at(prev.start), ';' | '{' | '}'),
+            TokenKind::Identifier => {
+                let word = prev.text(self.input);
+                !matches!(word, "else" | "do")
+            }
+            _ => true,
+        }
+    }
+
+    /// An object literal opening at `open`. Shorthand properties and methods are spelled
+    /// out in place; with spreads or computed keys it is built by `__hook_assign` from
+    /// object literals and the spread values, in order. Anything else, or a block after
+    /// all (`case x: { … }`), is walked as usual.
+    fn object_literal(&mut self, open: lexer::Token) {
+        let close = lexer::closing_bracket(self.input, open.end, self.end);
+        let members = (close < self.end).then(|| self.members(open.end..close)).flatten();
+        let Some(members) = members else {
+            self.output.copy("{", open.start);
+            self.pos = open.end;
+            return;
+        };
+
+        let assigned = members.iter().any(|(_, member)| matches!(member, Member::Spread(_) | Member::Computed { .. }));
+        if !assigned {
+            self.output.copy("{", open.start);
+            let mut copied = open.end;
+            for (range, member) in &members {
+                self.output.copy(&self.input[copied..range.start], copied);
+                self.object_member(range, member);
+                copied = range.end;
+            }
+            self.output.copy(&self.input[copied..close + 1], copied);
+        } else {
+            self.shared.object_assign = true;
+            self.output.generate("__hook_assign(", open.start);
+            // Whether an object literal of plain members is open, and whether any argument is written
+            let (mut in_object, mut written) = (false, false);
+            if matches!(members.first(), Some((_, Member::Spread(_) | Member::Computed { .. }))) {
+                self.output.push_str("{}");
+                written = true;
+            }
+            for (range, member) in &members {
+                match member {
+                    Member::Spread(_) | Member::Computed { .. } => {
+                        if in_object {
+                            self.output.push_str(" }");
+                            in_object = false;
                         }
-                        ParserState::InBlockComment => {
-                            // Handle block comments in template expression
-                            self.handle_block_comment();
+                        if written {
+                            self.output.push_str(", ");
                         }
                     }
+                    _ if in_object => self.output.push_str(", "),
+                    _ => {
+                        self.output.push_str(if written { ", { " } else { "{ " });
+                        in_object = true;
+                    }
                 }
-                
-                if brace_depth == 0 {
-                    self.output.push('}');
+                written = true;
+                self.object_member(range, member);
+            }
+            if in_object {
+                self.output.push_str(" }");
+            }
+            self.output.push(')');
+        }
+        self.pos = close + 1;
+        self.prev = Some(lexer::token_at(self.input, close, self.end, false));
+        self.regex_allowed = false;
+    }
+
+    /// The members of the object literal in `range`, or `None` if it cannot be one
+    fn members(&self, range: Range<usize>) -> Option<Vec<(Range<usize>, Member)>> {
+        let mut depth = 0usize;
+        for token in lexer::Lexer::new(self.input, range.start, range.end) {
+            if token.kind == TokenKind::Punct {
+                match self.char_at(token.start) {
+                    '(' | '[' | '{' => depth += 1,
+                    ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                    ';' if depth == 0 => return None,
+                    _ => {}
                 }
-                continue;
             }
+        }
+        self.elements(range).into_iter().map(|element| element.and_then(|element| self.member(element))).collect()
+    }
 
-            if let Some(c) = ch {
-                self.output.push(c);
+    /// The object literal member in `range`, or `None` if it cannot be one
+    fn member(&self, range: Range<usize>) -> Option<(Range<usize>, Member)> {
+        if let Some(value) = self.spread_value(&range) {
+            return Some((range, Member::Spread(value)));
+        }
+        let tokens: Vec<lexer::Token> =
+            lexer::Lexer::new(self.input, range.start, range.end).filter(|token| !token.is_trivia()).collect();
+        let first = tokens[0];
+        let (key_end, rest) = if first.is_punct(self.input, '[') {
+            let close = lexer::closing_bracket(self.input, first.end, range.end);
+            (close + 1, tokens.iter().position(|token| token.start > close).map_or(&[][..], |i| &tokens[i..]))
+        } else {
+            (first.end, &tokens[1..])
+        };
+        let member = match rest.first() {
+            None if first.kind == TokenKind::Identifier => Member::Shorthand,
+            Some(colon) if colon.is_punct(self.input, ':') => Member::Property(self.trimmed(colon.end..range.end)),
+            Some(open) if open.is_punct(self.input, '(') => {
+                // `if (a) { … }` in a block after a label
+                let word = first.text(self.input);
+                if BLOCK_KEYWORDS.contains(&word) || word == "function" {
+                    return None;
+                }
+                let close = lexer::closing_bracket(self.input, open.end, range.end);
+                let body = rest.iter().find(|token| token.start > close).filter(|body| {
+                    body.is_punct(self.input, '{') && lexer::closing_bracket(self.input, body.end, range.end) + 1 == range.end
+                })?;
+                Member::Method { key_end, open: *open, close, body: *body }
             }
-            self.pos += 1;
+            // `get name() { … }`, `async name() { … }`, `*name() { … }`
+            Some(_) if first.kind == TokenKind::Identifier || first.is_punct(self.input, '*') => {
+                let word = first.text(self.input);
+                if !matches!(word, "get" | "set" | "async" | "*") {
+                    return None;
+                }
+                Member::Other
+            }
+            _ => return None,
+        };
+        if first.is_punct(self.input, '[') && matches!(member, Member::Property(_) | Member::Method { .. }) {
+            let key = self.trimmed(first.end..key_end - 1);
+            return Some((range, Member::Computed { key, value: Box::new(member) }));
+        }
+        if !matches!(first.kind, TokenKind::Identifier | TokenKind::String | TokenKind::Number)
+            && !matches!(member, Member::Other)
+        {
+            return None;
         }
+        Some((range, member))
     }
 
-    fn handle_line_comment(&mut self) {
-        // Copy comment as-is until newline
-        while self.pos < self.input.len() {
-            let ch = self.current_char();
-            if let Some(c) = ch {
-                self.output.push(c);
-                if c == '\n' {
-                    self.pos += 1;
-                    break;
+    /// One member of an object literal in ES5: a property of an object literal, or an
+    /// argument of `__hook_assign` for a spread or computed key
+    fn object_member(&mut self, range: &Range<usize>, member: &Member) {
+        match member {
+            Member::Property(value) => {
+                self.output.copy(&self.input[range.start..value.start], range.start);
+                let code = self.downlevel(value.clone());
+                self.output.append(&code);
+            }
+            Member::Shorthand => {
+                self.output.copy(&self.input[range.clone()], range.start);
+                self.output.generate(": ", range.start);
+                let code = self.downlevel(range.clone());
+                self.output.append(&code);
+            }
+            Member::Method { key_end, open, close, body } => {
+                self.output.copy(&self.input[range.start..*key_end], range.start);
+                self.output.generate(": function ", open.start);
+                self.function(*open, *close, *body);
+            }
+            Member::Computed { key, value } => {
+                self.shared.property = true;
+                self.output.generate("__hook_property(", range.start);
+                let code = self.downlevel(key.clone());
+                self.output.append(&code);
+                self.output.push_str(", ");
+                match value.as_ref() {
+                    Member::Property(value) => {
+                        let code = self.downlevel(value.clone());
+                        self.output.append(&code);
+                    }
+                    Member::Method { open, close, body, .. } => {
+                        self.output.generate("function ", open.start);
+                        self.function(*open, *close, *body);
+                    }
+                    _ => unreachable!("computed keys name properties and methods"),
                 }
+                self.output.push(')');
+            }
+            Member::Spread(value) => {
+                let code = self.downlevel(value.clone());
+                self.output.append(&code);
+            }
+            Member::Other => {
+                let code = self.downlevel(range.clone());
+                self.output.append(&code);
             }
-            self.pos += 1;
         }
     }
 
-    fn handle_block_comment(&mut self) {
-        self.output.push('/');
-        self.output.push('*');
-        self.pos += 2;
+    /// An array literal opening at `open`, built with `concat` if it has spread elements
+    /// and walked as usual otherwise
+    fn array_literal(&mut self, open: lexer::Token) {
+        let close = lexer::closing_bracket(self.input, open.end, self.end);
+        let elements = self.elements(open.end..close);
+        if close >= self.end || !elements.iter().flatten().any(|element| self.spread_value(element).is_some()) {
+            self.output.copy("[", open.start);
+            self.pos = open.end;
+            return;
+        }
+        self.spread_array(&elements, open.start);
+        self.pos = close + 1;
+        self.prev = Some(lexer::token_at(self.input, close, self.end, false));
+        self.regex_allowed = false;
+    }
 
-        // Copy until */ is found
-        while self.pos + 1 < self.input.len() {
-            let ch = self.current_char();
-            let next = self.peek(1);
+    /// A call whose arguments in `open`…`close` spread a value, as `callee.apply(…)`, or
+    /// `new callee(…)` binding the arguments first; `false`, with nothing written, if they
+    /// do not spread or the callee cannot be told apart
+    fn spread_call(&mut self, open: lexer::Token, close: usize) -> bool {
+        let elements = self.elements(open.end..close);
+        if !elements.iter().flatten().any(|element| self.spread_value(element).is_some()) {
+            return false;
+        }
+        let Some(start) = callee_start(self.output.as_str()) else { return false };
+        let before = self.output.as_str()[..start].trim_end();
+        if before.ends_with('.') {
+            return false;
+        }
+        let new = before.strip_suffix("new").filter(|rest| !rest.ends_with(lexer::is_identifier_char)).map(str::len);
+        let callee = self.output.as_str()[start..].trim_end().to_string();
+        let start = new.unwrap_or(start);
+        let at = self.output.original_pos_of(start);
+        self.output.truncate(start);
+        match member_object(&callee) {
+            _ if new.is_some() => {
+                self.output.generate(&format!("new (Function.prototype.bind.apply({}, [null].concat(", callee), at);
+            }
+            None => self.output.generate(&format!("{}.apply(void 0, ", callee), at),
+            Some(object) if is_simple(object) || object == "this" => {
+                self.output.generate(&format!("{}.apply({}, ", callee, object), at);
+            }
+            Some(object) => {
+                let temp = self.temp();
+                self.hoisted.temps.push(temp.clone());
+                let member = &callee[object.len()..];
+                self.output.generate(&format!("({} = {}){}.apply({}, ", temp, object, member, temp), at);
+            }
+        }
+        match &elements[..] {
+            [Some(only)] => {
+                let value = self.spread_value(only).expect("the only argument is spread");
+                let code = self.downlevel(value.clone());
+                if new.is_some() {
+                    self.shared.spread = true;
+                    self.output.generate("__hook_spread(", value.start);
+                    self.output.append(&code);
+                    self.output.push(')');
+                } else {
+                    self.output.append(&code);
+                }
+            }
+            _ => self.spread_array(&elements, open.start),
+        }
+        if new.is_some() {
+            self.output.generate(")))(", close);
+        }
+        self.output.copy(")", close);
+        self.pos = close + 1;
+        self.prev = Some(lexer::token_at(self.input, close, self.end, false));
+        self.regex_allowed = false;
+        true
+    }
 
-            if let Some(c) = ch {
-                self.output.push(c);
+    /// The array of `elements` with their spread values' elements in place, as array literals
+    /// joined by `concat`
+    fn spread_array(&mut self, elements: &[Option<Range<usize>>], at: usize) {
+        self.shared.spread = true;
+        // Whether an array literal of plain elements is open, and whether `concat` is
+        let (mut in_array, mut concat) = (false, false);
+        if elements.first().is_some_and(|element| element.as_ref().is_some_and(|e| self.spread_value(e).is_some())) {
+            self.output.generate("[].concat(", at);
+            concat = true;
+        }
+        let mut arguments = 0;
+        for (i, element) in elements.iter().enumerate() {
+            let spread = element.as_ref().and_then(|element| self.spread_value(element));
+            if let Some(value) = spread {
+                if in_array {
+                    self.output.push(']');
+                    in_array = false;
+                }
+                if !concat {
+                    self.output.push_str(".concat(");
+                    concat = true;
+                }
+                if arguments > 0 {
+                    self.output.push_str(", ");
+                }
+                arguments += 1;
+                self.output.generate("__hook_spread(", value.start);
+                let code = self.downlevel(value);
+                self.output.append(&code);
+                self.output.push(')');
+                continue;
+            }
+            if in_array {
+                self.output.push_str(", ");
+            } else {
+                if concat {
+                    if arguments > 0 {
+                        self.output.push_str(", ");
+                    }
+                    arguments += 1;
+                }
+                self.output.generate("[", at);
+                in_array = true;
+            }
+            if let Some(element) = element {
+                let code = self.downlevel(element.clone());
+                self.output.append(&code);
+            } else if i + 1 == elements.len() {
+                // A hole at the end needs its own comma
+                self.output.push(',');
             }
+        }
+        if in_array {
+            self.output.push(']');
+        }
+        if concat {
+            self.output.push(')');
+        }
+    }
 
-            if ch == Some('*') && next == Some('/') {
-                self.output.push('/');
-                self.pos += 2;
-                break;
+    /// A template literal token, after the significant token `prev`. An untagged literal
+    /// becomes its strings and `String(…)` of each interpolation joined by `+`. A literal
+    /// that is cut off or has an invalid escape is kept, its interpolations downleveled.
+    fn template_literal(&mut self, token: lexer::Token, prev: Option<lexer::Token>) {
+        self.pos = token.end;
+        let interpolations = lexer::template_interpolations(self.input, &token);
+        let closed = token.end - token.start >= 2
+            && self.char_at(token.end - 1) == '`'
+            && interpolations.last().is_none_or(|code| code.end < token.end - 1);
+        let quasis: Vec<String> = if closed {
+            let mut start = token.start + 1;
+            let mut quasis = Vec::new();
+            for code in &interpolations {
+                quasis.push(self.input[start..code.start - 2].to_string());
+                start = code.end + 1;
+            }
+            quasis.push(self.input[start..token.end - 1].to_string());
+            quasis
+        } else {
+            Vec::new()
+        };
+
+        if closed && prev.is_some_and(|prev| self.is_tag(&prev)) {
+            self.tagged_template(token, &quasis, interpolations);
+            return;
+        }
+        let cooked: Option<Vec<String>> = quasis.iter().map(|quasi| cooked_string(quasi)).collect();
+        let Some(cooked) = cooked.filter(|_| closed) else {
+            let mut copied = token.start;
+            for code in interpolations {
+                self.output.copy(&self.input[copied..code.start], copied);
+                copied = code.end;
+                let code = self.downlevel(code);
+                self.output.append(&code);
             }
+            self.output.copy(&self.input[copied..token.end], copied);
+            return;
+        };
 
-            self.pos += 1;
+        let mut parts = Vec::new();
+        let mut interpolations = interpolations.into_iter();
+        for value in cooked {
+            if !value.is_empty() {
+                parts.push(MappedText::generated(&string_literal(&value), token.start));
+            }
+            if let Some(code) = interpolations.next() {
+                let mut part = MappedText::generated("String(", code.start);
+                part.append(&self.interpolation(code));
+                part.push(')');
+                parts.push(part);
+            }
+        }
+        if parts.is_empty() {
+            self.output.generate("\"\"", token.start);
+            return;
+        }
+        let parens = parts.len() > 1 && self.concatenation_needs_parens(prev, token.end);
+        if parens {
+            self.output.generate("(", token.start);
+        }
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(" + ");
+            }
+            self.output.append(part);
+        }
+        if parens {
+            self.output.push(')');
         }
     }
 
-    fn is_optional_chaining_context(&self) -> bool {
-        // Look back to see if we have a valid object reference
-        // Valid: identifiers, ), ], or whitespace before ?
-        let mut check_pos = self.pos as i32 - 1;
+    /// `` tag`a${x}b` `` as `tag(__hook_template_N, x)`, the tag already in the output. The
+    /// strings array is created once, at the top, so each evaluation of the call site gets
+    /// the same array as it would natively; an invalid escape makes its string `undefined`.
+    fn tagged_template(&mut self, token: lexer::Token, quasis: &[String], interpolations: Vec<Range<usize>>) {
+        let cooked: Vec<String> = quasis
+            .iter()
+            .map(|quasi| cooked_string(quasi).map_or_else(|| "void 0".to_string(), |value| string_literal(&value)))
+            .collect();
+        let raw: Vec<String> =
+            quasis.iter().map(|quasi| string_literal(&quasi.replace("\r\n", "\n").replace('\r', "\n"))).collect();
+        self.shared.templates.push(format!("__hook_template_object([{}], [{}])", cooked.join(", "), raw.join(", ")));
+        self.output.generate(&format!("(__hook_template_{}", self.shared.templates.len()), token.start);
+        for code in interpolations {
+            self.output.push_str(", ");
+            let code = self.interpolation(code);
+            self.output.append(&code);
+        }
+        self.output.push(')');
+    }
 
-        while check_pos >= 0 && self.input[check_pos as usize].is_whitespace() {
-            check_pos -= 1;
+    /// The downleveled code of a template interpolation as a single expression, in
+    /// parentheses if it is a comma expression
+    fn interpolation(&mut self, code: Range<usize>) -> MappedText {
+        let mut depth = 0usize;
+        let comma = lexer::Lexer::new(self.input, code.start, code.end).any(|token| {
+            if token.kind != TokenKind::Punct {
+                return false;
+            }
+            match self.char_at(token.start) {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                ',' => return depth == 0,
+                _ => {}
+            }
+            false
+        });
+        let downleveled = self.downlevel(code.clone());
+        if !comma {
+            return downleveled;
         }
+        let mut wrapped = MappedText::generated("(", code.start);
+        wrapped.append(&downleveled);
+        wrapped.push(')');
+        wrapped
+    }
 
-        if check_pos < 0 {
-            return false;
+    /// Whether a template literal after `prev` is tagged by it: it follows a name, a call,
+    /// a member access or another template
+    fn is_tag(&self, prev: &lexer::Token) -> bool {
+        match prev.kind {
+            TokenKind::Identifier => !prev.regex_allowed_after(self.input, true),
+            TokenKind::Punct => matches!(self.char_at(prev.start), ')' | ']'),
+            TokenKind::Template => true,
+            _ => false,
         }
+    }
 
-        let ch = self.input[check_pos as usize];
-        ch.is_alphanumeric() || ch == '_' || ch == ')' || ch == ']'
+    /// Whether the `+` concatenation replacing a template literal, after `prev` and ending
+    /// at byte offset `end`, binds looser than the operators around it
+    fn concatenation_needs_parens(&self, prev: Option<lexer::Token>, end: usize) -> bool {
+        let before = prev.is_some_and(|prev| match prev.kind {
+            TokenKind::Punct => matches!(self.char_at(prev.start), '+' | '-' | '*' | '/' | '%' | '!' | '~'),
+            TokenKind::Identifier => {
+                let word = prev.text(self.input);
+                matches!(word, "typeof" | "void" | "delete" | "await" | "new")
+            }
+            _ => false,
+        });
+        let next = lexer::Lexer::new(self.input, end, self.end).peek_significant();
+        let after = next.is_some_and(|next| match next.kind {
+            TokenKind::Punct => match self.char_at(next.start) {
+                '.' | '[' | '(' | '*' | '/' | '%' => true,
+                // `?.`, but not `?.5` or `??`
+                '?' => {
+                    self.input[next.start + 1..].starts_with('.')
+                        && !self.input.as_bytes().get(next.start + 2).is_some_and(u8::is_ascii_digit)
+                }
+                _ => false,
+            },
+            TokenKind::Template => true,
+            _ => false,
+        });
+        before || after
+    }
+
+    /// `?.`, `??` or the `?` of a conditional, with `pos` at the `?`
+    fn handle_question_mark(&mut self) {
+        // Check for optional chaining; `a?.5:1` is a conditional
+        if self.peek(1) == Some('.')
+            && !self.peek(2).is_some_and(|c| c.is_ascii_digit())
+            && self.is_optional_chaining_context()
+        {
+            self.transform_optional_chaining();
+            self.regex_allowed = false;
+            return;
+        }
+
+        // Check for nullish coalescing
+        if self.peek(1) == Some('?') {
+            self.transform_nullish_coalescing();
+            self.regex_allowed = false;
+            return;
+        }
+
+        self.output.copy("?", self.pos);
+        self.pos += 1;
+        self.conditionals += 1;
+    }
+
+    fn is_optional_chaining_context(&self) -> bool {
+        // Look back to see if we have a valid object reference
+        // Valid: identifiers, ), ], or whitespace before ?
+        self.input[..self.pos]
+            .trim_end()
+            .chars()
+            .next_back()
+            .is_some_and(|ch| ch.is_alphanumeric() || ch == '_' || ch == ')' || ch == ']')
     }
 
     fn transform_optional_chaining(&mut self) {
         // Find the object reference before ?.
         let obj_start = self.find_object_start();
-        let raw_segment = self.output[obj_start..].to_string();
+        let at = self.output.original_pos_of(obj_start);
+        let raw_segment = self.output.as_str()[obj_start..].to_string();
         let mut obj = raw_segment.trim_end().to_string();
         let leading_ws: String = obj.chars().take_while(|c| c.is_whitespace()).collect();
         obj = obj.trim_start().to_string();
@@ -309,7 +1644,7 @@ impl Transpiler {
             }
         }
 
-        self.output.push_str(&leading_ws);
+        self.output.generate(&leading_ws, at);
         for kw in keyword_prefixes.into_iter() {
             self.output.push_str(kw);
             self.output.push(' ');
@@ -331,17 +1666,7 @@ impl Transpiler {
                 self.pos += 1;
 
                 // Copy bracket expression
-                let mut depth = 1;
-                while depth > 0 && self.pos < self.input.len() {
-                    let ch = self.current_char().unwrap_or(' ');
-                    if ch == '[' {
-                        depth += 1;
-                    } else if ch == ']' {
-                        depth -= 1;
-                    }
-                    self.output.push(ch);
-                    self.pos += 1;
-                }
+                self.copy_group();
                 self.output.push_str(" : undefined)");
             }
             Some('(') => {
@@ -354,17 +1679,7 @@ impl Transpiler {
                 self.pos += 1;
 
                 // Copy paren expression
-                let mut depth = 1;
-                while depth > 0 && self.pos < self.input.len() {
-                    let ch = self.current_char().unwrap_or(' ');
-                    if ch == '(' {
-                        depth += 1;
-                    } else if ch == ')' {
-                        depth -= 1;
-                    }
-                    self.output.push(ch);
-                    self.pos += 1;
-                }
+                self.copy_group();
                 self.output.push_str(" : undefined)");
             }
             _ => {
@@ -375,7 +1690,7 @@ impl Transpiler {
                 self.output.push_str(&obj);
                 self.output.push('.');
 
-                while self.pos < self.input.len() {
+                while self.pos < self.end {
                     let ch = self.current_char();
                     if let Some(c) = ch {
                         if c.is_alphanumeric() || c == '_' {
@@ -398,35 +1713,64 @@ impl Transpiler {
         // a ?? b → (a != null ? a : b)
         // Find the left operand
         let left_end = self.find_operator_left_operand();
-        let left = self.output[left_end..].trim().to_string();
+        let raw_left = &self.output.as_str()[left_end..];
+        let leading_ws = raw_left.len() - raw_left.trim_start().len();
+        let at = self.output.original_pos_of(left_end + leading_ws);
+        let left = raw_left.trim().to_string();
         self.output.truncate(left_end);
 
         self.pos += 2; // Skip ??
 
-        self.output.push_str("(");
+        self.output.generate("(", at);
         self.output.push_str(&left);
         self.output.push_str(" != null ? ");
         self.output.push_str(&left);
         self.output.push_str(" : ");
 
         // Find and copy the right operand
-        while self.pos < self.input.len() {
-            let ch = self.current_char();
-            match ch {
-                Some(';') | Some(',') | Some(')') | Some('}') | Some(']') | Some('\n') => {
-                    self.output.push(')');
-                    break;
-                }
-                Some(c) => {
-                    self.output.push(c);
-                    self.pos += 1;
-                }
-                None => {
-                    self.output.push(')');
-                    break;
+        let end = self.find_operator_right_operand();
+        let right = self.downlevel(self.pos..end);
+        self.output.append(&right);
+        self.output.push(')');
+        self.pos = end;
+    }
+
+    /// Downlevel and copy the contents of the bracket group opened just before `pos`,
+    /// then its closing bracket
+    fn copy_group(&mut self) {
+        let close = lexer::closing_bracket(self.input, self.pos, self.end);
+        let code = self.downlevel(self.pos..close);
+        self.output.append(&code);
+        if close < self.end {
+            self.output.copy(&self.input[close..close + 1], close);
+        }
+        self.pos = (close + 1).min(self.end);
+    }
+
+    /// End of the operand starting at `pos`: the `;`, `,` or unmatched closing bracket
+    /// after it, or the line break that follows it
+    fn find_operator_right_operand(&self) -> usize {
+        let mut depth = 0usize;
+        let mut started = false;
+        for token in lexer::Lexer::new(self.input, self.pos, self.end) {
+            if token.kind == TokenKind::Whitespace {
+                if started && depth == 0 && token.text(self.input).contains('\n') {
+                    return token.start;
                 }
+                continue;
+            }
+            started = true;
+            if token.kind != TokenKind::Punct {
+                continue;
+            }
+            match self.char_at(token.start) {
+                '(' | '[' | '{' => depth += 1,
+                ';' | ',' | ')' | '}' | ']' if depth == 0 => return token.start,
+                ')' | '}' | ']' => depth -= 1,
+                _ => {}
             }
         }
+        self.end
     }
 
     fn find_object_start(&self) -> usize {
@@ -435,7 +1779,7 @@ impl Transpiler {
         let mut started = false;
         let mut paren_depth = 0; // counts both () and []
 
-        for (idx, ch) in self.output.char_indices().rev() {
+        for (idx, ch) in self.output.as_str().char_indices().rev() {
             if !started {
                 if ch.is_whitespace() {
                     continue;
@@ -491,7 +1835,7 @@ impl Transpiler {
         let mut paren_depth = 0;
         let mut bracket_depth = 0;
 
-        for (idx, ch) in self.output.char_indices().rev() {
+        for (idx, ch) in self.output.as_str().char_indices().rev() {
             match ch {
                 ')' => paren_depth += 1,
                 '(' => {
@@ -518,30 +1862,91 @@ impl Transpiler {
         0
     }
 
+    /// The char at byte offset `pos` of the input
+    fn char_at(&self, pos: usize) -> char {
+        self.input[pos..].chars().next().unwrap_or_default()
+    }
+
     fn current_char(&self) -> Option<char> {
-        self.input.get(self.pos).copied()
+        self.peek(0)
     }
 
     fn peek(&self, offset: usize) -> Option<char> {
-        self.input.get(self.pos + offset).copied()
+        self.input.get(self.pos..self.end)?.chars().nth(offset)
     }
+}
 
-    fn is_word_boundary_at(&self, pos: usize) -> bool {
-        match self.input.get(pos) {
-            Some(c) => !c.is_alphanumeric() && *c != '_' && *c != '$',
-            None => true,
+/// Byte index in `code` where the callee of a call about to be written ends it: a chain of
+/// names, member accesses, indexing and calls
+fn callee_start(code: &str) -> Option<usize> {
+    let code = code.trim_end();
+    let before = |i: usize| code[..i].chars().next_back();
+    let mut i = code.len();
+    loop {
+        let group = match before(i)? {
+            ')' | ']' => {
+                let mut depth = 0usize;
+                loop {
+                    let c = before(i)?;
+                    i -= c.len_utf8();
+                    match c {
+                        ')' | ']' => depth += 1,
+                        '(' | '[' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                true
+            }
+            c if lexer::is_identifier_char(c) => {
+                i = code[..i].trim_end_matches(lexer::is_identifier_char).len();
+                false
+            }
+            _ => return None,
+        };
+        match before(i) {
+            Some('.') => i -= 1,
+            Some(c) if group && (lexer::is_identifier_char(c) || c == ')' || c == ']') => {}
+            _ => return Some(i),
         }
     }
+}
 
-    fn has_valid_word_prefix(&self) -> bool {
-        if self.pos == 0 {
-            return true;
-        }
-        match self.input.get(self.pos - 1) {
-            Some(c) => !c.is_alphanumeric() && *c != '_' && *c != '$',
-            None => true,
-        }
+/// The object a callee is a member of (`a.b` in `a.b.c` or `a.b[c]`), if it is a member access
+fn member_object(callee: &str) -> Option<&str> {
+    if let Some(open) = callee.strip_suffix(']').and_then(|_| {
+        let mut depth = 0usize;
+        callee.char_indices().rev().find(|&(_, c)| {
+            match c {
+                ']' | ')' => depth += 1,
+                '[' | '(' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        })
+    }) {
+        return Some(&callee[..open.0]).filter(|object| !object.is_empty());
     }
+    let name_start = callee.trim_end_matches(lexer::is_identifier_char).len();
+    callee[..name_start].strip_suffix('.').filter(|object| !object.is_empty())
+}
+
+/// `steps` as the declarators of a `var` statement
+fn declarators(steps: &[Step]) -> String {
+    let declarators: Vec<String> = steps.iter().map(|step| format!("{} = {}", step.target, step.value)).collect();
+    declarators.join(", ")
+}
+
+/// Whether the expression `code` is a plain name, which can be read repeatedly
+fn is_simple(code: &str) -> bool {
+    code.starts_with(lexer::is_identifier_start) && code.chars().all(lexer::is_identifier_char)
+}
+
+/// `value` as a double-quoted ES5 string literal
+fn string_literal(value: &str) -> String {
+    format!("\"{}\"", escape_string(value).replace('\u{2028}', "\\u2028").replace('\u{2029}', "\\u2029"))
 }
 
 #[cfg(test)]
@@ -561,7 +1966,7 @@ mod tests {
         let src = r#"const msg = `Hello ${user?.name}`;"#;
         let result = downlevel_for_jsc(src).unwrap();
         assert!(!result.contains("?."));
-        assert!(result.contains("`"));
+        assert!(!result.contains("`"));
     }
 
     #[test]
@@ -619,7 +2024,8 @@ mod tests {
         let src = r#"const url = `api/${version}/${endpoint?.type || 'default'}/path`;"#;
         let result = downlevel_for_jsc(src).unwrap();
         assert!(!result.contains("?."));
-        assert!(result.contains("`"));
+        assert!(result.starts_with(r#"var url = "api/" + String(version) + "/" + String((endpoint != null"#), "{}", result);
+        assert!(result.ends_with(r#"|| 'default') + "/path";"#), "{}", result);
     }
 
     #[test]
@@ -663,4 +2069,300 @@ mod tests {
         assert!(!result.contains("?."));
         assert!(result.contains("return await (obj != null ? obj.read : undefined)();"));
     }
+
+    #[test]
+    fn test_regex_literals_are_not_transformed() {
+        let src = "const re = /a?.b??c/g; const q = s.split(/'/); const x = obj?.prop;";
+        let result = downlevel_for_jsc(src).unwrap();
+        assert!(result.contains("/a?.b??c/g"));
+        assert!(result.contains("s.split(/'/)"));
+        assert!(result.contains("obj != null ? obj.prop"));
+    }
+
+    #[test]
+    fn test_conditional_with_decimal_is_not_optional_chaining() {
+        let result = downlevel_for_jsc("const x = a?.5:1;").unwrap();
+        assert_eq!(result, "var x = a?.5:1;");
+    }
+
+    #[test]
+    fn test_nullish_coalescing_right_operand_spans_call() {
+        let result = downlevel_for_jsc("const x = a ?? f(b, ')') ?? c?.d;").unwrap();
+        assert!(!result.contains("??") && !result.contains("?."), "{}", result);
+        assert!(result.contains("(f(b, ')') != null ? f(b, ')') :"), "{}", result);
+        assert!(result.ends_with("(c != null ? c.d : undefined)));"), "{}", result);
+    }
+
+    #[test]
+    fn test_arrow_functions_become_function_expressions() {
+        let result = downlevel_for_jsc("const f = x => x * 2; const g = (a, b) => { return a + b; };").unwrap();
+        assert_eq!(result, "var f = function (x) { return x * 2; }; var g = function (a, b) { return a + b; };");
+        let result = downlevel_for_jsc("items.map(i => i.id).filter(Boolean); const c = a => b => a + b;").unwrap();
+        assert_eq!(
+            result,
+            "items.map(function (i) { return i.id; }).filter(Boolean); var c = function (a) { return function (b) { return a + b; }; };"
+        );
+    }
+
+    #[test]
+    fn test_arrow_returning_object_literal() {
+        let result = downlevel_for_jsc("const g = () => ({ a: 1, b: [x, y] });").unwrap();
+        assert_eq!(result, "var g = function () { return ({ a: 1, b: [x, y] }); };");
+    }
+
+    #[test]
+    fn test_arrow_expression_body_ends_at_enclosing_syntax() {
+        let result = downlevel_for_jsc("const t = ok ? x => x : y => -y;").unwrap();
+        assert_eq!(result, "var t = ok ? function (x) { return x; } : function (y) { return -y; };");
+        let result = downlevel_for_jsc("const a = () => a\nconst b = n => n // n\n  + 1").unwrap();
+        assert_eq!(result, "var a = function () { return a; }\nvar b = function (n) { return n // n\n  + 1; }");
+        let result = downlevel_for_jsc("const u = `${xs.map(x => x + 1)}`;").unwrap();
+        assert_eq!(result, "var u = String(xs.map(function (x) { return x + 1; }));");
+    }
+
+    #[test]
+    fn test_arrow_captures_this_and_arguments_of_enclosing_function() {
+        let src = "function C() { this.n = 0; setTimeout(() => { this.n++; log(arguments[0], obj.this); }, 10); }";
+        let result = downlevel_for_jsc(src).unwrap();
+        assert_eq!(
+            result,
+            "function C() { var _this = this, _arguments = arguments; this.n = 0; setTimeout(function () { _this.n++; log(_arguments[0], obj.this); }, 10); }"
+        );
+        let result = downlevel_for_jsc("class A { m() { return () => () => this.v; } }").unwrap();
+        assert_eq!(
+            result,
+            "class A { m() { var _this = this; return function () { return function () { return _this.v; }; }; } }"
+        );
+    }
+
+    #[test]
+    fn test_arrow_aliases_do_not_shadow_user_bindings() {
+        let src = "function C(_this, _this2) { return () => [this, _this, `${_arguments}`, arguments]; }";
+        let result = downlevel_for_jsc(src).unwrap();
+        assert_eq!(
+            result,
+            "function C(_this, _this2) { var _this3 = this, _arguments2 = arguments; return function () { return [_this3, _this, String(_arguments), _arguments2]; }; }"
+        );
+    }
+
+    #[test]
+    fn test_arrow_regular_functions_keep_their_own_this() {
+        let result = downlevel_for_jsc("const f = () => function () { return this; };").unwrap();
+        assert_eq!(result, "var f = function () { return function () { return this; }; };");
+        let result = downlevel_for_jsc("if (a) { f(() => this); }").unwrap();
+        assert_eq!(result, "var _this = this; if (a) { f(function () { return _this; }); }");
+    }
+
+    #[test]
+    fn test_arrow_body_is_downleveled() {
+        let result = downlevel_for_jsc("const q = async () => { const r = await obj?.load(); return r ?? d; };").unwrap();
+        assert!(!result.contains("=>") && !result.contains("?.") && !result.contains("??"), "{}", result);
+        assert!(result.starts_with("var q = async function () { var r = await (obj != null"), "{}", result);
+    }
+
+    #[test]
+    fn test_template_literals_become_concatenation() {
+        let result = downlevel_for_jsc("const a = `${n} items`; const b = ``; f(`${c}`, `x`);").unwrap();
+        assert_eq!(result, r#"var a = String(n) + " items"; var b = ""; f(String(c), "x");"#);
+        let result = downlevel_for_jsc("const d = x - `a${b}`.length + typeof `${c}${e}`;").unwrap();
+        assert_eq!(result, r#"var d = x - ("a" + String(b)).length + typeof (String(c) + String(e));"#);
+        let result = downlevel_for_jsc("const h = `a${`b${c}`}${d, e}`;").unwrap();
+        assert_eq!(result, r#"var h = "a" + String("b" + String(c)) + String((d, e));"#);
+    }
+
+    #[test]
+    fn test_template_escapes_and_lines_are_translated() {
+        let result = downlevel_for_jsc("const s = `say \"hi\"\\t\\` \\${x}\r\n\\u{1F600}\u{2028}\\\nend`;").unwrap();
+        assert_eq!(result, "var s = \"say \\\"hi\\\"\\t` ${x}\\n\u{1F600}\\u2028end\";");
+    }
+
+    #[test]
+    fn test_tagged_templates_call_with_strings_object() {
+        let result = downlevel_for_jsc("const e = html`<p>${name}</p>\\n`;\nfunction g() { return String.raw`\\u{` + (() => t`${this.x}`)(); }").unwrap();
+        let (prelude, code) = result.split_once("var e").unwrap();
+        assert!(prelude.starts_with("function __hook_template_object(cooked, raw) {"), "{}", result);
+        assert!(prelude.ends_with(concat!(
+            r#"var __hook_template_1 = __hook_template_object(["<p>", "</p>\n"], ["<p>", "</p>\\n"]); "#,
+            r#"var __hook_template_2 = __hook_template_object([void 0], ["\\u{"]); "#,
+            r#"var __hook_template_3 = __hook_template_object(["", ""], ["", ""]); "#,
+        )), "{}", result);
+        assert_eq!(
+            code,
+            " = html(__hook_template_1, name);\nfunction g() { var _this = this; return String.raw(__hook_template_2) + (function () { return t(__hook_template_3, _this.x); })(); }"
+        );
+    }
+
+    #[test]
+    fn test_declaration_patterns_become_declarators() {
+        let result = downlevel_for_jsc("const [state, setState] = useState(0);").unwrap();
+        assert_eq!(result, "var _ref = useState(0), state = _ref[0], setState = _ref[1];");
+        let result = downlevel_for_jsc("let [x, , y = f(), ...zs] = list, n = 1;").unwrap();
+        assert_eq!(result, "var x = list[0], _ref = list[2], y = _ref === void 0 ? f() : _ref, zs = list.slice(3), n = 1;");
+        let result = downlevel_for_jsc("var { a: { b: [c] = [] } = {}, 'd-e': d, [k]: v } = obj;").unwrap();
+        assert_eq!(
+            result,
+            "var _ref = obj.a, _ref2 = _ref === void 0 ? {} : _ref, _ref3 = _ref2.b, _ref4 = _ref3 === void 0 ? [] : _ref3, c = _ref4[0], d = obj['d-e'], v = obj[k];"
+        );
+    }
+
+    #[test]
+    fn test_object_rest_uses_helper() {
+        let result = downlevel_for_jsc("const { a, [key()]: b, 0: c, ...rest } = props;").unwrap();
+        let (helper, code) = result.split_once("} } return rest; } ").unwrap();
+        assert!(helper.starts_with("function __hook_object_rest(source, excluded) {"), "{}", result);
+        assert_eq!(
+            code,
+            r#"var a = props.a, _ref = key(), b = props[_ref], c = props[0], rest = __hook_object_rest(props, ["a", _ref + "", "0"]);"#
+        );
+    }
+
+    #[test]
+    fn test_loop_head_patterns_destructure_in_body() {
+        let result = downlevel_for_jsc("for (const [k, { v }] of Object.entries(o)) { log(k, v); }").unwrap();
+        assert_eq!(result, "for (var _ref of Object.entries(o)) { var k = _ref[0], _ref2 = _ref[1], v = _ref2.v; log(k, v); }");
+        let result = downlevel_for_jsc("for (const [k, v] of m) if (v) log(k, v); else skip(k);\nnext();").unwrap();
+        assert_eq!(result, "for (var _ref of m) { var k = _ref[0], v = _ref[1]; if (v) log(k, v); else skip(k); }\nnext();");
+        let result = downlevel_for_jsc("for (const { id } of xs) ids.push(id), count++\nnext()").unwrap();
+        assert_eq!(result, "for (var _ref of xs) { var id = _ref.id; ids.push(id), count++ }\nnext()");
+    }
+
+    #[test]
+    fn test_catch_parameter_patterns_destructure_in_body() {
+        let result = downlevel_for_jsc("try { run(); } catch ({ message, code = 0 }) { log(message, code); }").unwrap();
+        assert_eq!(
+            result,
+            "try { run(); } catch (_ref) { var message = _ref.message, _ref2 = _ref.code, code = _ref2 === void 0 ? 0 : _ref2; log(message, code); }"
+        );
+        let result = downlevel_for_jsc("try { run(); } catch (error) { log(error); }").unwrap();
+        assert_eq!(result, "try { run(); } catch (error) { log(error); }");
+    }
+
+    #[test]
+    fn test_temporaries_skip_names_the_source_uses() {
+        let result = downlevel_for_jsc("const _ref = 1; const { a } = f();").unwrap();
+        assert_eq!(result, "var _ref = 1; var _ref2 = f(), a = _ref2.a;");
+    }
+
+    #[test]
+    fn test_assignment_patterns_use_hoisted_temporaries() {
+        let result = downlevel_for_jsc("function swap() { [a, b] = [b, a]; ({ x: this.x } = p); }").unwrap();
+        assert_eq!(
+            result,
+            "function swap() { var _ref, _ref2; (_ref = [b, a], a = _ref[0], b = _ref[1], _ref); ((_ref2 = p, this.x = _ref2.x, _ref2)); }"
+        );
+    }
+
+    #[test]
+    fn test_parameter_patterns_defaults_and_rest() {
+        let result = downlevel_for_jsc("function C({ title, items = [] }, n = 1, ...rest) { return title; }").unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "function C(_ref, n) { var title = _ref.title, _ref2 = _ref.items, items = _ref2 === void 0 ? [] : _ref2; ",
+                "if (n === void 0) n = 1; var rest = Array.prototype.slice.call(arguments, 2); return title; }"
+            )
+        );
+        let result = downlevel_for_jsc("xs.map(({ id }, i = 0) => id + i); const f = (...args) => { g(args); };").unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "xs.map(function (_ref, i) { var id = _ref.id; if (i === void 0) i = 0; return id + i; }); ",
+                "var f = function () { var args = Array.prototype.slice.call(arguments, 0); g(args); };"
+            )
+        );
+        let result = downlevel_for_jsc("class A { m({ a } = {}) { return () => this.v + a; } }").unwrap();
+        assert_eq!(
+            result,
+            "class A { m(_ref) { var _this = this; var _ref2 = _ref === void 0 ? {} : _ref, a = _ref2.a; return function () { return _this.v + a; }; } }"
+        );
+    }
+
+    #[test]
+    fn test_object_spread_and_computed_keys_use_assign() {
+        let result = downlevel_for_jsc("const s = { a: 1, ...b, c, [k()]: 2, ...d };").unwrap();
+        let (helpers, code) = result.split_once("return object; } ").unwrap();
+        assert!(helpers.starts_with("function __hook_assign(target) {"), "{}", result);
+        assert_eq!(code, "var s = __hook_assign({ a: 1 }, b, { c: c }, __hook_property(k(), 2), d);");
+        let result = downlevel_for_jsc("const f = () => ({ ...state, [key]() { return this; } });").unwrap();
+        assert_eq!(
+            result.split_once("return object; } ").unwrap().1,
+            "var f = function () { return (__hook_assign({}, state, __hook_property(key, function () { return this; }))); };"
+        );
+    }
+
+    #[test]
+    fn test_helpers_and_aliases_follow_directives() {
+        let result = downlevel_for_jsc("'use strict';\nconst s = { ...a };\nfunction f() { 'use strict'; return () => this; }").unwrap();
+        assert_eq!(
+            result,
+            format!(
+                "'use strict'; {}\nvar s = __hook_assign({{}}, a);\nfunction f() {{ 'use strict'; var _this = this; return function () {{ return _this; }}; }}",
+                OBJECT_ASSIGN_HELPER.trim_end()
+            )
+        );
+        // Only computed keys need `__hook_property`
+        assert!(!result.contains("__hook_property"), "{}", result);
+    }
+
+    #[test]
+    fn test_shorthand_properties_and_methods_are_spelled_out() {
+        let result = downlevel_for_jsc("const o = { a, 'b'() { return () => this; }, c: { d }, get e() { return 1; } };").unwrap();
+        assert_eq!(
+            result,
+            "var o = { a: a, 'b': function () { var _this = this; return function () { return _this; }; }, c: { d: d }, get e() { return 1; } };"
+        );
+    }
+
+    #[test]
+    fn test_blocks_are_not_object_literals() {
+        for src in [
+            "if (a) { b }",
+            "label: { a }",
+            "switch (k) { case 1: { a } default: { if (b) { c(); } } }",
+            "x = y ? { a: 1 } : 2; { z }",
+        ] {
+            assert_eq!(downlevel_for_jsc(src).unwrap(), src);
+        }
+        assert_eq!(downlevel_for_jsc("x = y ? a : { b };").unwrap(), "x = y ? a : { b: b };");
+    }
+
+    #[test]
+    fn test_module_specifier_lists_are_not_object_literals() {
+        for src in [
+            "import D, { a } from 'm';",
+            "import { a, b as c } from 'm';",
+            "export { a, b as default };",
+            "export { a } from 'm';",
+            "import * as ns from 'm';",
+        ] {
+            assert_eq!(downlevel_for_jsc(src).unwrap(), src);
+        }
+        assert_eq!(downlevel_for_jsc("export default { a };").unwrap(), "export default { a: a };");
+        assert_eq!(downlevel_for_jsc("import('m').then(({ a }) => a);").unwrap(), "import('m').then(function (_ref) { var a = _ref.a; return a; });");
+    }
+
+    #[test]
+    fn test_array_spread_uses_concat() {
+        let result = downlevel_for_jsc("const a = [...list, item]; const b = [x, ...ys, , ];").unwrap();
+        let (helper, code) = result.split_once("slice.call(value); } ").unwrap();
+        assert!(helper.starts_with("function __hook_spread(value) {"), "{}", result);
+        assert_eq!(code, "var a = [].concat(__hook_spread(list), [item]); var b = [x].concat(__hook_spread(ys), [,]);");
+    }
+
+    #[test]
+    fn test_call_spread_uses_apply() {
+        let result = downlevel_for_jsc("f(...args); this.set(...args); a[b](...c); g()(...d);").unwrap();
+        assert_eq!(
+            result,
+            "f.apply(void 0, args); this.set.apply(this, args); a[b].apply(a, c); g().apply(void 0, d);"
+        );
+        let result = downlevel_for_jsc("function h() { obj.m(a, ...b); get().m(...c); return new Err(...d); }").unwrap();
+        assert_eq!(
+            result.split_once("slice.call(value); } ").unwrap().1,
+            concat!(
+                "function h() { var _ref; obj.m.apply(obj, [a].concat(__hook_spread(b))); (_ref = get()).m.apply(_ref, c); ",
+                "return new (Function.prototype.bind.apply(Err, [null].concat(__hook_spread(d))))(); }"
+            )
+        );
+    }
 }
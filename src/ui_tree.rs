@@ -0,0 +1,260 @@
+//! Serializable element trees for native renderers, produced by [`crate::compile_ui_tree`].
+//!
+//! Markup that is mostly static does not need JavaScript to be displayed. This backend
+//! turns every outermost JSX element or fragment of a module into a [`UiRoot`]: element
+//! types, literal props and text as data, with each dynamic expression replaced by a
+//! numbered placeholder whose source is listed in [`UiRoot::slots`]. A renderer can inflate
+//! a root whose `is_static` is set without evaluating any code, and the static parts of the
+//! others before the hook fills in their slots.
+//!
+//! Intrinsic tags and props are renamed by the target's element map, as in code generation.
+//! JSX nested inside a dynamic expression stays part of that slot's source. With the
+//! `ast-json` feature the tree serializes with serde, enums tagged by `"type"`.
+
+#[cfg(feature = "ast-json")]
+use serde::Serialize;
+
+use crate::ast::{
+    Expression, JsxAttributeItem, JsxAttributeValue, JsxChild, JsxElement, JsxFragment, JsxNode, Module,
+};
+use crate::element_map::ElementMap;
+use crate::error::Span;
+use crate::jsx_codegen::is_intrinsic;
+use crate::lexer::{self, TokenKind};
+
+/// The JSX roots of a module in source order
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UiTree {
+    pub roots: Vec<UiRoot>,
+}
+
+/// One outermost JSX element or fragment
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiRoot {
+    pub node: UiNode,
+    /// Dynamic expressions, indexed by the `slot` of each placeholder
+    pub slots: Vec<UiSlot>,
+    /// Whether the root has no slots and no components, so it renders without running code
+    pub is_static: bool,
+    pub span: Span,
+}
+
+/// A dynamic expression left for the hook to evaluate
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiSlot {
+    pub source: String,
+    pub span: Span,
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiNode {
+    /// An intrinsic element such as `div`, with its tag as the renderer expects it
+    Element { name: String, props: Vec<UiProp>, children: Vec<UiNode> },
+    /// A component (`Button`, `Icons.Arrow`), which needs the hook's code to render
+    Component { name: String, props: Vec<UiProp>, children: Vec<UiNode> },
+    Fragment { children: Vec<UiNode> },
+    /// Text as React renders it: entities decoded, JSX whitespace rules applied
+    Text { value: String },
+    /// A child computed at run time
+    Dynamic { slot: usize },
+}
+
+/// A prop in source order, so later props and spreads override earlier ones
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiProp {
+    Prop { name: String, value: UiValue },
+    /// `{...props}`
+    Spread { slot: usize },
+}
+
+#[cfg_attr(feature = "ast-json", derive(Serialize))]
+#[cfg_attr(feature = "ast-json", serde(tag = "type"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiValue {
+    String { value: String },
+    Number { value: f64 },
+    Boolean { value: bool },
+    Null,
+    /// `icon=<Icon />`
+    Element { node: Box<UiNode> },
+    Dynamic { slot: usize },
+}
+
+impl UiTree {
+    /// Build the tree for a parsed module, renaming intrinsic tags and props with `element_map`
+    pub fn from_module(module: &Module, element_map: Option<&ElementMap>) -> Self {
+        let roots = module
+            .jsx
+            .iter()
+            .map(|node| {
+                let mut builder = Builder { element_map, slots: Vec::new(), has_components: false };
+                let ui_node = builder.node(node);
+                UiRoot {
+                    node: ui_node,
+                    is_static: builder.slots.is_empty() && !builder.has_components,
+                    slots: builder.slots,
+                    span: node.span(),
+                }
+            })
+            .collect();
+        Self { roots }
+    }
+}
+
+/// Converts one root, collecting its slots
+struct Builder<'a> {
+    element_map: Option<&'a ElementMap>,
+    slots: Vec<UiSlot>,
+    has_components: bool,
+}
+
+impl Builder<'_> {
+    fn node(&mut self, node: &JsxNode) -> UiNode {
+        match node {
+            JsxNode::Element(element) => self.element(element),
+            JsxNode::Fragment(fragment) => self.fragment(fragment),
+        }
+    }
+
+    fn element(&mut self, element: &JsxElement) -> UiNode {
+        let tag = element.name.to_string();
+        let intrinsic = is_intrinsic(&tag);
+        let map = self.element_map.filter(|_| intrinsic);
+        let props = element
+            .attributes
+            .iter()
+            .map(|item| match item {
+                JsxAttributeItem::Attribute(attr) => UiProp::Prop {
+                    name: map.map_or(attr.name.as_str(), |map| map.prop(&tag, &attr.name)).to_string(),
+                    value: match &attr.value {
+                        None => UiValue::Boolean { value: true },
+                        Some(JsxAttributeValue::String { value, .. }) => UiValue::String { value: value.clone() },
+                        Some(JsxAttributeValue::ExpressionContainer(container)) => match &container.expression {
+                            Some(expression) => self.value(expression),
+                            None => UiValue::Null,
+                        },
+                        Some(JsxAttributeValue::Element(node)) => UiValue::Element { node: Box::new(self.node(node)) },
+                    },
+                },
+                JsxAttributeItem::Spread(spread) => UiProp::Spread { slot: self.slot(&spread.argument) },
+            })
+            .collect();
+        let children = self.children(&element.children);
+        if intrinsic {
+            let name = map.map_or(tag.as_str(), |map| map.tag(&tag)).to_string();
+            UiNode::Element { name, props, children }
+        } else {
+            self.has_components = true;
+            UiNode::Component { name: tag, props, children }
+        }
+    }
+
+    fn fragment(&mut self, fragment: &JsxFragment) -> UiNode {
+        UiNode::Fragment { children: self.children(&fragment.children) }
+    }
+
+    /// The children React renders: empty text, empty containers and booleans and `null`
+    /// produce nothing
+    fn children(&mut self, children: &[JsxChild]) -> Vec<UiNode> {
+        children
+            .iter()
+            .filter_map(|child| match child {
+                JsxChild::Text(text) if text.value.is_empty() => None,
+                JsxChild::Text(text) => Some(UiNode::Text { value: text.value.clone() }),
+                JsxChild::ExpressionContainer(container) => match self.value(container.expression.as_ref()?) {
+                    UiValue::String { value } => Some(UiNode::Text { value }),
+                    UiValue::Number { value } => Some(UiNode::Text { value: number_text(value) }),
+                    UiValue::Dynamic { slot } => Some(UiNode::Dynamic { slot }),
+                    _ => None,
+                },
+                JsxChild::Spread(spread) => Some(UiNode::Dynamic { slot: self.slot(&spread.expression) }),
+                JsxChild::Element(element) => Some(self.element(element)),
+                JsxChild::Fragment(fragment) => Some(self.fragment(fragment)),
+            })
+            .collect()
+    }
+
+    /// The literal `expression` as data, or a placeholder for it
+    fn value(&mut self, expression: &Expression) -> UiValue {
+        literal_value(expression).unwrap_or_else(|| UiValue::Dynamic { slot: self.slot(expression) })
+    }
+
+    fn slot(&mut self, expression: &Expression) -> usize {
+        self.slots.push(UiSlot { source: expression.source.clone(), span: expression.span });
+        self.slots.len() - 1
+    }
+}
+
+/// The value of a literal expression ([`Expression::is_literal`]), or `None` if it is not
+/// one or uses syntax whose value is not worth reproducing here (legacy octal, BigInt)
+pub(crate) fn literal_value(expression: &Expression) -> Option<UiValue> {
+    if !expression.is_literal() {
+        return None;
+    }
+    let source = expression.source.as_str();
+    let mut tokens = lexer::Lexer::new(source, 0, source.len()).filter(|token| !token.is_trivia());
+    let mut token = tokens.next()?;
+    let negated = token.is_punct(source, '-');
+    if negated {
+        token = tokens.next()?;
+    }
+    let text = token.text(source);
+    match token.kind {
+        TokenKind::String | TokenKind::Template => {
+            let inner = &text[1..text.len() - 1];
+            lexer::cooked_string(inner).map(|value| UiValue::String { value })
+        }
+        TokenKind::Number => {
+            number_value(text).map(|value| UiValue::Number { value: if negated { -value } else { value } })
+        }
+        _ => Some(match text {
+            "true" => UiValue::Boolean { value: true },
+            "false" => UiValue::Boolean { value: false },
+            _ => UiValue::Null,
+        }),
+    }
+}
+
+/// The value of a numeric literal
+fn number_value(text: &str) -> Option<f64> {
+    let text = text.replace('_', "");
+    let radix = match text.get(..2).map(str::to_ascii_lowercase).as_deref() {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ if text.ends_with('n') || (text.starts_with('0') && text[1..].starts_with(|c: char| c.is_ascii_digit())) => {
+            return None;
+        }
+        _ => return text.parse().ok(),
+    };
+    u64::from_str_radix(&text[2..], radix).ok().map(|value| value as f64)
+}
+
+/// A number as JavaScript converts it to text for the common cases: integers without a
+/// fraction and no negative zero
+pub(crate) fn number_text(value: f64) -> String {
+    if value == 0.0 { "0".to_string() } else { value.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_parsed() {
+        assert_eq!(number_value("1_000.5"), Some(1000.5));
+        assert_eq!(number_value("0xFF"), Some(255.0));
+        assert_eq!(number_value(".5e1"), Some(5.0));
+        assert_eq!(number_value("017"), None);
+        assert_eq!(number_value("10n"), None);
+        assert_eq!(number_text(42.0), "42");
+        assert_eq!(number_text(-0.0), "0");
+    }
+}
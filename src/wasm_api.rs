@@ -7,7 +7,13 @@ use std::sync::Mutex;
 #[derive(Serialize)]
 struct WasmTranspileResult {
     code: Option<String>,
+    /// v3 source map JSON, also inlined into `code`, when source maps were requested
+    source_map: Option<String>,
     error: Option<String>,
+    /// Structured error (code, span, code frame) for editor and overlay integrations
+    diagnostic: Option<TranspileError>,
+    /// Non-fatal problems found while transpiling, e.g. unknown HTML entities
+    warnings: Vec<TranspileError>,
 }
 
 #[derive(Serialize)]
@@ -15,6 +21,28 @@ struct WasmTranspileResultWithMetadata {
     code: Option<String>,
     metadata: Option<crate::TranspileMetadata>,
     error: Option<String>,
+    diagnostic: Option<TranspileError>,
+}
+
+#[derive(Serialize)]
+struct WasmParseResult {
+    module: Option<crate::ast::Module>,
+    error: Option<String>,
+    diagnostic: Option<TranspileError>,
+}
+
+#[derive(Serialize)]
+struct WasmUiTreeResult {
+    tree: Option<crate::ui_tree::UiTree>,
+    error: Option<String>,
+    diagnostic: Option<TranspileError>,
+}
+
+#[derive(Serialize)]
+struct WasmPrerenderResult {
+    html: Option<crate::html_prerender::PrerenderedHtml>,
+    error: Option<String>,
+    diagnostic: Option<TranspileError>,
 }
 
 #[derive(Serialize)]
@@ -54,8 +82,17 @@ pub fn get_debug_level() -> String {
     })
 }
 
+/// Transpile for the browser; with `source_maps` the code carries an inline source map so
+/// devtools show the original hook, and `jsx_backend: "dom"` builds DOM nodes directly
+/// instead of calling the JSX runtime
 #[wasm_bindgen]
-pub fn transpile_jsx(source: &str, filename: &str, is_typescript: Option<bool>) -> JsValue {
+pub fn transpile_jsx(
+    source: &str,
+    filename: &str,
+    is_typescript: Option<bool>,
+    source_maps: Option<bool>,
+    jsx_backend: Option<String>,
+) -> JsValue {
     let is_typescript = is_typescript.unwrap_or_else(|| {
         filename.ends_with(".ts") || filename.ends_with(".tsx")
     });
@@ -66,25 +103,178 @@ pub fn transpile_jsx(source: &str, filename: &str, is_typescript: Option<bool>)
             .unwrap_or(DebugLevel::default())
     });
     
+    let source_maps = source_maps.unwrap_or(false);
     let opts = TranspileOptions {
         is_typescript,
         target: TranspileTarget::Web,  // Web browsers support modern JS
         filename: Some(filename.to_string()),
-        source_maps: false,
-        inline_source_map: false,
+        source_maps,
+        inline_source_map: source_maps,
         compat_for_jsc: false,
         debug_level,
+        jsx_backend: match jsx_backend.as_deref() {
+            Some("dom") => JsxBackend::Dom,
+            _ => JsxBackend::Runtime,
+        },
         ..Default::default()
     };
     
-    let result = match transpile_jsx_with_options(source, &opts) {
-        Ok(code) => WasmTranspileResult {
-            code: Some(code),
+    let result = match transpile_jsx_with_source_map(source, &opts) {
+        Ok(output) => WasmTranspileResult {
+            code: Some(output.code),
+            source_map: output.source_map,
             error: None,
+            diagnostic: None,
+            warnings: output.warnings,
         },
         Err(err) => WasmTranspileResult {
             code: None,
+            source_map: None,
+            error: Some(err.to_string()),
+            diagnostic: Some(err),
+            warnings: Vec::new(),
+        },
+    };
+    to_value(&result)
+        .unwrap_or_else(|err| JsValue::from_str(&format!("serde-wasm-bindgen error: {err}")))
+}
+
+/// Transpile in error-recovery mode, returning `{ code, diagnostics }` so editors can
+/// underline every problem in one pass
+#[wasm_bindgen]
+pub fn transpile_jsx_recovering(source: &str, filename: &str, is_typescript: Option<bool>) -> JsValue {
+    let is_typescript = is_typescript.unwrap_or_else(|| {
+        filename.ends_with(".ts") || filename.ends_with(".tsx")
+    });
+
+    let debug_level = WASM_DEBUG_LEVEL.with(|dl| {
+        dl.lock()
+            .map(|level| *level)
+            .unwrap_or(DebugLevel::default())
+    });
+
+    let opts = TranspileOptions {
+        is_typescript,
+        target: TranspileTarget::Web,
+        filename: Some(filename.to_string()),
+        compat_for_jsc: false,
+        debug_level,
+        ..Default::default()
+    };
+
+    to_value(&crate::transpile_jsx_recovering(source, &opts))
+        .unwrap_or_else(|err| JsValue::from_str(&format!("serde-wasm-bindgen error: {err}")))
+}
+
+/// Parse without generating code, returning `{ module, error, diagnostic }` where `module`
+/// is the typed AST (imports, exports and JSX with spans)
+#[wasm_bindgen]
+pub fn parse_module(source: &str, filename: &str, is_typescript: Option<bool>) -> JsValue {
+    let is_typescript = is_typescript.unwrap_or_else(|| {
+        filename.ends_with(".ts") || filename.ends_with(".tsx")
+    });
+
+    let debug_level = WASM_DEBUG_LEVEL.with(|dl| {
+        dl.lock()
+            .map(|level| *level)
+            .unwrap_or(DebugLevel::default())
+    });
+
+    let opts = TranspileOptions {
+        is_typescript,
+        target: TranspileTarget::Web,
+        filename: Some(filename.to_string()),
+        debug_level,
+        ..Default::default()
+    };
+
+    let result = match crate::parse_module(source, &opts) {
+        Ok(module) => WasmParseResult {
+            module: Some(module),
+            error: None,
+            diagnostic: None,
+        },
+        Err(err) => WasmParseResult {
+            module: None,
+            error: Some(err.to_string()),
+            diagnostic: Some(err),
+        },
+    };
+    to_value(&result)
+        .unwrap_or_else(|err| JsValue::from_str(&format!("serde-wasm-bindgen error: {err}")))
+}
+
+/// Compile the JSX roots to element trees, returning `{ tree, error, diagnostic }` where
+/// `tree.roots` hold each root's elements, literal props and text with placeholders for
+/// dynamic expressions
+#[wasm_bindgen]
+pub fn compile_ui_tree(source: &str, filename: &str, is_typescript: Option<bool>) -> JsValue {
+    let is_typescript = is_typescript.unwrap_or_else(|| {
+        filename.ends_with(".ts") || filename.ends_with(".tsx")
+    });
+
+    let debug_level = WASM_DEBUG_LEVEL.with(|dl| {
+        dl.lock()
+            .map(|level| *level)
+            .unwrap_or(DebugLevel::default())
+    });
+
+    let opts = TranspileOptions {
+        is_typescript,
+        target: TranspileTarget::Web,
+        filename: Some(filename.to_string()),
+        debug_level,
+        ..Default::default()
+    };
+
+    let result = match crate::compile_ui_tree(source, &opts) {
+        Ok(tree) => WasmUiTreeResult {
+            tree: Some(tree),
+            error: None,
+            diagnostic: None,
+        },
+        Err(err) => WasmUiTreeResult {
+            tree: None,
+            error: Some(err.to_string()),
+            diagnostic: Some(err),
+        },
+    };
+    to_value(&result)
+        .unwrap_or_else(|err| JsValue::from_str(&format!("serde-wasm-bindgen error: {err}")))
+}
+
+/// Pre-render the JSX roots to HTML, returning `{ html, error, diagnostic }` where
+/// `html.roots` hold each root's markup and the holes left in it for hydration
+#[wasm_bindgen]
+pub fn prerender_html(source: &str, filename: &str, is_typescript: Option<bool>) -> JsValue {
+    let is_typescript = is_typescript.unwrap_or_else(|| {
+        filename.ends_with(".ts") || filename.ends_with(".tsx")
+    });
+
+    let debug_level = WASM_DEBUG_LEVEL.with(|dl| {
+        dl.lock()
+            .map(|level| *level)
+            .unwrap_or(DebugLevel::default())
+    });
+
+    let opts = TranspileOptions {
+        is_typescript,
+        target: TranspileTarget::Web,
+        filename: Some(filename.to_string()),
+        debug_level,
+        ..Default::default()
+    };
+
+    let result = match crate::prerender_html(source, &opts) {
+        Ok(html) => WasmPrerenderResult {
+            html: Some(html),
+            error: None,
+            diagnostic: None,
+        },
+        Err(err) => WasmPrerenderResult {
+            html: None,
             error: Some(err.to_string()),
+            diagnostic: Some(err),
         },
     };
     to_value(&result)
@@ -107,11 +297,13 @@ pub fn transpile_jsx_with_metadata(source: &str, filename: &str, is_typescript:
             code: Some(transpile_result.code),
             metadata: Some(transpile_result.metadata),
             error: None,
+            diagnostic: None,
         },
         Err(err) => WasmTranspileResultWithMetadata {
             code: None,
             metadata: None,
             error: Some(err.to_string()),
+            diagnostic: Some(err),
         },
     };
     to_value(&result)
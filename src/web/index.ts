@@ -63,8 +63,8 @@ export async function initWasmTranspiler(): Promise<void> {
       await init({ module_or_path: wasmPath })
     }
 
-    const transpileFn = (code: string, filename: string, isTypescript?: boolean) => {
-      return transpile_jsx(code, filename || 'module.tsx', isTypescript)
+    const transpileFn = (code: string, filename: string, isTypescript?: boolean, sourceMaps?: boolean, jsxBackend?: 'runtime' | 'dom') => {
+      return transpile_jsx(code, filename || 'module.tsx', isTypescript, sourceMaps, jsxBackend)
     }
 
     const transpileWithMetadataFn = (code: string, filename: string, isTypescript?: boolean) => {
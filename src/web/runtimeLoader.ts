@@ -152,7 +152,7 @@ export class WebModuleLoader implements ModuleLoader {
                         ; (window as any).__hook_react = context.React
                     const jsxFactory = mkJsxFactory(context.React)
                     const fragmentFactory = (context.React && (context.React.Fragment || (context.React as any).Fragment)) ? (context.React.Fragment || (context.React as any).Fragment) : undefined
-                    if (!(window as any).__hook_jsx_runtime) { (window as any).__hook_jsx_runtime = { jsx: jsxFactory, jsxs: jsxFactory, Fragment: fragmentFactory } }
+                    if (!(window as any).__hook_jsx_runtime) { (window as any).__hook_jsx_runtime = { jsx: jsxFactory, jsxs: jsxFactory, Fragment: fragmentFactory, createElement: context.React?.createElement } }
                     if (!(window as any).__jsx) { (window as any).__jsx = jsxFactory; (window as any).__jsxs = jsxFactory }
                     if (!(window as any).__Fragment) { (window as any).__Fragment = fragmentFactory }
                     ; (window as any).__hook_file_renderer = (context && context.FileRenderer) || null
@@ -244,7 +244,7 @@ try {
             const jsxFactory2 = mkJsxFactory(context.React)
             const fragmentFactory = context.React?.Fragment
             if (!(globalThis as any).__hook_jsx_runtime) {
-                ; (globalThis as any).__hook_jsx_runtime = { jsx: jsxFactory2, jsxs: jsxFactory2, Fragment: fragmentFactory }
+                ; (globalThis as any).__hook_jsx_runtime = { jsx: jsxFactory2, jsxs: jsxFactory2, Fragment: fragmentFactory, createElement: context.React?.createElement }
             }
             if (!(globalThis as any).__jsx && jsxFactory2) {
                 ; (globalThis as any).__jsx = jsxFactory2
@@ -13,11 +13,11 @@ fn test_literal_array_of_jsx() {
     let out = transpile_jsx_simple(src).expect("should transpile");
     println!("OUTPUT:\n{}", out);
     
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"span\", { key: \"1\", children: [\"One\"] })"));
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"span\", { key: \"2\", children: [\"Two\"] })"));
-    // Ensure the array structure is preserved
-    assert!(out.contains("children: [["));
-    assert!(out.contains("]] })")); 
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"span\", { children: \"One\" }, \"1\")"));
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"span\", { children: \"Two\" }, \"2\")"));
+    // Ensure the array structure is preserved: the array is the only child
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { children: ["));
+    assert!(out.contains("] })")); 
 }
 
 #[test]
@@ -75,7 +75,7 @@ fn test_deeply_nested_arrays() {
     assert_eq!(count, 7, "Should have 7 JSX nodes, found {count}\nOutput: {out}");
     
     // Verify structure of one of the inner divs (note: whitespace might be preserved from original expression)
-    assert!(out.contains("children: [ [ __hook_jsx_runtime.jsx(\"span\", { key: \"1.1\", children: [\"Deep\"] }), __hook_jsx_runtime.jsx(\"span\", { key: \"1.2\", children: [\"Nested\"] }) ] ]"));
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { children: [ __hook_jsx_runtime.jsx(\"span\", { children: \"Deep\" }, \"1.1\"), __hook_jsx_runtime.jsx(\"span\", { children: \"Nested\" }, \"1.2\") ] }, \"1\")"));
 }
 
 #[test]
@@ -103,8 +103,8 @@ fn test_nested_array_in_map() {
     let out = transpile_jsx_simple(src).expect("should transpile");
 
     assert!(out.contains("item => ["));
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"li\", { key: item.id + \"-1\""));
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"li\", { key: item.id + \"-2\""));
+    assert!(out.contains("__hook_jsx_runtime.jsxs(\"li\", { children: [item.name, \" (1)\"] }, item.id + \"-1\")"));
+    assert!(out.contains("__hook_jsx_runtime.jsxs(\"li\", { children: [item.name, \" (2)\"] }, item.id + \"-2\")"));
 }
 
 #[test]
@@ -139,7 +139,7 @@ fn test_mixed_array() {
     let out = transpile_jsx_simple(src).expect("should transpile");
 
     assert!(out.contains("\"Static text\""));
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { key: \"div\", children: [\"JSX element\"] })"));
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { children: \"JSX element\" }, \"div\")"));
     assert!(out.contains("42"));
     assert!(out.contains("variable"));
 }
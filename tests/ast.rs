@@ -0,0 +1,224 @@
+use relay_hook_transpiler::ast::{
+    ExportDeclaration, ExportSpecifier, ImportSpecifier, JsxAttributeItem, JsxAttributeValue,
+    JsxChild, JsxElement, JsxElementName, JsxNode, Module,
+};
+use relay_hook_transpiler::{parse_module, ErrorCode, TranspileOptions};
+
+fn parse(src: &str) -> Module {
+    parse_module(src, &TranspileOptions::default()).unwrap_or_else(|e| panic!("{}", e.render()))
+}
+
+fn element(node: &JsxNode) -> &JsxElement {
+    match node {
+        JsxNode::Element(element) => element,
+        other => panic!("expected an element, got {other:?}"),
+    }
+}
+
+#[test]
+fn elements_attributes_and_children() {
+    let module = parse(r#"const a = <Card.Header id="x" disabled {...rest} icon=<Icon /> onClick={() => go(1)}>Hi &amp; bye{name}{/* note */}{...items}</Card.Header>;"#);
+    assert_eq!(module.jsx.len(), 1);
+    let card = element(&module.jsx[0]);
+    assert_eq!(card.name, JsxElementName::Member { parts: vec!["Card".into(), "Header".into()] });
+    assert!(!card.self_closing);
+
+    let [
+        JsxAttributeItem::Attribute(id),
+        JsxAttributeItem::Attribute(disabled),
+        JsxAttributeItem::Spread(rest),
+        JsxAttributeItem::Attribute(icon),
+        JsxAttributeItem::Attribute(on_click),
+    ] = card.attributes.as_slice()
+    else {
+        panic!("unexpected attributes: {:?}", card.attributes);
+    };
+    assert!(matches!(&id.value, Some(JsxAttributeValue::String { value, .. }) if value == "x"));
+    assert_eq!(disabled.name, "disabled");
+    assert_eq!(disabled.value, None);
+    assert_eq!(rest.argument.source, "rest");
+    assert!(matches!(&icon.value, Some(JsxAttributeValue::Element(node)) if element(node).self_closing));
+    let Some(JsxAttributeValue::ExpressionContainer(handler)) = &on_click.value else {
+        panic!("expected an expression");
+    };
+    assert_eq!(handler.expression.as_ref().unwrap().source, "() => go(1)");
+
+    let [JsxChild::Text(text), JsxChild::ExpressionContainer(name), JsxChild::ExpressionContainer(note), JsxChild::Spread(items)] =
+        card.children.as_slice()
+    else {
+        panic!("unexpected children: {:?}", card.children);
+    };
+    assert_eq!(text.value, "Hi & bye");
+    assert_eq!(text.raw, "Hi &amp; bye");
+    assert_eq!(name.expression.as_ref().unwrap().source, "name");
+    assert_eq!(note.expression, None);
+    assert_eq!(items.expression.source, "items");
+}
+
+#[test]
+fn spans_point_into_the_original_source() {
+    let src = "const a = 1;\nconst b = <ul>\n  <li>é</li>\n</ul>;";
+    let module = parse(src);
+    let ul = element(&module.jsx[0]);
+    assert_eq!((ul.span.line, ul.span.column), (2, 11));
+    assert_eq!(&src[ul.span.start..ul.span.end], "<ul>\n  <li>é</li>\n</ul>");
+
+    let li = ul.children.iter().find_map(|child| match child {
+        JsxChild::Element(li) => Some(li),
+        _ => None,
+    });
+    let li = li.expect("li child");
+    assert_eq!((li.span.line, li.span.column, li.span.end_line, li.span.end_column), (3, 3, 3, 13));
+    assert_eq!(&src[li.span.start..li.span.end], "<li>é</li>");
+}
+
+#[test]
+fn spans_give_utf16_positions_for_js_hosts() {
+    let src = "const e = '😀';\nconst b = <b>😀</b>;";
+    let module = parse(src);
+    let b = element(&module.jsx[0]);
+    let utf16: Vec<u16> = src.encode_utf16().collect();
+    assert_eq!(String::from_utf16(&utf16[b.span.utf16_start..b.span.utf16_end]).unwrap(), "<b>😀</b>");
+    assert_eq!((b.span.end_column, b.span.end_utf16_column), (19, 20));
+    assert_eq!(b.span.char_start, b.span.utf16_start - 1);
+}
+
+#[test]
+fn typescript_spans_skip_removed_types() {
+    let src = "const f = (x: number): JSX.Element => <b>{x as any}</b>;";
+    let opts = TranspileOptions { is_typescript: true, ..Default::default() };
+    let module = parse_module(src, &opts).unwrap();
+    let b = element(&module.jsx[0]);
+    assert_eq!(&src[b.span.start..b.span.end], "<b>{x as any}</b>");
+    assert_eq!(b.span.column, 39);
+}
+
+#[test]
+fn jsx_nested_in_expressions_is_parsed() {
+    let module = parse("const list = <ul>{items.map((i) => <li key={i.id}>{i.label}</li>)}</ul>;");
+    let ul = element(&module.jsx[0]);
+    let Some(JsxChild::ExpressionContainer(container)) = ul.children.first() else {
+        panic!("expected an expression child");
+    };
+    let expression = container.expression.as_ref().unwrap();
+    assert_eq!(expression.source, "items.map((i) => <li key={i.id}>{i.label}</li>)");
+    assert_eq!(expression.jsx.len(), 1);
+    assert_eq!(element(&expression.jsx[0]).name.to_string(), "li");
+}
+
+#[test]
+fn imports_are_collected() {
+    let src = concat!(
+        "import React, { useState as useLocal, useEffect } from 'react';\n",
+        "import * as utils from \"./utils\";\n",
+        "import './styles.css';\n",
+        "const lazy = import('./lazy');\n",
+        "import {\n  y as\n    z } from './multi';\n",
+    );
+    // TypeScript's `as` casts are removed first; renames must survive that
+    for is_typescript in [false, true] {
+        let opts = TranspileOptions { is_typescript, ..Default::default() };
+        let module = parse_module(src, &opts).unwrap_or_else(|e| panic!("{}", e.render()));
+        let sources: Vec<_> = module.imports.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, ["react", "./utils", "./styles.css", "./multi"]);
+        assert_eq!(
+            module.imports[0].specifiers,
+            [
+                ImportSpecifier::Default { local: "React".into() },
+                ImportSpecifier::Named { imported: "useState".into(), local: "useLocal".into() },
+                ImportSpecifier::Named { imported: "useEffect".into(), local: "useEffect".into() },
+            ]
+        );
+        assert_eq!(module.imports[1].specifiers, [ImportSpecifier::Namespace { local: "utils".into() }]);
+        assert!(module.imports[2].specifiers.is_empty());
+        assert_eq!(module.imports[3].specifiers, [ImportSpecifier::Named { imported: "y".into(), local: "z".into() }]);
+        assert_eq!(module.imports[1].span.line, 2);
+    }
+}
+
+#[test]
+fn type_only_imports_in_typescript() {
+    let opts = TranspileOptions { is_typescript: true, ..Default::default() };
+    let module = parse_module("import type { Props } from './types';\nimport type from 'type';", &opts).unwrap();
+    assert!(module.imports[0].type_only);
+    assert!(!module.imports[1].type_only);
+    assert_eq!(module.imports[1].specifiers, [ImportSpecifier::Default { local: "type".into() }]);
+}
+
+#[test]
+fn exports_are_collected() {
+    let src = concat!(
+        "export const a = <div title=\"x, y\" />, { b, c: [d] } = obj;\n",
+        "export let e = 1\n",
+        "export async function useThing() {}\n",
+        "export class Widget {}\n",
+        "export { a as alias, e };\n",
+        "export { helper } from './helpers';\n",
+        "export * as ns from './ns';\n",
+        "export default function App() { return <App.Root />; }\n",
+        "export { e as default };\n",
+    );
+    let declaration = |kind: &str, names: &[&str]| ExportDeclaration::Declaration {
+        kind: kind.into(),
+        names: names.iter().map(|n| n.to_string()).collect(),
+        span: Default::default(),
+    };
+    let without_span = |export: &ExportDeclaration| match export.clone() {
+        ExportDeclaration::Default { name, .. } => ExportDeclaration::Default { name, span: Default::default() },
+        ExportDeclaration::Declaration { kind, names, .. } => ExportDeclaration::Declaration { kind, names, span: Default::default() },
+        ExportDeclaration::Named { specifiers, source, .. } => ExportDeclaration::Named { specifiers, source, span: Default::default() },
+        ExportDeclaration::All { exported, source, .. } => ExportDeclaration::All { exported, source, span: Default::default() },
+    };
+    let specifier = |local: &str, exported: &str| ExportSpecifier { local: local.into(), exported: exported.into() };
+
+    // TypeScript's `as` casts are removed first; renames must survive that
+    for is_typescript in [false, true] {
+        let opts = TranspileOptions { is_typescript, ..Default::default() };
+        let module = parse_module(src, &opts).unwrap_or_else(|e| panic!("{}", e.render()));
+        let exports: Vec<_> = module.exports.iter().map(without_span).collect();
+        assert_eq!(
+            exports,
+            [
+                declaration("const", &["a", "b", "d"]),
+                declaration("let", &["e"]),
+                declaration("function", &["useThing"]),
+                declaration("class", &["Widget"]),
+                ExportDeclaration::Named { specifiers: vec![specifier("a", "alias"), specifier("e", "e")], source: None, span: Default::default() },
+                ExportDeclaration::Named { specifiers: vec![specifier("helper", "helper")], source: Some("./helpers".into()), span: Default::default() },
+                ExportDeclaration::All { exported: Some("ns".into()), source: "./ns".into(), span: Default::default() },
+                ExportDeclaration::Default { name: Some("App".into()), span: Default::default() },
+                ExportDeclaration::Named { specifiers: vec![specifier("e", "default")], source: None, span: Default::default() },
+            ]
+        );
+        assert_eq!(module.exports[7].span().line, 8);
+        // JSX in exported declarations is still part of the tree
+        assert_eq!(module.jsx.len(), 2);
+    }
+}
+
+#[test]
+fn parse_errors_carry_codes_and_spans() {
+    let err = parse_module("const a = <div>\n  <span></div>;", &TranspileOptions::default()).unwrap_err();
+    assert_eq!(err.code, ErrorCode::MismatchedClosingTag);
+    assert_eq!(err.span.as_deref().map(|s| s.line), Some(2));
+}
+
+#[cfg(feature = "ast-json")]
+#[test]
+fn module_serializes_to_json() {
+    let json = relay_hook_transpiler::parse_module_json(
+        "import { useState } from 'react';\nexport default () => <p class=\"x\">{count}</p>;",
+        &TranspileOptions::default(),
+    )
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["imports"][0]["specifiers"][0]["type"], "Named");
+    assert_eq!(value["exports"][0]["type"], "Default");
+    let p = &value["jsx"][0];
+    assert_eq!(p["type"], "Element");
+    assert_eq!(p["name"]["name"], "p");
+    assert_eq!(p["attributes"][0]["value"]["value"], "x");
+    assert_eq!(p["children"][0]["expression"]["source"], "count");
+    assert_eq!(p["span"]["line"], 2);
+    assert!(p["children"][0]["expression"].get("holes").is_none());
+}
@@ -0,0 +1,8 @@
+//! Helpers shared by the integration tests
+
+use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions};
+
+/// Transpile `src`, failing the test with the rendered diagnostic if it does not
+pub fn transpile(src: &str, opts: &TranspileOptions) -> String {
+    transpile_jsx_with_options(src, opts).unwrap_or_else(|e| panic!("{}", e.render()))
+}
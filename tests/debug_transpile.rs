@@ -182,7 +182,8 @@ fn test_android_target_with_debug_trace() {
         }
         Err(e) => {
             // Expected on web-only builds without native-swc feature
-            assert!(e.contains("SWC") || e.contains("transformation"));
+            let msg = e.to_string();
+            assert!(msg.contains("SWC") || msg.contains("transformation"));
         }
     }
 }
@@ -0,0 +1,132 @@
+//! Destructuring, default and rest parameter regression tests for the custom Android path
+//! Validates that hook components lower to ES5 JavaScriptCore can run
+
+#[cfg(not(feature = "native-swc"))]
+#[test]
+fn use_state_pairs_are_destructured() {
+    use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions, TranspileTarget};
+
+    let source = r#"
+import { useState, useEffect } from "react";
+
+export default function Counter() {
+    const [count, setCount] = useState(0);
+    const [doubled, setDoubled] = useState(0);
+
+    useEffect(() => {
+        setDoubled(count * 2);
+    }, [count]);
+
+    return <button onClick={() => setCount(count + 1)}>{count} / {doubled}</button>;
+}
+"#;
+
+    let opts = TranspileOptions {
+        filename: Some("counter.jsx".to_string()),
+        target: TranspileTarget::Android,
+        ..Default::default()
+    };
+
+    let result = transpile_jsx_with_options(source, &opts).expect("transpilation failed");
+
+    assert!(result.contains("var _ref = useState(0), count = _ref[0], setCount = _ref[1];"), "{}", result);
+    assert!(result.contains("var _ref2 = useState(0), doubled = _ref2[0], setDoubled = _ref2[1];"), "{}", result);
+    assert!(!result.contains("const") && !result.contains("=>"), "{}", result);
+}
+
+#[cfg(not(feature = "native-swc"))]
+#[test]
+fn component_props_with_defaults_and_rest() {
+    use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions, TranspileTarget};
+
+    let source = r#"
+export default function TodoList({ title, items = [] }, ...rest) {
+    const { onSelect, theme: { color } = {} } = rest[0] || {};
+    return <ul title={title}>{items.map(({ id, label }, i = 0) => <li key={id} onClick={() => onSelect(id, i)}>{label}</li>)}</ul>;
+}
+"#;
+
+    let opts = TranspileOptions {
+        filename: Some("todo.jsx".to_string()),
+        target: TranspileTarget::Android,
+        ..Default::default()
+    };
+
+    let result = transpile_jsx_with_options(source, &opts).expect("transpilation failed");
+
+    assert!(
+        result.contains(concat!(
+            "function TodoList(_ref) { var title = _ref.title, _ref2 = _ref.items, items = _ref2 === void 0 ? [] : _ref2; ",
+            "var rest = Array.prototype.slice.call(arguments, 1);"
+        )),
+        "{}",
+        result
+    );
+    assert!(
+        result.contains("var _ref3 = rest[0] || {}, onSelect = _ref3.onSelect, _ref4 = _ref3.theme, _ref5 = _ref4 === void 0 ? {} : _ref4, color = _ref5.color;"),
+        "{}",
+        result
+    );
+    assert!(result.contains("function (_ref6, i) { var id = _ref6.id, label = _ref6.label; if (i === void 0) i = 0; return "), "{}", result);
+}
+
+#[cfg(not(feature = "native-swc"))]
+#[test]
+fn effect_loops_and_swaps_are_destructured() {
+    use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions, TranspileTarget};
+
+    let source = r#"
+import { useEffect } from "react";
+
+export default function Pairs({ entries }) {
+    useEffect(() => {
+        let first, second;
+        for (const [key, { value }] of entries) {
+            [first, second] = [key, value];
+        }
+    }, [entries]);
+    return <div />;
+}
+"#;
+
+    let opts = TranspileOptions {
+        filename: Some("pairs.jsx".to_string()),
+        target: TranspileTarget::Android,
+        ..Default::default()
+    };
+
+    let result = transpile_jsx_with_options(source, &opts).expect("transpilation failed");
+
+    assert!(result.contains("function Pairs(_ref) { var _ref4; var entries = _ref.entries;"), "{}", result);
+    assert!(result.contains("for (var _ref2 of entries) { var key = _ref2[0], _ref3 = _ref2[1], value = _ref3.value;"), "{}", result);
+    assert!(result.contains("(_ref4 = [key, value], first = _ref4[0], second = _ref4[1], _ref4);"), "{}", result);
+}
+
+#[cfg(not(feature = "native-swc"))]
+#[test]
+fn temporary_naming_scales_linearly() {
+    use std::time::{Duration, Instant};
+
+    use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions, TranspileTarget};
+
+    let opts = TranspileOptions { target: TranspileTarget::Android, ..Default::default() };
+    // Best of three runs over `count` functions, each needing two temporaries
+    let time = |count: usize| -> Duration {
+        let source: String = (0..count)
+            .map(|i| format!("function f{i}() {{ const [a, b] = useState({i}); const {{ c, d }} = load(); return a + c; }}\n"))
+            .collect();
+        (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                transpile_jsx_with_options(&source, &opts).expect("transpilation failed");
+                start.elapsed()
+            })
+            .min()
+            .unwrap()
+    };
+
+    let (small, large) = (time(500), time(4000));
+    // Linear is x8; naming each temporary by rescanning the earlier ones made it x60 and more
+    let ratio = large.as_secs_f64() / small.as_secs_f64();
+    assert!(ratio < 24.0, "8x the input took {ratio:.1}x the time ({small:?} -> {large:?})");
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::transpile;
+use relay_hook_transpiler::{JsxBackend, TranspileOptions};
+
+/// `out` after the helper declarations, which end with `__hook_dom_props`
+fn without_helpers(out: &str) -> &str {
+    out.split_once("__hook_dom_prop(el, name, props[name]); } ").map_or(out, |(_, code)| code)
+}
+
+#[test]
+fn literal_markup_is_built_in_place() {
+    let opts = TranspileOptions { jsx_backend: JsxBackend::Dom, ..Default::default() };
+    let out = transpile(r#"const a = <p className="note" tabIndex={0} hidden data-x={false} key="k">Hi &amp; bye</p>;"#, &opts);
+    assert_eq!(
+        out,
+        concat!(
+            r#"const a = (function () { var __el = document.createElement("p"); "#,
+            r#"__el.setAttribute("class", "note"); __el.setAttribute("tabindex", "0"); __el.setAttribute("hidden", ""); "#,
+            r#"__el.setAttribute("data-x", "false"); __el.appendChild(document.createTextNode("Hi & bye")); return __el; })();"#,
+        )
+    );
+}
+
+#[test]
+fn dynamic_values_are_evaluated_outside_and_patched_in() {
+    let opts = TranspileOptions { jsx_backend: JsxBackend::Dom, ..Default::default() };
+    let out = transpile(
+        "const b = <ul onClick={this.pick} {...rest}>{items.map(i => <li>{i}</li>)}<Item name=\"x\">y</Item><></></ul>;",
+        &opts,
+    );
+    assert_eq!(
+        without_helpers(&out),
+        concat!(
+            r#"const b = (function (__v0, __v1, __v2, __v3, __v4) { var __el = document.createElement("ul"); "#,
+            r#"__hook_dom_prop(__el, "onClick", __v0); __hook_dom_props(__el, __v1); __hook_dom_append(__el, __v2); "#,
+            r#"__hook_dom_append(__el, __v3); __el.appendChild(__v4); return __el; })(this.pick, rest, "#,
+            r#"items.map(i => (function (__v0) { var __el = document.createElement("li"); __hook_dom_append(__el, __v0); return __el; })(i)), "#,
+            r#"Item({ name: "x", children: "y" }), "#,
+            r#"(function () { var __el = document.createDocumentFragment(); return __el; })());"#,
+        )
+    );
+}
+
+#[test]
+fn helpers_are_declared_on_the_first_line_only_when_used() {
+    let opts = TranspileOptions { jsx_backend: JsxBackend::Dom, ..Default::default() };
+    let out = transpile("import x from 'x';\nconst c = <b>{x}</b>;\n", &opts);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("function __hook_dom_append(parent, child) {"), "{out}");
+    assert!(lines[0].ends_with("import x from 'x';"), "{out}");
+    assert!(!transpile("const d = <b>static</b>;", &opts).contains("function __hook_dom_"));
+}
+
+#[test]
+fn svg_children_use_the_svg_namespace() {
+    let opts = TranspileOptions { jsx_backend: JsxBackend::Dom, ..Default::default() };
+    let out = transpile(r#"const e = <svg viewBox="0 0 1 1"><circle r="1" /><foreignObject><div /></foreignObject></svg>;"#, &opts);
+    assert_eq!(out.matches(r#"document.createElementNS("http://www.w3.org/2000/svg", "#).count(), 3, "{out}");
+    assert!(out.contains(r#"document.createElement("div")"#), "{out}");
+}
+
+#[test]
+fn hoisted_static_nodes_are_cloned() {
+    let opts = TranspileOptions { hoist_static_jsx: true, jsx_backend: JsxBackend::Dom, ..Default::default() };
+    let out = transpile("const f = () => <div>{n}<hr /></div>;", &opts);
+    assert!(without_helpers(&out).starts_with(r#"const __hook_static_jsx_1 = (function () { var __el = document.createElement("hr"); return __el; })();"#), "{out}");
+    assert!(out.contains("__el.appendChild(__v1); return __el; })(n, __hook_static_jsx_1.cloneNode(true))"), "{out}");
+}
+
+#[test]
+fn form_values_are_properties_and_selects_are_set_after_their_options() {
+    let opts = TranspileOptions { jsx_backend: JsxBackend::Dom, ..Default::default() };
+    let out = transpile(r#"const g = <textarea value="a" rows={2} />;"#, &opts);
+    assert_eq!(
+        out,
+        r#"const g = (function () { var __el = document.createElement("textarea"); __el.value = "a"; __el.setAttribute("rows", "2"); return __el; })();"#
+    );
+
+    let out = transpile(r#"const h = <select defaultValue={choice} name="c"><option value="a">A</option></select>;"#, &opts);
+    assert_eq!(
+        without_helpers(&out),
+        concat!(
+            r#"const h = (function (__v0, __v1) { var __el = document.createElement("select"); __el.appendChild(__v1); "#,
+            r#"__hook_dom_prop(__el, "value", __v0); __el.setAttribute("name", "c"); return __el; })(choice, "#,
+            r#"(function () { var __el = document.createElement("option"); __el.setAttribute("value", "a"); "#,
+            r#"__el.appendChild(document.createTextNode("A")); return __el; })());"#,
+        )
+    );
+}
+
+#[test]
+fn svg_props_use_react_dom_attribute_names() {
+    let opts = TranspileOptions { jsx_backend: JsxBackend::Dom, ..Default::default() };
+    let out = transpile(r#"const i = <svg><path strokeWidth={2} xlinkHref="/a" strokeLinecap={cap} viewBox="0 0 1 1" /></svg>;"#, &opts);
+    assert!(out.contains(r#"__el.setAttribute("stroke-width", "2"); __el.setAttribute("xlink:href", "/a"); "#), "{out}");
+    assert!(out.contains(r#"__hook_dom_prop(__el, "stroke-linecap", __v0); __el.setAttribute("viewBox", "0 0 1 1");"#), "{out}");
+}
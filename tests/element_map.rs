@@ -0,0 +1,84 @@
+use relay_hook_transpiler::{
+    transpile_jsx_recovering, transpile_jsx_with_options, transpile_jsx_with_source_map, ElementMap, ErrorCode,
+    TranspileOptions, TranspileTarget,
+};
+
+const NATIVE_TABLE: &str = r#"{
+    "elements": {
+        "div": { "name": "View" },
+        "img": { "name": "Image", "props": { "src": "source" } },
+        "marquee": { "supported": false }
+    },
+    "props": { "onClick": "onPress" },
+    "events": ["onClick"]
+}"#;
+
+fn native() -> TranspileOptions {
+    TranspileOptions { element_map: Some(ElementMap::from_json(NATIVE_TABLE).unwrap()), ..Default::default() }
+}
+
+#[test]
+fn intrinsic_tags_and_props_are_renamed() {
+    let src = r#"<div onClick={go}><img src={uri} /><Card onClick={go} /><span>x</span></div>"#;
+    let out = transpile_jsx_with_options(src, &native()).unwrap();
+    assert_eq!(
+        out,
+        r#"__hook_jsx_runtime.jsxs("View", { onPress: go, children: [__hook_jsx_runtime.jsx("Image", { source: uri }), __hook_jsx_runtime.jsx(Card, { onClick: go }), __hook_jsx_runtime.jsx("span", { children: "x" })] })"#
+    );
+}
+
+#[test]
+fn unsupported_tags_and_events_warn_at_their_names() {
+    let src = r#"<div onMouseEnter={hover}><marquee>hi</marquee></div>"#;
+    let result = transpile_jsx_recovering(src, &native());
+    let found: Vec<(ErrorCode, &str)> = result
+        .diagnostics
+        .iter()
+        .map(|d| {
+            let span = d.span.as_deref().unwrap();
+            (d.code, &src[span.start..span.end])
+        })
+        .collect();
+    assert_eq!(found, vec![(ErrorCode::UnsupportedProp, "onMouseEnter"), (ErrorCode::UnsupportedElement, "marquee")]);
+    // Warnings never stop the transpile and come back with its output
+    let output = transpile_jsx_with_source_map(src, &native()).unwrap();
+    let codes: Vec<ErrorCode> = output.warnings.iter().map(|w| w.code).collect();
+    assert_eq!(codes, vec![ErrorCode::UnsupportedProp, ErrorCode::UnsupportedElement]);
+    assert!(output.warnings[1].code_frame.is_some());
+}
+
+#[test]
+fn android_uses_its_built_in_table() {
+    let src = "const a = () => <div onKeyDown={k}><video src={v} /></div>;";
+    let android = TranspileOptions { target: TranspileTarget::Android, ..Default::default() };
+    let codes: Vec<ErrorCode> = transpile_jsx_recovering(src, &android).diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(codes, vec![ErrorCode::UnsupportedProp, ErrorCode::UnsupportedElement]);
+
+    // The web renders React DOM and has no table; an empty table turns Android's off
+    assert!(transpile_jsx_recovering(src, &TranspileOptions::default()).diagnostics.is_empty());
+    let unmapped = TranspileOptions { element_map: Some(ElementMap::default()), ..android };
+    assert!(transpile_jsx_recovering(src, &unmapped).diagnostics.is_empty());
+}
+
+#[test]
+fn invalid_tables_are_rejected() {
+    let err = ElementMap::from_json(r#"{ "elements": { "div": { "name": 1 } } }"#).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidElementMap);
+}
+
+#[cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
+#[test]
+fn swc_applies_the_same_table() {
+    let opts = TranspileOptions { target: TranspileTarget::Android, compat_for_jsc: false, ..native() };
+    let out = transpile_jsx_with_options(r#"<div onClick={go}><img src={uri} /><Image /></div>"#, &opts).unwrap();
+    assert!(out.contains(r#"_jsxs("View", {"#), "{out}");
+    assert!(out.contains("onPress: go"), "{out}");
+    assert!(out.contains(r#"_jsx("Image", {"#) && out.contains("source: uri"), "{out}");
+    // A component that happens to share the target's name is still a reference
+    assert!(out.contains("_jsx(Image, {})"), "{out}");
+    assert!(!out.contains("hook-mapped-element-"), "{out}");
+
+    let output = transpile_jsx_with_source_map("<marquee onMouseEnter={hover} />", &opts).unwrap();
+    let codes: Vec<ErrorCode> = output.warnings.iter().map(|w| w.code).collect();
+    assert_eq!(codes, vec![ErrorCode::UnsupportedElement, ErrorCode::UnsupportedProp]);
+}
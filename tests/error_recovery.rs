@@ -0,0 +1,165 @@
+use relay_hook_transpiler::{
+    transpile_jsx_recovering, transpile_jsx_with_options, ErrorCode, TranspileOptions,
+};
+
+#[test]
+fn reports_every_mismatched_tag_in_one_pass() {
+    let src = r#"export default function App() {
+  return (
+    <div>
+      <h1>Title</h2>
+      <p>Body</span>
+      <b>bold</i>
+    </div>
+  );
+}"#;
+    let opts = TranspileOptions {
+        filename: Some("app.jsx".to_string()),
+        ..Default::default()
+    };
+
+    // Strict mode still stops at the first problem
+    let first = transpile_jsx_with_options(src, &opts).unwrap_err();
+    assert_eq!(first.span.as_deref().unwrap().line, 4);
+
+    let result = transpile_jsx_recovering(src, &opts);
+    let lines: Vec<usize> = result.diagnostics.iter().map(|d| d.span.as_deref().unwrap().line).collect();
+    assert_eq!(lines, vec![4, 5, 6], "diagnostics: {:#?}", result.diagnostics);
+    assert!(result
+        .diagnostics
+        .iter()
+        .all(|d| d.code == ErrorCode::MismatchedClosingTag && d.code_frame.is_some()));
+
+    // Best-effort output still contains every element
+    assert!(result.code.contains("\"h1\""));
+    assert!(result.code.contains("\"p\""));
+    assert!(result.code.contains("\"b\""));
+    assert!(result.code.contains("export default function App()"));
+}
+
+#[test]
+fn ancestor_closing_tag_closes_unclosed_children() {
+    let src = "const x = <ul><li>one <li>two</ul>;\nconst y = <p>ok</p>;";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+
+    assert_eq!(result.diagnostics.len(), 2, "diagnostics: {:#?}", result.diagnostics);
+    assert!(result.code.contains("__hook_jsx_runtime.jsx(\"p\""));
+    assert!(result.code.contains("const y ="));
+}
+
+#[test]
+fn misspelled_closing_tag_closes_current_element() {
+    let src = "const x = <div><p>a</pp><p>b</p></div>;";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.code.matches("__hook_jsx_runtime.jsx(\"p\"").count(), 2, "code: {}", result.code);
+    assert!(result.code.starts_with("const x = __hook_jsx_runtime.jsxs(\"div\""));
+}
+
+#[test]
+fn collects_errors_from_nested_expressions_and_typescript_checks() {
+    let src = "const a: string = 'x';\nconst list = <ul>{items.map(i => <li>{i}</b>)}</ul>;\nconst b: number = 1;";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+
+    let codes: Vec<ErrorCode> = result.diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(
+        codes,
+        vec![
+            ErrorCode::TypeScriptAnnotation,
+            ErrorCode::TypeScriptAnnotation,
+            ErrorCode::MismatchedClosingTag,
+        ]
+    );
+    let nested = &result.diagnostics[2];
+    assert_eq!(nested.span.as_deref().unwrap().line, 2);
+    assert_eq!(&src[nested.span.as_deref().unwrap().start..nested.span.as_deref().unwrap().end], "</b>");
+}
+
+#[test]
+fn unterminated_element_resyncs_at_statement_boundary() {
+    let src = "const a = <div className=\"x\";\nconst b = <span>fine</span>;";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+
+    assert!(!result.diagnostics.is_empty());
+    assert!(result.code.contains("const a = null;"), "code: {}", result.code);
+    assert!(result.code.contains("__hook_jsx_runtime.jsx(\"span\""));
+}
+
+#[test]
+fn missing_brace_before_closing_tag_is_reported_at_the_container() {
+    let src = "function A() {\n  return <ul>{items.map(i => <li>{i}</li>)</ul>;\n}\nfunction B() {\n  return <p>b</p>;\n}\n";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+
+    assert_eq!(result.diagnostics.len(), 1, "diagnostics: {:#?}", result.diagnostics);
+    let error = &result.diagnostics[0];
+    assert_eq!(error.code, ErrorCode::ExpectedChar);
+    let span = error.span.as_deref().unwrap();
+    assert_eq!((span.line, &src[span.start..span.end]), (2, "{"));
+
+    // The container ends at `</ul>`, and the code after it is untouched
+    assert!(result.code.contains(r#"jsx("ul", { children: items.map(i => __hook_jsx_runtime.jsx("li", { children: i })) });"#), "code: {}", result.code);
+    assert!(result.code.contains("function B() {\n  return __hook_jsx_runtime.jsx(\"p\", { children: \"b\" });\n}"), "code: {}", result.code);
+}
+
+#[test]
+fn unclosed_element_does_not_swallow_the_following_functions() {
+    let src = "function A() {\n  return (\n    <div>\n      <p>x</p>\n  );\n}\nfunction B() {\n  return <p>b</p>;\n}\n";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+
+    assert_eq!(result.diagnostics.len(), 1, "diagnostics: {:#?}", result.diagnostics);
+    let error = &result.diagnostics[0];
+    assert_eq!(error.code, ErrorCode::UnexpectedEof);
+    let span = error.span.as_deref().unwrap();
+    assert_eq!((span.line, &src[span.start..span.end]), (3, "<div"));
+
+    assert!(result.code.contains(r#"jsx("div", { children: __hook_jsx_runtime.jsx("p", { children: "x" }) })"#), "code: {}", result.code);
+    assert!(result.code.contains("  );\n}\nfunction B() {\n  return __hook_jsx_runtime.jsx(\"p\", { children: \"b\" });\n}"), "code: {}", result.code);
+
+    // A statement-ending `;` also ends the element when no `}` follows
+    let src = "const a = <div><b>x</b>;\nconst b = <p>ok</p>;\n";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+    assert_eq!(result.diagnostics.len(), 1, "diagnostics: {:#?}", result.diagnostics);
+    assert!(result.code.ends_with(";\nconst b = __hook_jsx_runtime.jsx(\"p\", { children: \"ok\" });\n"), "code: {}", result.code);
+
+    // Strict mode stops at the same opening tag
+    let error = transpile_jsx_with_options(src, &TranspileOptions::default()).unwrap_err();
+    assert_eq!(error.span.as_deref().map(|span| &src[span.start..span.end]), Some("<div"));
+}
+
+#[test]
+fn statements_after_a_broken_element_survive() {
+    // An unclosed element inside an attribute reads to the end of the input
+    let src = "const x = <a b={<c>}>d</a>;\nconst y = <e/>;\nfoo();";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+    assert_eq!(result.code, "const x = null;\nconst y = __hook_jsx_runtime.jsx(\"e\", {});\nfoo();");
+    let found: Vec<(ErrorCode, &str)> = result
+        .diagnostics
+        .iter()
+        .map(|d| {
+            let span = d.span.as_deref().unwrap();
+            (d.code, &src[span.start..span.end])
+        })
+        .collect();
+    assert_eq!(found, vec![(ErrorCode::MismatchedClosingTag, "</a>"), (ErrorCode::ExpectedChar, "{")]);
+
+    // So does an unterminated attribute string
+    let src = "const a = <p title=\"oops>hi</p>;\nconst b = 2;";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+    assert_eq!(result.code, "const a = null;\nconst b = 2;");
+    assert_eq!(result.diagnostics.len(), 1, "diagnostics: {:#?}", result.diagnostics);
+    assert_eq!(result.diagnostics[0].span.as_deref().map(|span| span.start), Some(19));
+
+    // Inside parentheses the element ends at the `)` closing them
+    let src = "render(\n  <div title=\"x>\n    hi\n  </div>\n);\nnext();";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+    assert_eq!(result.code, "render(\n  null);\nnext();");
+}
+
+#[test]
+fn clean_source_has_no_diagnostics() {
+    let src = "const el = <div className=\"ok\"><span>hi</span></div>;";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+    assert!(result.diagnostics.is_empty());
+    assert_eq!(result.code, transpile_jsx_with_options(src, &TranspileOptions::default()).unwrap());
+}
@@ -0,0 +1,119 @@
+use relay_hook_transpiler::{
+    transpile_jsx_recovering, transpile_jsx_simple, transpile_jsx_with_options, ErrorCode,
+    ErrorKind, Severity, TranspileOptions,
+};
+
+#[test]
+fn mismatched_closing_tag_reports_span_and_frame() {
+    let src = "export default function App() {\n  return <div>\n    <span>hi</p>\n  </div>;\n}\n";
+    let opts = TranspileOptions {
+        filename: Some("app.jsx".to_string()),
+        ..Default::default()
+    };
+    let err = transpile_jsx_with_options(src, &opts).unwrap_err();
+
+    assert_eq!(err.code, ErrorCode::MismatchedClosingTag);
+    assert_eq!(err.kind, ErrorKind::Syntax);
+    assert_eq!(err.filename.as_deref(), Some("app.jsx"));
+
+    let span = err.span.as_deref().expect("span should be set");
+    assert_eq!(span.line, 3);
+    assert_eq!(span.column, 13);
+    assert_eq!(&src[span.start..span.end], "</p>");
+
+    let frame = err.code_frame.as_deref().unwrap();
+    assert!(frame.contains("> 3 |     <span>hi</p>"), "frame:\n{frame}");
+    assert!(err.render().contains("--> app.jsx:3:13"));
+}
+
+#[test]
+fn spans_after_emoji_and_cjk_text_count_every_unit() {
+    let src = "const t = '你好 👋';\nconst x = <p>👋 {t}</i>;";
+    let err = transpile_jsx_simple(src).unwrap_err();
+
+    let span = err.span.as_deref().unwrap();
+    assert_eq!(&src[span.start..span.end], "</i>");
+    let chars: Vec<char> = src.chars().collect();
+    assert_eq!(chars[span.char_start..span.char_end].iter().collect::<String>(), "</i>");
+    // JavaScript and Java index strings by UTF-16 code unit
+    let utf16: Vec<u16> = src.encode_utf16().collect();
+    assert_eq!(String::from_utf16(&utf16[span.utf16_start..span.utf16_end]).unwrap(), "</i>");
+    assert_eq!((span.line, span.column, span.utf16_column), (2, 19, 20));
+    assert_eq!(span.start_position().utf16_offset, span.utf16_start);
+}
+
+#[test]
+fn nested_expression_errors_point_into_outer_source() {
+    // The inner element lives inside a {expr} that is transpiled recursively
+    let src = "const x = <ul>\n  {items.map(i => <li>{i}</span>)}\n</ul>;";
+    let err = transpile_jsx_simple(src).unwrap_err();
+
+    assert_eq!(err.code, ErrorCode::MismatchedClosingTag);
+    let span = err.span.as_deref().unwrap();
+    assert_eq!(span.line, 2);
+    assert_eq!(&src[span.start..span.end], "</span>");
+}
+
+#[test]
+fn typescript_in_js_mode_has_typescript_kind() {
+    let src = "const a = 1;\nconst x: number = 5;";
+    let err = transpile_jsx_simple(src).unwrap_err();
+
+    assert_eq!(err.code, ErrorCode::TypeScriptAnnotation);
+    assert_eq!(err.kind, ErrorKind::TypeScript);
+    assert_eq!(err.span.as_deref().unwrap().line, 2);
+    assert!(err.to_string().contains("at line 2"));
+}
+
+#[test]
+fn typescript_errors_keep_line_numbers_after_stripping() {
+    let src = "interface Props {\n  title: string;\n  count: number;\n}\nconst el = <div></span>;";
+    let opts = TranspileOptions {
+        is_typescript: true,
+        ..Default::default()
+    };
+    let err = transpile_jsx_with_options(src, &opts).unwrap_err();
+
+    assert_eq!(err.code, ErrorCode::MismatchedClosingTag);
+    assert_eq!(err.span.as_deref().unwrap().line, 5);
+}
+
+#[test]
+fn unexpected_eof_is_reported() {
+    let err = transpile_jsx_simple("<div><span>open").unwrap_err();
+    assert_eq!(err.code, ErrorCode::UnexpectedEof);
+    assert_eq!(err.code.as_str(), "HT1004");
+}
+
+#[test]
+fn unknown_entities_are_warnings() {
+    let src = "const a = <p>caf&eacute;</p>;\nconst b = <p title=\"&nope;\">x</p>;";
+    assert!(transpile_jsx_simple(src).unwrap().contains("caf\u{e9}"));
+
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+    assert_eq!(result.diagnostics.len(), 1);
+    let warning = &result.diagnostics[0];
+    assert_eq!(warning.code, ErrorCode::UnknownEntity);
+    assert_eq!(warning.severity, Severity::Warning);
+    assert_eq!(warning.span.as_deref().unwrap().line, 2);
+    assert!(warning.render().starts_with("warning[HT1007]"));
+}
+
+#[cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
+#[test]
+fn swc_parse_errors_carry_position() {
+    use relay_hook_transpiler::TranspileTarget;
+
+    let src = "export default function App() {\n  return <div>;\n}\n";
+    let opts = TranspileOptions {
+        target: TranspileTarget::Android,
+        filename: Some("broken.jsx".to_string()),
+        ..Default::default()
+    };
+    let err = transpile_jsx_with_options(src, &opts).unwrap_err();
+
+    assert_eq!(err.kind, ErrorKind::Swc);
+    assert_eq!(err.code, ErrorCode::SwcParse);
+    assert!(err.span.is_some(), "SWC errors should keep their span");
+    assert!(err.code_frame.is_some());
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::transpile;
+use relay_hook_transpiler::{
+    transpile_jsx_recovering, transpile_jsx_with_options, ErrorCode, TranspileOptions,
+};
+
+#[test]
+fn pasted_markup_becomes_react_props() {
+    let src = r#"<label class="field" for="city" style="color:red; font-size: 2em">City<input id="city" tabindex="2" onclick="pick(event)"><br></label>"#;
+    let opts = TranspileOptions { html_compat: true, ..Default::default() };
+    assert_eq!(
+        transpile(src, &opts),
+        r#"__hook_jsx_runtime.jsxs("label", { className: "field", htmlFor: "city", style: { color: "red", fontSize: "2em" }, children: ["City", __hook_jsx_runtime.jsx("input", { id: "city", tabIndex: "2", onClick: function (event) { pick(event) } }), __hook_jsx_runtime.jsx("br", {})] })"#
+    );
+}
+
+#[test]
+fn every_fix_is_a_warning() {
+    let src = r#"<p class="a" style="margin:0">x<br><img src="a.png"></img></p>"#;
+    let opts = TranspileOptions { html_compat: true, ..Default::default() };
+    let result = transpile_jsx_recovering(src, &opts);
+    let fixes: Vec<(&str, &str)> = result
+        .diagnostics
+        .iter()
+        .map(|d| {
+            assert_eq!(d.code, ErrorCode::HtmlCompat);
+            let span = d.span.as_deref().unwrap();
+            (&src[span.start..span.end], d.message.as_str())
+        })
+        .collect();
+    assert_eq!(
+        fixes,
+        vec![
+            ("class", "Renamed HTML attribute 'class' to 'className'"),
+            ("\"margin:0\"", "Converted the style string to a style object"),
+            ("<br>", "Closed HTML void element <br>; JSX needs <br />"),
+        ]
+    );
+    // `<img></img>` is already closed, so it needs no fix
+    assert!(result.code.contains(r#"__hook_jsx_runtime.jsx("img", { src: "a.png" })"#), "{}", result.code);
+}
+
+#[test]
+fn off_by_default() {
+    let err = transpile_jsx_with_options("<p>a<br>b</p>", &TranspileOptions::default()).unwrap_err();
+    assert_eq!(err.code, ErrorCode::MismatchedClosingTag);
+    assert_eq!(
+        transpile(r#"<p class="a" />"#, &TranspileOptions::default()),
+        r#"__hook_jsx_runtime.jsx("p", { "class": "a" })"#
+    );
+}
+
+#[test]
+fn expression_values_and_react_props_are_kept() {
+    let src = r#"<button onclick={save} onClick="go()" style={styles.button} className="b">Save</button>"#;
+    let opts = TranspileOptions { html_compat: true, ..Default::default() };
+    let result = transpile_jsx_recovering(src, &opts);
+    assert_eq!(
+        result.code,
+        r#"__hook_jsx_runtime.jsx("button", { onClick: save, ...{ onClick: function (event) { go() } }, style: styles.button, className: "b", children: "Save" })"#
+    );
+    // The rename, the wrapped string handler and the duplicate `onClick` the rename created
+    let codes: Vec<ErrorCode> = result.diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(codes, vec![ErrorCode::HtmlCompat, ErrorCode::HtmlCompat, ErrorCode::DuplicateProp]);
+}
+
+#[test]
+fn typescript_files_step_over_void_elements() {
+    let opts = TranspileOptions { is_typescript: true, html_compat: true, ..Default::default() };
+    let src = "const a = (n: number) => <div>line<br>{n}<hr></div>;\nconst b: string = 'x';\n";
+    assert_eq!(
+        transpile(src, &opts),
+        "const a = (n ) => __hook_jsx_runtime.jsxs(\"div\", { children: [\"line\", __hook_jsx_runtime.jsx(\"br\", {}), n, __hook_jsx_runtime.jsx(\"hr\", {})] });\nconst b = 'x';\n"
+    );
+}
@@ -0,0 +1,107 @@
+use relay_hook_transpiler::html_prerender::{HtmlHoleKind, PrerenderedHtml};
+use relay_hook_transpiler::{prerender_html, ErrorCode, TranspileOptions};
+
+fn render(src: &str) -> PrerenderedHtml {
+    prerender_html(src, &TranspileOptions::default()).unwrap_or_else(|e| panic!("{}", e.render()))
+}
+
+#[test]
+fn constant_jsx_renders_completely() {
+    let html = render(
+        r#"export default () => (
+  <section className="hero" tabIndex={-1} data-open={false} hidden={false} disabled key="k">
+    <h1 title='Tom &amp; "Jerry"'>Hi &lt;there&gt;</h1>
+    <label htmlFor="q">Count: {3}{" items"}{null}</label><br />
+    <img src="a.png" alt="" />
+  </section>
+);"#,
+    );
+    let root = &html.roots[0];
+    assert!(root.is_static);
+    assert!(root.holes.is_empty());
+    assert_eq!(
+        root.html,
+        concat!(
+            r#"<section class="hero" tabindex="-1" data-open="false" disabled="">"#,
+            r#"<h1 title="Tom &amp; &quot;Jerry&quot;">Hi &lt;there&gt;</h1>"#,
+            r#"<label for="q">Count: <!-- -->3<!-- --> items</label><br/>"#,
+            r#"<img src="a.png" alt=""/>"#,
+            "</section>",
+        )
+    );
+}
+
+#[test]
+fn dynamic_parts_are_marked_for_hydration() {
+    let src = r#"const App = ({ user, items }) => <>
+  <p id="greeting" className={cls} onClick={greet} {...rest}>Hello {user.name}!</p>
+  <List items={items} />
+  {items.length > 0 && <hr />}
+</>;"#;
+    let html = render(src);
+    let root = &html.roots[0];
+    assert!(!root.is_static);
+    assert_eq!(
+        root.html,
+        r#"<p id="greeting" data-hook-holes="0 1 2">Hello <!--hook:3-->!</p><!--hook:4--><!--hook:5-->"#
+    );
+    let holes: Vec<(&HtmlHoleKind, &str)> = root.holes.iter().map(|h| (&h.kind, h.source.as_str())).collect();
+    assert_eq!(
+        holes,
+        [
+            (&HtmlHoleKind::Prop { name: "className".into() }, "cls"),
+            (&HtmlHoleKind::Prop { name: "onClick".into() }, "greet"),
+            (&HtmlHoleKind::Spread, "rest"),
+            (&HtmlHoleKind::Child, "user.name"),
+            (&HtmlHoleKind::Child, "<List items={items} />"),
+            (&HtmlHoleKind::Child, "items.length > 0 && <hr />"),
+        ]
+    );
+    let list = &root.holes[4].span;
+    assert_eq!(&src[list.start..list.end], "<List items={items} />");
+}
+
+#[test]
+fn each_root_renders_separately() {
+    let html = render("const a = <b>bold</b>;\nconst b = <i>{text}</i>;\nconst c = <Icon />;");
+    let rendered: Vec<(&str, bool)> = html.roots.iter().map(|r| (r.html.as_str(), r.is_static)).collect();
+    assert_eq!(rendered, [("<b>bold</b>", true), ("<i><!--hook:0--></i>", false), ("<!--hook:0-->", false)]);
+}
+
+#[test]
+fn form_values_render_as_react_dom_does() {
+    let html = render(
+        r#"const form = <form>
+  <textarea value="a<b" rows={2} />
+  <select value="b"><option value="a">A</option><option value={"b"}>B</option><option>c</option></select>
+  <select defaultValue={2}><option>1</option><option>{2}</option></select>
+  <textarea value={draft} />
+</form>;"#,
+    );
+    let root = &html.roots[0];
+    assert_eq!(
+        root.html,
+        concat!(
+            r#"<form><textarea rows="2">a&lt;b</textarea>"#,
+            r#"<select><option value="a">A</option><option value="b" selected="">B</option><option>c</option></select>"#,
+            r#"<select><option>1</option><option selected="">2</option></select>"#,
+            r#"<textarea data-hook-holes="0"></textarea></form>"#,
+        )
+    );
+    assert!(matches!(&root.holes[0].kind, HtmlHoleKind::Prop { name } if name == "value"));
+}
+
+#[test]
+fn svg_props_render_as_react_dom_names_them() {
+    let html = render(r#"const icon = <svg viewBox="0 0 2 2"><path strokeWidth={2} fillOpacity="0.5" xlinkHref="/a" /></svg>;"#);
+    assert_eq!(
+        html.roots[0].html,
+        r#"<svg viewBox="0 0 2 2"><path stroke-width="2" fill-opacity="0.5" xlink:href="/a"></path></svg>"#
+    );
+}
+
+#[test]
+fn parse_errors_are_reported() {
+    let err = prerender_html("<div><span></div>", &TranspileOptions::default()).unwrap_err();
+    assert_eq!(err.code, ErrorCode::MismatchedClosingTag);
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::transpile;
+use relay_hook_transpiler::{JsxRuntime, TranspileOptions};
+
+#[test]
+fn automatic_runtime_emits_jsx_dev() {
+    let opts = TranspileOptions { filename: Some("src/App.jsx".to_string()), development: true, ..Default::default() };
+    let out = transpile("const a = <div id=\"x\" key=\"k\">hi</div>;", &opts);
+    assert_eq!(
+        out,
+        r#"const a = __hook_jsx_runtime.jsxDEV("div", { id: "x", children: "hi" }, "k", false, { fileName: "src/App.jsx", lineNumber: 1, columnNumber: 11 }, this);"#
+    );
+}
+
+#[test]
+fn missing_key_and_static_children() {
+    let opts = TranspileOptions { filename: Some("src/App.jsx".to_string()), development: true, ..Default::default() };
+    let out = transpile("<ul>\n  <li>a</li>\n  <li>b</li>\n</ul>", &opts);
+    assert!(
+        out.starts_with(r#"__hook_jsx_runtime.jsxDEV("ul", { children: ["#),
+        "{out}"
+    );
+    assert!(
+        out.ends_with(r#"] }, void 0, true, { fileName: "src/App.jsx", lineNumber: 1, columnNumber: 1 }, this)"#),
+        "{out}"
+    );
+    assert!(out.contains("lineNumber: 2, columnNumber: 3"), "{out}");
+    assert!(out.contains("lineNumber: 3, columnNumber: 3"), "{out}");
+}
+
+#[test]
+fn nested_expressions_report_original_positions() {
+    let src = "export const List = () => (\n  <ul>\n    {items.map(i => (\n      <Item key={i.id} {...i} />\n    ))}\n  </ul>\n);";
+    let opts = TranspileOptions { filename: Some("src/App.jsx".to_string()), development: true, ..Default::default() };
+    let out = transpile(src, &opts);
+    assert!(
+        out.contains(r#"__hook_jsx_runtime.jsxDEV(Item, { ...i }, i.id, false, { fileName: "src/App.jsx", lineNumber: 4, columnNumber: 7 }, this)"#),
+        "{out}"
+    );
+    assert!(out.contains("lineNumber: 2, columnNumber: 3"), "{out}");
+}
+
+#[test]
+fn typescript_positions_refer_to_the_original_source() {
+    let src = "const f = (x: number): JSX.Element => <b>{x as any}</b>;\nconst g = <T,>(v: T) => <i />;";
+    let opts = TranspileOptions { is_typescript: true, filename: Some("src/App.jsx".to_string()), development: true, ..Default::default() };
+    let out = transpile(src, &opts);
+    assert!(out.contains("lineNumber: 1, columnNumber: 39"), "{out}");
+    assert!(out.contains("lineNumber: 2, columnNumber: 25"), "{out}");
+}
+
+#[test]
+fn classic_runtime_adds_source_and_self_props() {
+    let opts = TranspileOptions {
+        jsx_runtime: JsxRuntime::classic("h", "Fragment"),
+        filename: Some("src/App.jsx".to_string()),
+        development: true,
+        ..Default::default()
+    };
+    let out = transpile("<>\n<p key=\"a\">x</p></>", &opts);
+    assert_eq!(
+        out,
+        r#"h(Fragment, null, h("p", { key: "a", __source: { fileName: "src/App.jsx", lineNumber: 2, columnNumber: 1 }, __self: this }, "x"))"#
+    );
+}
+
+#[test]
+fn import_source_uses_the_dev_runtime() {
+    let opts = TranspileOptions { filename: Some("src/App.jsx".to_string()), development: true, ..Default::default() };
+    let out = transpile("/** @jsxImportSource preact */\n<a />", &opts);
+    assert!(
+        out.starts_with("import * as __hook_jsx_runtime from \"preact/jsx-dev-runtime\"; "),
+        "{out}"
+    );
+}
+
+#[test]
+fn missing_filename_falls_back() {
+    let opts = TranspileOptions { development: true, ..Default::default() };
+    let out = transpile("<a />", &opts);
+    assert!(out.contains(r#"fileName: "hook.jsx""#), "{out}");
+}
+
+#[cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
+#[test]
+fn swc_emits_matching_dev_calls() {
+    use relay_hook_transpiler::TranspileTarget;
+
+    let opts = TranspileOptions {
+        target: TranspileTarget::Android,
+        compat_for_jsc: false,
+        filename: Some("src/App.jsx".to_string()),
+        development: true,
+        ..Default::default()
+    };
+    let out = transpile("export const a = <div>\n  <p key=\"k\">hi</p>\n</div>;", &opts);
+    assert!(out.contains("jsxDEV"), "{out}");
+    assert!(out.contains("lineNumber: 2"), "{out}");
+    assert!(out.contains("columnNumber: 3"), "{out}");
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::transpile;
+use relay_hook_transpiler::{transpile_jsx_recovering, transpile_jsx_with_options, ErrorCode, TranspileOptions};
+
+#[test]
+fn namespaced_attribute_names_are_quoted_keys() {
+    let out = transpile(r##"<use xlink:href="#icon" xml:lang="en" />"##, &TranspileOptions::default());
+    assert_eq!(
+        out,
+        r##"__hook_jsx_runtime.jsx("use", { "xlink:href": "#icon", "xml:lang": "en" })"##
+    );
+}
+
+#[test]
+fn namespaced_tag_names_are_strings() {
+    let out = transpile(r#"<svg:svg><svg:rect width="10" /></svg:svg>"#, &TranspileOptions::default());
+    assert_eq!(
+        out,
+        r#"__hook_jsx_runtime.jsx("svg:svg", { children: __hook_jsx_runtime.jsx("svg:rect", { width: "10" }) })"#
+    );
+}
+
+#[test]
+fn member_expression_tags() {
+    let out = transpile("<Icons.Arrow.Left size={2}>x</Icons.Arrow.Left>", &TranspileOptions::default());
+    assert_eq!(out, r#"__hook_jsx_runtime.jsx(Icons.Arrow.Left, { size: 2, children: "x" })"#);
+
+    // Lowercase member expressions are still references, not intrinsic elements
+    let out = transpile("<ctx.Provider value={v} />", &TranspileOptions::default());
+    assert_eq!(out, "__hook_jsx_runtime.jsx(ctx.Provider, { value: v })");
+}
+
+#[test]
+fn custom_element_and_identifier_tag_names() {
+    assert_eq!(transpile("<my-element />", &TranspileOptions::default()), r#"__hook_jsx_runtime.jsx("my-element", {})"#);
+    assert_eq!(transpile("<_Private />", &TranspileOptions::default()), "__hook_jsx_runtime.jsx(_Private, {})");
+    assert_eq!(transpile("<$Comp />", &TranspileOptions::default()), "__hook_jsx_runtime.jsx($Comp, {})");
+}
+
+#[test]
+fn element_valued_attributes() {
+    let out = transpile("<Button icon=<Icon name=\"x\" /> label=<>Hi</> />", &TranspileOptions::default());
+    assert_eq!(
+        out,
+        r#"__hook_jsx_runtime.jsx(Button, { icon: __hook_jsx_runtime.jsx(Icon, { name: "x" }), label: __hook_jsx_runtime.jsx(__hook_jsx_runtime.Fragment, { children: "Hi" }) })"#
+    );
+}
+
+#[test]
+fn spread_children() {
+    let out = transpile("<ul>{...items}</ul>", &TranspileOptions::default());
+    assert_eq!(out, r#"__hook_jsx_runtime.jsxs("ul", { children: [...items] })"#);
+
+    let out = transpile("<ul><li>first</li>{ ...rest.map(r => <li>{r}</li>) }</ul>", &TranspileOptions::default());
+    assert!(out.starts_with("__hook_jsx_runtime.jsxs(\"ul\""), "{out}");
+    assert!(out.contains("...rest.map(r => __hook_jsx_runtime.jsx(\"li\", { children: r }))"), "{out}");
+}
+
+#[test]
+fn non_ascii_identifiers() {
+    let out = transpile("<Überschrift größe=\"groß\">Grüße</Überschrift>", &TranspileOptions::default());
+    assert_eq!(out, r#"__hook_jsx_runtime.jsx(Überschrift, { größe: "groß", children: "Grüße" })"#);
+
+    let out = transpile("<日本 名前={値} />", &TranspileOptions::default());
+    assert_eq!(out, "__hook_jsx_runtime.jsx(日本, { 名前: 値 })");
+}
+
+#[test]
+fn spread_attributes_and_comments_inside_tags() {
+    let out = transpile("<div { ...props } /* note */ id=\"a\" // trailing\n />", &TranspileOptions::default());
+    assert_eq!(out, r#"__hook_jsx_runtime.jsx("div", { ...props, id: "a" })"#);
+}
+
+#[test]
+fn whitespace_inside_tags() {
+    let out = transpile("<div >x</ div >", &TranspileOptions::default());
+    assert_eq!(out, r#"__hook_jsx_runtime.jsx("div", { children: "x" })"#);
+
+    let out = transpile("<br / >", &TranspileOptions::default());
+    assert_eq!(out, r#"__hook_jsx_runtime.jsx("br", {})"#);
+}
+
+#[test]
+fn svg_heavy_component() {
+    let src = r##"export default function Logo() {
+  return (
+    <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 24 24">
+      <defs>
+        <linearGradient id="g"><stop offset="0" /></linearGradient>
+      </defs>
+      <use xlink:href="#g" />
+    </svg>
+  );
+}"##;
+    let out = transpile(src, &TranspileOptions::default());
+    assert!(out.contains(r#""xmlns:xlink": "http://www.w3.org/1999/xlink""#), "{out}");
+    assert!(out.contains(r##"__hook_jsx_runtime.jsx("use", { "xlink:href": "#g" })"##), "{out}");
+    assert!(out.contains(r#"__hook_jsx_runtime.jsx("linearGradient""#), "{out}");
+
+    let opts = TranspileOptions { is_typescript: true, ..Default::default() };
+    let ts_out = transpile_jsx_with_options(&src.replace("Logo()", "Logo(): JSX.Element"), &opts).unwrap();
+    assert!(ts_out.contains(r##""xlink:href": "#g""##), "{ts_out}");
+}
+
+#[test]
+fn non_identifier_prop_keys_are_quoted() {
+    let out = transpile(r#"<button aria-label="Close" data-id={1} class="x" for="y" onClick={f} disabled />"#, &TranspileOptions::default());
+    assert_eq!(
+        out,
+        r#"__hook_jsx_runtime.jsx("button", { "aria-label": "Close", "data-id": 1, "class": "x", "for": "y", onClick: f, disabled: true })"#
+    );
+}
+
+#[test]
+fn duplicate_props_warn_and_last_wins() {
+    let src = "<div id=\"a\" {...rest} title=\"t\" id=\"b\" key={1} key={2} />";
+    let result = transpile_jsx_recovering(src, &TranspileOptions::default());
+    assert_eq!(
+        result.code,
+        r#"__hook_jsx_runtime.createElement("div", { id: "a", ...rest, title: "t", ...{ id: "b" }, key: 1, ...{ key: 2 } })"#
+    );
+
+    let codes: Vec<ErrorCode> = result.diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(codes, vec![ErrorCode::DuplicateProp, ErrorCode::DuplicateProp]);
+    let first = result.diagnostics[0].span.as_deref().unwrap();
+    assert_eq!(&src[first.start..first.end], "id");
+    assert_eq!(first.column, 33);
+
+    // Duplicates are warnings, so strict mode still succeeds
+    assert_eq!(transpile(src, &TranspileOptions::default()), result.code);
+}
+
+#[test]
+fn duplicate_props_still_evaluate_the_earlier_value() {
+    let out = transpile("<div a={f()} b=\"1\" a={g()} key={k()} key={l()} />", &TranspileOptions::default());
+    assert_eq!(out, r#"__hook_jsx_runtime.jsx("div", { a: f(), b: "1", ...{ a: g() } }, (k(), l()))"#);
+}
+
+#[test]
+fn trailing_line_comment_in_expression_keeps_its_line_break() {
+    let out = transpile("<div a={x // why\n} />", &TranspileOptions::default());
+    assert_eq!(out, "__hook_jsx_runtime.jsx(\"div\", { a: x // why\n })");
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::transpile;
+#[cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
+use relay_hook_transpiler::transpile_jsx_with_options;
+use relay_hook_transpiler::{JsxRuntime, TranspileOptions};
+
+#[test]
+fn automatic_runtime_with_custom_object() {
+    let runtime = JsxRuntime::Automatic {
+        object: "$rt".to_string(),
+        import_source: None,
+    };
+    let opts = TranspileOptions { jsx_runtime: runtime, ..Default::default() };
+    let out = transpile("const a = <><b key=\"k\">{x}</b></>;", &opts);
+    assert_eq!(
+        out,
+        r#"const a = $rt.jsx($rt.Fragment, { children: $rt.jsx("b", { children: x }, "k") });"#
+    );
+}
+
+#[test]
+fn classic_runtime_emits_create_element_calls() {
+    let opts = TranspileOptions { jsx_runtime: JsxRuntime::classic("React.createElement", "React.Fragment"), ..Default::default() };
+    let out = transpile("const a = <div className=\"x\" key={id}>hi {name}<br /></div>;", &opts);
+    assert_eq!(
+        out,
+        r#"const a = React.createElement("div", { className: "x", key: id }, "hi ", name, React.createElement("br", null));"#
+    );
+}
+
+#[test]
+fn classic_runtime_fragments_and_spread_children() {
+    let opts = TranspileOptions { jsx_runtime: JsxRuntime::classic("h", "Fragment"), ..Default::default() };
+    let out = transpile("<><A {...p} />{...rest}</>", &opts);
+    assert_eq!(out, "h(Fragment, null, h(A, { ...p }), ...rest)");
+}
+
+#[test]
+fn jsx_pragmas_switch_to_classic_for_the_file() {
+    let src = "/** @jsx h */\n/** @jsxFrag Fragment */\nimport { h, Fragment } from 'preact';\nexport const App = () => <><p>{items.map(i => <i>{i}</i>)}</p></>;";
+    let out = transpile(src, &TranspileOptions::default());
+    assert!(
+        out.contains("h(Fragment, null, h(\"p\", null, items.map(i => h(\"i\", null, i))))"),
+        "{out}"
+    );
+    assert!(!out.contains("__hook_jsx_runtime"), "{out}");
+}
+
+#[test]
+fn jsx_runtime_pragma_overrides_classic_option() {
+    let src = "// @jsxRuntime automatic\nconst a = <div />;";
+    let opts = TranspileOptions { jsx_runtime: JsxRuntime::classic("h", "Fragment"), ..Default::default() };
+    let out = transpile(src, &opts);
+    assert_eq!(out, "// @jsxRuntime automatic\nconst a = __hook_jsx_runtime.jsx(\"div\", {});");
+}
+
+#[test]
+fn jsx_import_source_pragma_imports_the_runtime() {
+    let src = "/** @jsxImportSource preact */\nconst a = <div />;";
+    let out = transpile(src, &TranspileOptions::default());
+    assert_eq!(
+        out,
+        "import * as __hook_jsx_runtime from \"preact/jsx-runtime\"; /** @jsxImportSource preact */\nconst a = __hook_jsx_runtime.jsx(\"div\", {});"
+    );
+}
+
+#[test]
+fn key_after_spread_imports_create_element_from_the_import_source() {
+    let src = "/** @jsxImportSource preact */\nconst a = <div {...p} key=\"k\" />;";
+    let out = transpile(src, &TranspileOptions::default());
+    assert_eq!(
+        out,
+        "import { createElement as __hook_jsx_runtime_createElement } from \"preact\"; /** @jsxImportSource preact */\nconst a = __hook_jsx_runtime_createElement(\"div\", { ...p, key: \"k\" });"
+    );
+}
+
+#[test]
+fn pragmas_inside_strings_are_ignored() {
+    let src = "const s = '/** @jsx h */';\nconst a = <div />;";
+    let out = transpile(src, &TranspileOptions::default());
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", {})"), "{out}");
+}
+
+#[cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
+#[test]
+fn swc_honours_runtime_and_pragmas() {
+    use relay_hook_transpiler::TranspileTarget;
+
+    let opts = TranspileOptions {
+        target: TranspileTarget::Android,
+        compat_for_jsc: false,
+        jsx_runtime: JsxRuntime::classic("h", "Fragment"),
+        ..Default::default()
+    };
+    let out = transpile_jsx_with_options("export const a = <><p>hi</p></>;", &opts).unwrap();
+    assert!(out.contains("h(Fragment, null, h(\"p\", null, \"hi\"))"), "{out}");
+
+    let opts = TranspileOptions { jsx_runtime: JsxRuntime::default(), ..opts };
+    let out = transpile_jsx_with_options("/** @jsx h */\nexport const a = <p />;", &opts).unwrap();
+    assert!(out.contains("h(\"p\", null)"), "{out}");
+}
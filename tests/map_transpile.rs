@@ -28,16 +28,17 @@ fn comprehensive_map_test() {
     let out = transpile_jsx_simple(src).expect("should transpile");
 
     // Verify root div
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { children: [peers.map("));
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { children: peers.map("));
     
-    // Verify inner div inside map
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { key: p.id, children: ["));
+    // Verify inner div inside map, with its key after the props
+    assert!(out.contains("__hook_jsx_runtime.jsxs(\"div\", { children: ["));
+    assert!(out.contains("] }, p.id)"));
     
     // Verify span inside inner div
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"span\", { children: [p.name] })"));
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"span\", { children: p.name })"));
     
     // Verify button inside inner div
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"button\", { onClick: () => remove(p.id), children: [\"X\"] })"));
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"button\", { onClick: () => remove(p.id), children: \"X\" })"));
 }
 
 #[test]
@@ -98,11 +99,11 @@ fn map_with_sibling_elements_creates_nested_array() {
     assert!(label_pos < map_pos, "label div should come before peers.map in output");
     
     // Verify the map returns JSX elements with the correct structure
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { key: peer.host"), 
-            "map should return div elements with key prop");
-    assert!(out.contains("children: [peer.host]"), 
+    assert!(out.contains("__hook_jsx_runtime.jsxs(\"div\", { children: [") && out.contains("] }, peer.host)"), 
+            "map should return div elements with key argument");
+    assert!(out.contains("children: peer.host"), 
             "first span in mapped items should contain peer.host");
-    assert!(out.contains("children: [peer.note]"), 
+    assert!(out.contains("children: peer.note"), 
             "second span in mapped items should contain peer.note");
     
     // The critical part: children array contains both static element and map result
@@ -133,8 +134,8 @@ fn nested_map_calls_transpile() {
     assert!(out.contains("cat.items.map"), "should contain inner map");
     
     // Both should produce JSX elements
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { key: cat.id"), 
+    assert!(out.contains("] }, cat.id)"), 
             "outer map should create divs");
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"span\", { key: item.id"), 
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"span\", { children: item.title }, item.id)"), 
             "inner map should create spans");
 }
@@ -0,0 +1,74 @@
+//! Regex literals containing quotes, braces or `<` must not desynchronize string tracking
+//! in the JSX scanner, the TypeScript passes or the downleveler
+
+mod common;
+
+use common::transpile;
+use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions};
+
+#[test]
+fn quote_in_regex_does_not_hide_following_jsx() {
+    let out = transpile("const isQuote = (s) => /'/.test(s);\nconst el = <b>{isQuote(x) ? 'yes' : 'no'}</b>;", &TranspileOptions::default());
+    assert!(out.contains("/'/.test(s)"), "output: {}", out);
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"b\", { children: isQuote(x) ? 'yes' : 'no' })"), "output: {}", out);
+}
+
+#[test]
+fn double_quoted_pattern_with_flags() {
+    let src = r#"export function useQuoted(text) {
+  const parts = text.match(/"[^"]*"/g) || [];
+  return <ul>{parts.map((p) => <li key={p}>{p}</li>)}</ul>;
+}"#;
+    let out = transpile(src, &TranspileOptions::default());
+    assert!(out.contains(r#"text.match(/"[^"]*"/g)"#), "output: {}", out);
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"li\", { children: p }, p)"), "output: {}", out);
+}
+
+#[test]
+fn regex_with_tag_like_text_is_not_jsx() {
+    let out = transpile("const strip = (s) => s.replace(/<b>|<\\/b>/g, '');\nconst el = <i>x</i>;", &TranspileOptions::default());
+    assert!(out.contains("s.replace(/<b>|<\\/b>/g, '')"), "output: {}", out);
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"i\", { children: \"x\" })"), "output: {}", out);
+}
+
+#[test]
+fn division_is_not_a_regex() {
+    let out = transpile("const half = total / 2, third = (n) / 3;\nconst el = <p>{a / b}</p>;", &TranspileOptions::default());
+    assert!(out.contains("const half = total / 2, third = (n) / 3;"), "output: {}", out);
+    assert!(out.contains("children: a / b"), "output: {}", out);
+}
+
+#[test]
+fn regex_inside_jsx_expression_and_template() {
+    let out = transpile("const el = <p title={s.replace(/}/g, '')}>{`${s.split(/`/).length}`}</p>;", &TranspileOptions::default());
+    assert!(out.contains("title: s.replace(/}/g, '')"), "output: {}", out);
+    assert!(out.contains("children: `${s.split(/`/).length}`"), "output: {}", out);
+}
+
+#[test]
+fn typescript_after_regex_is_still_stripped() {
+    let opts = TranspileOptions { is_typescript: true, ..Default::default() };
+    let src = "const re = /'/g;\nconst count: number = s.replace(re, '').length;\nconst el = <b>{count}</b>;";
+    let out = transpile_jsx_with_options(src, &opts).unwrap();
+    assert!(!out.contains(": number"), "output: {}", out);
+    assert!(out.contains("/'/g"), "output: {}", out);
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"b\""), "output: {}", out);
+}
+
+#[test]
+fn typescript_keywords_in_regex_are_not_flagged() {
+    let out = transpile("const re = /interface: string/;\nconst el = <b>ok</b>;", &TranspileOptions::default());
+    assert!(out.contains("/interface: string/"), "output: {}", out);
+}
+
+#[cfg(not(feature = "native-swc"))]
+#[test]
+fn downleveling_leaves_regex_literals_alone() {
+    use relay_hook_transpiler::TranspileTarget;
+
+    let opts = TranspileOptions { target: TranspileTarget::Android, ..Default::default() };
+    let src = "const m = s.match(/a?.b/) ?? [];\nconst el = <b>{m?.length}</b>;";
+    let out = transpile_jsx_with_options(src, &opts).unwrap();
+    assert!(out.contains("s.match(/a?.b/)"), "output: {}", out);
+    assert!(!out.contains("m?.length"), "output: {}", out);
+}
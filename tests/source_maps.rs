@@ -0,0 +1,132 @@
+//! Source maps from the custom pipeline: every mapped output token must lead back to the
+//! same token in the original source, through TypeScript stripping, JSX generation,
+//! downleveling and dynamic import rewriting.
+
+use relay_hook_transpiler::{transpile_jsx_with_source_map, TranspileOptions};
+#[cfg(not(feature = "native-swc"))]
+use relay_hook_transpiler::TranspileTarget;
+
+/// Decode a v3 `mappings` string into `(generated line, generated column, original line,
+/// original column)` tuples
+fn decode_mappings(mappings: &str) -> Vec<(usize, usize, usize, usize)> {
+    const BASE64: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut segments = Vec::new();
+    let (mut source_line, mut source_col) = (0i64, 0i64);
+    for (line, groups) in mappings.split(';').enumerate() {
+        let mut col = 0i64;
+        for group in groups.split(',').filter(|g| !g.is_empty()) {
+            let mut fields = Vec::new();
+            let (mut value, mut shift) = (0i64, 0);
+            for ch in group.chars() {
+                let digit = BASE64.find(ch).unwrap() as i64;
+                value |= (digit & 31) << shift;
+                shift += 5;
+                if digit & 32 == 0 {
+                    fields.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+                    value = 0;
+                    shift = 0;
+                }
+            }
+            col += fields[0];
+            source_line += fields[2];
+            source_col += fields[3];
+            segments.push((line, col as usize, source_line as usize, source_col as usize));
+        }
+    }
+    segments
+}
+
+fn json_field<'a>(json: &'a str, name: &str) -> &'a str {
+    let start = json.find(&format!("\"{name}\":\"")).unwrap() + name.len() + 4;
+    let end = start + json[start..].find('"').unwrap();
+    &json[start..end]
+}
+
+/// Transpile with a source map and return the code and its decoded mappings
+fn transpile(source: &str, opts: TranspileOptions) -> (String, Vec<(usize, usize, usize, usize)>) {
+    let opts = TranspileOptions { source_maps: true, filename: Some("hook.tsx".into()), ..opts };
+    let output = transpile_jsx_with_source_map(source, &opts).unwrap();
+    let map = output.source_map.expect("source map requested");
+    assert!(map.contains(r#""version":3"#), "{}", map);
+    assert!(map.contains(r#""sources":["hook.tsx"]"#), "{}", map);
+    (output.code, decode_mappings(json_field(&map, "mappings")))
+}
+
+/// Original `(line, column)` of the mapped output token that starts with `needle`
+fn original_of(code: &str, mappings: &[(usize, usize, usize, usize)], needle: &str) -> (usize, usize) {
+    let at = code.find(needle).unwrap_or_else(|| panic!("{needle:?} not in output:\n{code}"));
+    let line = code[..at].matches('\n').count();
+    let col = at - code[..at].rfind('\n').map_or(0, |i| i + 1);
+    mappings
+        .iter()
+        .find(|&&(l, c, _, _)| l == line && c == col)
+        .map(|&(_, _, ol, oc)| (ol, oc))
+        .unwrap_or_else(|| panic!("no mapping for {needle:?} at {line}:{col} in:\n{code}"))
+}
+
+/// `(line, column)` of `needle` in the original source
+fn position_in(source: &str, needle: &str) -> (usize, usize) {
+    let at = source.find(needle).unwrap();
+    let line = source[..at].matches('\n').count();
+    (line, at - source[..at].rfind('\n').map_or(0, |i| i + 1))
+}
+
+#[test]
+fn multi_line_jsx_maps_to_original_lines() {
+    let source = "export default function Card({ title }) {\n  const [open, setOpen] = useState(false);\n  return (\n    <div className=\"card\">\n      <h2 onClick={() => setOpen(!open)}>\n        {title}\n      </h2>\n    </div>\n  );\n}\n";
+    let (code, mappings) = transpile(source, TranspileOptions::default());
+
+    assert_eq!(original_of(&code, &mappings, "useState"), position_in(source, "useState"));
+    assert_eq!(original_of(&code, &mappings, "setOpen(!open)"), position_in(source, "setOpen(!open)"));
+    assert_eq!(original_of(&code, &mappings, "title }) })"), position_in(source, "title}"));
+    // Generated calls map to the element they were generated for
+    assert_eq!(original_of(&code, &mappings, "__hook_jsx_runtime.jsx(\"h2\""), position_in(source, "<h2"));
+    assert_eq!(original_of(&code, &mappings, "\"card\""), position_in(source, "\"card\""));
+}
+
+#[test]
+fn stripped_types_keep_positions() {
+    let source = "interface Props { name: string }\n\nexport function greet({ name }: Props): string {\n  const upper: string = name.toUpperCase();\n  return `hi ${upper}`;\n}\n";
+    let opts = TranspileOptions { is_typescript: true, ..Default::default() };
+    let (code, mappings) = transpile(source, opts);
+
+    assert!(!code.contains("interface"), "{}", code);
+    assert_eq!(original_of(&code, &mappings, "greet"), position_in(source, "greet"));
+    assert_eq!(original_of(&code, &mappings, "toUpperCase"), position_in(source, "toUpperCase"));
+    assert_eq!(original_of(&code, &mappings, "`hi"), position_in(source, "`hi"));
+}
+
+// With native-swc the Android target is transpiled (and mapped) by SWC instead
+#[cfg(not(feature = "native-swc"))]
+#[test]
+fn android_downleveling_and_dynamic_imports() {
+    let source = "const load = async () => {\n  const mod = await import('./x.jsx');\n  let n = mod?.count ?? 0;\n  return <b>{n}</b>;\n};\n";
+    let opts = TranspileOptions { target: TranspileTarget::Android, ..Default::default() };
+    let (code, mappings) = transpile(source, opts);
+
+    assert!(code.contains("__hook_import"), "{}", code);
+    assert_eq!(original_of(&code, &mappings, "__hook_import"), position_in(source, "import("));
+    assert_eq!(original_of(&code, &mappings, "'./x.jsx'"), position_in(source, "'./x.jsx'"));
+    assert_eq!(original_of(&code, &mappings, "var n"), position_in(source, "let n"));
+    // The rewritten `??` and `?.` map to the expression they replace
+    assert_eq!(original_of(&code, &mappings, "((mod != null"), position_in(source, "mod?.count"));
+}
+
+#[test]
+fn inline_and_external_maps() {
+    let source = "const a = <p>hi</p>;\n";
+    let external = TranspileOptions { source_maps: true, ..Default::default() };
+    let output = transpile_jsx_with_source_map(source, &external).unwrap();
+    assert!(!output.code.contains("sourceMappingURL"));
+    assert!(output.source_map.unwrap().contains(r#""sources":["hook.jsx"]"#));
+
+    let inline = TranspileOptions { inline_source_map: true, ..external };
+    let output = transpile_jsx_with_source_map(source, &inline).unwrap();
+    let (code, comment) = output.code.rsplit_once('\n').unwrap();
+    assert!(comment.starts_with("//# sourceMappingURL=data:application/json;base64,"), "{}", comment);
+    assert!(code.contains("__hook_jsx_runtime.jsx(\"p\""));
+
+    let output = transpile_jsx_with_source_map(source, &TranspileOptions::default()).unwrap();
+    assert!(output.source_map.is_none());
+    assert!(!output.code.contains("sourceMappingURL"));
+}
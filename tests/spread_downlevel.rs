@@ -0,0 +1,70 @@
+//! Spread and object literal regression tests for the custom Android path
+//! Validates that components spreading props and state lower to ES5 JavaScriptCore can run
+
+#[cfg(not(feature = "native-swc"))]
+#[test]
+fn jsx_prop_spreads_are_assigned() {
+    use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions, TranspileTarget};
+
+    let source = r#"
+export default function Row({ item, ...props }) {
+    return <li {...props} className="row"><Item key={item.id} {...item} onRemove={() => remove(item.id)} /></li>;
+}
+"#;
+
+    let opts = TranspileOptions {
+        filename: Some("row.jsx".to_string()),
+        target: TranspileTarget::Android,
+        ..Default::default()
+    };
+
+    let result = transpile_jsx_with_options(source, &opts).expect("transpilation failed");
+
+    assert!(result.starts_with("function __hook_object_rest(source, excluded) {"), "{}", result);
+    assert!(result.contains("function __hook_assign(target) {"), "{}", result);
+    assert!(result.contains(r#"__hook_assign({}, props, { className: "row", children: "#), "{}", result);
+    assert!(
+        result.contains("__hook_assign({}, item, { onRemove: function () { return remove(item.id); } }), item.id)"),
+        "{}",
+        result
+    );
+    assert!(!result.contains("..."), "{}", result);
+}
+
+#[cfg(not(feature = "native-swc"))]
+#[test]
+fn state_updates_spread_arrays_and_arguments() {
+    use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions, TranspileTarget};
+
+    let source = r#"
+import { useState } from "react";
+
+export default function Todos({ initial }) {
+    const [items, setItems] = useState(initial);
+    const add = (text) => setItems([...items, { id: items.length, text }]);
+    const longest = Math.max(...items.map((item) => item.text.length));
+    return <p onClick={() => add("new")}>{longest}</p>;
+}
+"#;
+
+    let opts = TranspileOptions {
+        filename: Some("todos.jsx".to_string()),
+        target: TranspileTarget::Android,
+        ..Default::default()
+    };
+
+    let result = transpile_jsx_with_options(source, &opts).expect("transpilation failed");
+
+    assert!(result.starts_with("function __hook_spread(value) {"), "{}", result);
+    assert!(
+        result.contains("return setItems([].concat(__hook_spread(items), [{ id: items.length, text: text }]));"),
+        "{}",
+        result
+    );
+    assert!(
+        result.contains("var longest = Math.max.apply(Math, items.map(function (item) { return item.text.length; }));"),
+        "{}",
+        result
+    );
+    assert!(!result.contains("..."), "{}", result);
+}
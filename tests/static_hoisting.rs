@@ -0,0 +1,104 @@
+mod common;
+
+use common::transpile;
+use relay_hook_transpiler::{JsxRuntime, TranspileOptions};
+
+const PICKER: &str = "import { useState } from 'react';\n\nexport function Picker({ themes }) {\n  const [theme, setTheme] = useState(null);\n  return (\n    <div>\n      <p className=\"hint\">Select a theme</p>\n      <ul>{themes.map(t => <li key={t} onClick={() => setTheme(t)}>{t}</li>)}</ul>\n    </div>\n  );\n}\n";
+
+#[test]
+fn static_subtrees_become_module_constants_after_the_imports() {
+    let opts = TranspileOptions { hoist_static_jsx: true, ..Default::default() };
+    let out = transpile(PICKER, &opts);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "import { useState } from 'react';");
+    assert_eq!(
+        lines[1],
+        r#"const __hook_static_jsx_1 = __hook_jsx_runtime.jsx("p", { className: "hint", children: "Select a theme" });"#
+    );
+    assert!(out.contains(r#"__hook_jsx_runtime.jsxs("div", { children: [__hook_static_jsx_1, __hook_jsx_runtime.jsx("ul""#), "{out}");
+    // The `li` reads `t`, so it is rebuilt on every call
+    assert!(out.contains(r#"__hook_jsx_runtime.jsx("li", { onClick: () => setTheme(t), children: t }, t)"#), "{out}");
+    // The declarations share a line with other code, so lines stay where they were
+    assert_eq!(out.lines().count(), transpile(PICKER, &TranspileOptions::default()).lines().count());
+}
+
+#[test]
+fn outermost_static_node_is_hoisted_whole() {
+    let src = "const a = () => <section><h1 id=\"t\" hidden>Title</h1><>{\"x\"}{-1}{`y`}{/* c */}<br /></></section>;\n";
+    let opts = TranspileOptions { hoist_static_jsx: true, ..Default::default() };
+    let out = transpile(src, &opts);
+    assert_eq!(
+        out,
+        "const __hook_static_jsx_1 = __hook_jsx_runtime.jsxs(\"section\", { children: [__hook_jsx_runtime.jsx(\"h1\", { id: \"t\", hidden: true, children: \"Title\" }), __hook_jsx_runtime.jsxs(__hook_jsx_runtime.Fragment, { children: [\"x\", -1, `y`, __hook_jsx_runtime.jsx(\"br\", {})] })] }); const a = () => __hook_static_jsx_1;\n"
+    );
+}
+
+#[test]
+fn declarations_follow_the_directive_prologue() {
+    let opts = TranspileOptions { hoist_static_jsx: true, ..Default::default() };
+    let out = transpile("'use strict';\nconst a = () => <br />;\n", &opts);
+    assert_eq!(
+        out,
+        "'use strict';\nconst __hook_static_jsx_1 = __hook_jsx_runtime.jsx(\"br\", {}); const a = () => __hook_static_jsx_1;\n"
+    );
+    // Imports after the directives still come first
+    let out = transpile("\"use client\";\nimport x from 'x';\nconst a = () => <br />;\n", &opts);
+    assert!(out.starts_with("\"use client\";\nimport x from 'x';\nconst __hook_static_jsx_1 = "), "{out}");
+}
+
+#[test]
+fn dynamic_parts_keep_elements_inline() {
+    let opts = TranspileOptions { hoist_static_jsx: true, ..Default::default() };
+    for src in [
+        "const a = () => <p>{name}</p>;",
+        "const a = () => <p {...props} />;",
+        "const a = () => <p ref={node} />;",
+        "const a = () => <p title={`${x}`} />;",
+        "const a = () => <p pattern={/x/} />;",
+        "const a = () => <Hint>text</Hint>;",
+        "const a = () => <ui.Hint />;",
+    ] {
+        let out = transpile(src, &opts);
+        assert!(!out.contains("__hook_static_jsx_"), "{src} -> {out}");
+    }
+    // Only the static child of a dynamic parent is hoisted
+    let out = transpile("const a = () => <Card><b>static</b>{name}</Card>;", &opts);
+    assert!(out.starts_with(r#"const __hook_static_jsx_1 = __hook_jsx_runtime.jsx("b", { children: "static" }); "#), "{out}");
+    assert!(out.contains("jsxs(Card, { children: [__hook_static_jsx_1, name] })"), "{out}");
+}
+
+#[test]
+fn off_by_default_and_in_development() {
+    let src = "const a = () => <p>hi</p>;";
+    assert_eq!(transpile(src, &TranspileOptions::default()), r#"const a = () => __hook_jsx_runtime.jsx("p", { children: "hi" });"#);
+    let dev = TranspileOptions { development: true, hoist_static_jsx: true, ..Default::default() };
+    assert!(!transpile(src, &dev).contains("__hook_static_jsx_"));
+}
+
+#[test]
+fn classic_runtime_and_typescript() {
+    let opts = TranspileOptions { jsx_runtime: JsxRuntime::classic("h", "Fragment"), is_typescript: true, hoist_static_jsx: true, ..Default::default() };
+    let src = "import { h } from 'preact';\nconst a = (n: number) => <i class=\"icon\" />;\n";
+    assert_eq!(
+        transpile(src, &opts),
+        "import { h } from 'preact';\nconst __hook_static_jsx_1 = h(\"i\", { \"class\": \"icon\" }); const a = (n ) => __hook_static_jsx_1;\n"
+    );
+}
+
+#[cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
+#[test]
+fn swc_hoists_the_same_subtrees() {
+    use relay_hook_transpiler::TranspileTarget;
+
+    let opts = TranspileOptions { target: TranspileTarget::Android, compat_for_jsc: false, hoist_static_jsx: true, ..Default::default() };
+    let out = transpile(PICKER, &opts);
+    let imports_end = out.find("from 'react';").unwrap();
+    let declaration = out.find("const __hook_static_jsx_1 = _jsx(\"p\"").expect(&out);
+    assert!(imports_end < declaration, "{out}");
+    assert!(out.contains("__hook_static_jsx_1,"), "{out}");
+    assert!(!out.contains("__hook_static_jsx_2"), "{out}");
+
+    let out = transpile("'use strict';\nexport const a = () => <br />;\n", &opts);
+    let declaration = out.find("const __hook_static_jsx_1").expect(&out);
+    assert!(out.find("use strict").is_some_and(|directive| directive < declaration), "{out}");
+}
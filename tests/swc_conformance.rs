@@ -0,0 +1,99 @@
+//! The custom parser's automatic-runtime output compared against SWC's React transform
+#![cfg(all(feature = "native-swc", not(target_arch = "wasm32")))]
+
+use relay_hook_transpiler::{transpile_jsx_with_options, TranspileOptions, TranspileTarget};
+
+/// Rename SWC's imported helpers to runtime-object members and drop whitespace outside
+/// string literals, so the two engines' output can be compared directly
+fn normalize(code: &str) -> String {
+    let code: String = code
+        .lines()
+        .filter(|line| !line.starts_with("import "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace("_jsxs(", "__hook_jsx_runtime.jsxs(")
+        .replace("_jsxDEV(", "__hook_jsx_runtime.jsxDEV(")
+        .replace("_jsx(", "__hook_jsx_runtime.jsx(")
+        .replace("_Fragment", "__hook_jsx_runtime.Fragment")
+        .replace("_createElement(", "__hook_jsx_runtime.createElement(");
+
+    let mut out = String::new();
+    let mut quote = None;
+    let mut escaped = false;
+    for ch in code.chars() {
+        match quote {
+            Some(q) => {
+                out.push(ch);
+                if escaped {
+                    escaped = false;
+                } else if ch == '\\' {
+                    escaped = true;
+                } else if ch == q {
+                    quote = None;
+                }
+            }
+            None if ch == '"' || ch == '\'' => {
+                quote = Some(ch);
+                out.push(ch);
+            }
+            None if ch.is_whitespace() => {}
+            None => out.push(ch),
+        }
+    }
+    out
+}
+
+fn assert_conforms(src: &str, development: bool) {
+    let custom = TranspileOptions { development, ..Default::default() };
+    let swc = TranspileOptions {
+        target: TranspileTarget::Android,
+        compat_for_jsc: false,
+        development,
+        ..Default::default()
+    };
+    let ours = transpile_jsx_with_options(src, &custom).unwrap_or_else(|e| panic!("{}", e.render()));
+    let theirs = transpile_jsx_with_options(src, &swc).unwrap_or_else(|e| panic!("{}", e.render()));
+    assert_eq!(normalize(&ours), normalize(&theirs), "\ncustom: {ours}\nswc: {theirs}");
+}
+
+const CASES: &[&str] = &[
+    "const a = <div />;",
+    "const a = <div>x</div>;",
+    "const a = <div>{value}</div>;",
+    "const a = <div>{a}{b}</div>;",
+    "const a = <ul>\n  <li>one</li>\n  <li>two</li>\n</ul>;",
+    "const a = <ul>{...items}</ul>;",
+    "const a = <p>hello {name}!</p>;",
+    "const a = <List items={items} render={(i) => <Item {...i} />} />;",
+    "const a = <li key={id} className=\"row\">{label}</li>;",
+    "const a = <div key=\"k\" {...props} />;",
+    "const a = <div {...props} key=\"k\" />;",
+    "const a = <div {...props} key=\"k\" id=\"x\">only</div>;",
+    "const a = <></>;",
+    "const a = <>text</>;",
+    "const a = <><A /><B /></>;",
+    "const a = <Outer>\n  {list.map((x) => <Inner key={x.id}>{x.name}</Inner>)}\n</Outer>;",
+];
+
+#[test]
+fn automatic_runtime_matches_swc() {
+    for src in CASES {
+        assert_conforms(src, false);
+    }
+}
+
+#[test]
+fn development_runtime_matches_swc() {
+    // SWC passes `void 0` rather than `this` as the self argument inside arrow functions
+    for src in CASES.iter().filter(|src| !src.contains("=>")) {
+        assert_conforms(src, true);
+    }
+}
+
+#[test]
+fn key_after_spread_with_several_children_passes_them_as_arguments() {
+    // SWC folds several children into the props object here; React's createElement (and
+    // Babel) take them as arguments, which is what the custom parser emits
+    let out = transpile_jsx_with_options("<div {...p} key=\"k\">a{b}</div>", &TranspileOptions::default()).unwrap();
+    assert_eq!(out, r#"__hook_jsx_runtime.createElement("div", { ...p, key: "k" }, "a", b)"#);
+}
@@ -33,7 +33,7 @@ fn test_ts_type_annotations_on() {
     assert!(out.contains("const x") && out.contains("= \"hello\""));
     assert!(out.contains("const y") && out.contains("= 42"));
     assert!(out.contains("function greet(name"));
-    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { children: [greet(x)] })"));
+    assert!(out.contains("__hook_jsx_runtime.jsx(\"div\", { children: greet(x) })"));
 }
 
 #[test]
@@ -79,3 +79,13 @@ fn test_ts_complex_constructs() {
     assert!(out.contains("const x = u"));
     assert!(out.contains("const y = u.name"));
 }
+
+#[test]
+fn test_ts_import_export_renames_are_kept() {
+    let src = "import def, { a as b } from 'm';\nimport * as ns from 'ns';\nexport * as all from './all';\nexport { b as default };\nconst c = ns as Ns;\n";
+    let out = transpile_jsx_with_options(src, &TranspileOptions { is_typescript: true, ..Default::default() }).expect("should transpile");
+    assert_eq!(
+        out,
+        "import def, { a as b } from 'm';\nimport * as ns from 'ns';\nexport * as all from './all';\nexport { b as default };\nconst c = ns  ;\n"
+    );
+}
@@ -0,0 +1,121 @@
+//! Angle brackets in TSX follow TypeScript's own rules: a `<` in expression position is
+//! a JSX element unless it opens an arrow function's type parameters (`<T,>`,
+//! `<T extends U>`, or `<T>(…) =>`), and a `<` after an operand is a type argument list
+//! only if it closes and is followed by something that cannot continue a comparison.
+
+use relay_hook_transpiler::{transpile_jsx_simple, transpile_jsx_with_options, ErrorCode, TranspileOptions};
+
+/// Transpile as TypeScript, collapsing the whitespace left behind by stripped types
+fn strip(src: &str) -> String {
+    let opts = TranspileOptions { is_typescript: true, ..Default::default() };
+    let out = transpile_jsx_with_options(src, &opts).unwrap_or_else(|e| panic!("failed to transpile {src:?}: {}", e.render()));
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[test]
+fn arrow_function_type_parameters() {
+    let cases = [
+        ("const id = <T,>(x: T) => x;", "const id = (x ) => x;"),
+        ("const id = <T, U>(x: T, y: U) => x;", "const id = (x , y ) => x;"),
+        ("const f = <T extends Base>(x: T) => x;", "const f = (x ) => x;"),
+        ("const f = <T extends keyof P = 'a'>(k: T) => k;", "const f = (k ) => k;"),
+        ("const f = <T>(x: T) => x;", "const f = (x ) => x;"),
+        ("const f = <T>(x: T): T[] => [x];", "const f = (x ) => [x];"),
+        ("const f = async <T,>(x: T): Promise<T> => x;", "const f = async (x ) => x;"),
+        ("const f = <const T,>(x: T) => x;", "const f = (x ) => x;"),
+        ("const f = <const T extends readonly unknown[]>(x: T) => x;", "const f = (x ) => x;"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(strip(src), expected, "{}", src);
+    }
+}
+
+#[test]
+fn elements_that_look_like_type_parameters() {
+    let cases = [
+        (r#"const e = <div extends="x" />;"#, r#"const e = __hook_jsx_runtime.jsx("div", { "extends": "x" });"#),
+        ("const e = <b>(note)</b>;", r#"const e = __hook_jsx_runtime.jsx("b", { children: "(note)" });"#),
+        ("const e = <T>text</T>;", r#"const e = __hook_jsx_runtime.jsx(T, { children: "text" });"#),
+        ("const e = cond ? <A/> : <B/>;", "const e = cond ? __hook_jsx_runtime.jsx(A, {}) : __hook_jsx_runtime.jsx(B, {});"),
+        ("export default <div/>;", r#"export default __hook_jsx_runtime.jsx("div", {});"#),
+        ("function f() {}\n<p>{f()}</p>", r#"function f() {} __hook_jsx_runtime.jsx("p", { children: f() })"#),
+        (r#"const e = <const value="x" />;"#, r#"const e = __hook_jsx_runtime.jsx("const", { value: "x" });"#),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(strip(src), expected, "{}", src);
+        // The same source is plain JSX
+        assert!(transpile_jsx_simple(src).is_ok(), "{}", src);
+    }
+}
+
+#[test]
+fn elements_with_type_arguments() {
+    let cases = [
+        ("const e = <Select<Option> value={v} />;", "const e = __hook_jsx_runtime.jsx(Select, { value: v });"),
+        ("const e = <UI.List<Map<K, V>>>{rows}</UI.List>;", "const e = __hook_jsx_runtime.jsx(UI.List, { children: rows });"),
+        ("const e = <p>a<b>c</b></p>;", r#"const e = __hook_jsx_runtime.jsxs("p", { children: ["a", __hook_jsx_runtime.jsx("b", { children: "c" })] });"#),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(strip(src), expected, "{}", src);
+    }
+}
+
+#[test]
+fn type_arguments_after_expressions() {
+    let cases = [
+        ("const [u, setU] = useState<User | null>(null);", "const [u, setU] = useState (null);"),
+        ("const [u, setU] = useState <User | null>(null);", "const [u, setU] = useState (null);"),
+        ("const ref = useRef<Map<string, number[]>>(new Map());", "const ref = useRef (new Map());"),
+        ("const m = new Map<string, number>();", "const m = new Map ();"),
+        ("const q = tag<T>`x`;", "const q = tag `x`;"),
+        ("const g = f<T>;", "const g = f ;"),
+        ("const r = a < b > (c);", "const r = a (c);"),
+        ("const h = api.get<Item[]>(url).then(r);", "const h = api.get (url).then(r);"),
+        ("class Box<T> extends Base<T> {}", "class Box extends Base {}"),
+        ("interface I {}\nfunction f<T>(x: T) {}", "function f (x ) {}"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(strip(src), expected, "{}", src);
+    }
+}
+
+#[test]
+fn comparisons_are_left_alone() {
+    let cases = [
+        "const c = a <b && c> d;",
+        "const c = a < b > -1;",
+        "const c = a < b > c;",
+        "const c = a < b || c > d;",
+        "for (let i = 0; i < n; i++) x(i > 2);",
+        "if (x < y) { z(); }",
+        "const c = a < (b > c);",
+        "if (lo <= x && hi >= x) { y(); }",
+        "const c = a < b == c > d;",
+    ];
+    for src in cases {
+        assert_eq!(strip(src), src, "{}", src);
+        assert_eq!(transpile_jsx_simple(src).unwrap(), src);
+    }
+}
+
+#[test]
+fn comparisons_next_to_elements() {
+    let out = strip("const e = n < max ? <Item n={n} /> : <Empty />;");
+    assert_eq!(
+        out,
+        "const e = n < max ? __hook_jsx_runtime.jsx(Item, { n: n }) : __hook_jsx_runtime.jsx(Empty, {});"
+    );
+}
+
+#[test]
+fn javascript_mode_reports_type_lists() {
+    for src in [
+        "const id = <T,>(x: T) => x;",
+        "const f = <T extends Base>(x: T) => x;",
+        "const [u, setU] = useState <User | null>(null);",
+        "const m = new Map<string, number>();",
+    ] {
+        let err = transpile_jsx_simple(src).expect_err(src);
+        assert_eq!(err.code, ErrorCode::TypeScriptGeneric, "{}", src);
+    }
+}
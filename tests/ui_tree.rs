@@ -0,0 +1,133 @@
+use relay_hook_transpiler::ui_tree::{UiNode, UiProp, UiTree, UiValue};
+use relay_hook_transpiler::{compile_ui_tree, ElementMap, ErrorCode, TranspileOptions, TranspileTarget};
+
+fn compile(src: &str) -> UiTree {
+    compile_ui_tree(src, &TranspileOptions::default()).unwrap_or_else(|e| panic!("{}", e.render()))
+}
+
+fn prop(name: &str, value: UiValue) -> UiProp {
+    UiProp::Prop { name: name.into(), value }
+}
+
+fn text(value: &str) -> UiNode {
+    UiNode::Text { value: value.into() }
+}
+
+#[test]
+fn static_markup_becomes_data() {
+    let tree = compile(
+        r#"export default () => <div className="card" tabIndex={-1} hidden>
+  <h1 title={'AB'}>Hi &amp; bye</h1>
+  {42}{true}{null}{/* note */}{`plain`}
+</div>;"#,
+    );
+    assert_eq!(tree.roots.len(), 1);
+    let root = &tree.roots[0];
+    assert!(root.is_static);
+    assert!(root.slots.is_empty());
+    assert_eq!(root.span.line, 1);
+    assert_eq!(
+        root.node,
+        UiNode::Element {
+            name: "div".into(),
+            props: vec![
+                prop("className", UiValue::String { value: "card".into() }),
+                prop("tabIndex", UiValue::Number { value: -1.0 }),
+                prop("hidden", UiValue::Boolean { value: true }),
+            ],
+            children: vec![
+                UiNode::Element {
+                    name: "h1".into(),
+                    props: vec![prop("title", UiValue::String { value: "AB".into() })],
+                    children: vec![text("Hi & bye")],
+                },
+                text("42"),
+                text("plain"),
+            ],
+        }
+    );
+}
+
+#[test]
+fn dynamic_expressions_become_slots() {
+    let src = "const App = ({ items, onPick }) => (\n  <>\n    <List {...rest} icon=<Icon /> onPick={onPick}>{items.map(i => <li>{i}</li>)}</List>\n  </>\n);";
+    let tree = compile(src);
+    let root = &tree.roots[0];
+    assert!(!root.is_static);
+    let sources: Vec<&str> = root.slots.iter().map(|slot| slot.source.as_str()).collect();
+    assert_eq!(sources, ["rest", "onPick", "items.map(i => <li>{i}</li>)"]);
+    assert_eq!(&src[root.slots[1].span.start..root.slots[1].span.end], "onPick");
+    assert_eq!(
+        root.node,
+        UiNode::Fragment {
+            children: vec![UiNode::Component {
+                name: "List".into(),
+                props: vec![
+                    UiProp::Spread { slot: 0 },
+                    prop(
+                        "icon",
+                        UiValue::Element {
+                            node: Box::new(UiNode::Component { name: "Icon".into(), props: vec![], children: vec![] }),
+                        },
+                    ),
+                    prop("onPick", UiValue::Dynamic { slot: 1 }),
+                ],
+                children: vec![UiNode::Dynamic { slot: 2 }],
+            }],
+        }
+    );
+}
+
+#[test]
+fn each_root_is_separate_and_components_need_code() {
+    let tree = compile("const a = <p>{name}</p>;\nconst b = <Header />;\nconst c = <br />;");
+    let flags: Vec<(bool, usize)> = tree.roots.iter().map(|root| (root.is_static, root.slots.len())).collect();
+    assert_eq!(flags, [(false, 1), (false, 0), (true, 0)]);
+    assert_eq!(tree.roots[0].node, UiNode::Element { name: "p".into(), props: vec![], children: vec![UiNode::Dynamic { slot: 0 }] });
+}
+
+#[test]
+fn element_map_renames_intrinsics() {
+    let opts = TranspileOptions {
+        target: TranspileTarget::Android,
+        element_map: Some(
+            ElementMap::from_json(r#"{ "elements": { "img": { "name": "Image", "props": { "src": "source" } } } }"#)
+                .unwrap(),
+        ),
+        ..Default::default()
+    };
+    let tree = compile_ui_tree(r#"<img src="a.png" />"#, &opts).unwrap();
+    assert_eq!(
+        tree.roots[0].node,
+        UiNode::Element {
+            name: "Image".into(),
+            props: vec![prop("source", UiValue::String { value: "a.png".into() })],
+            children: vec![],
+        }
+    );
+    assert!(tree.roots[0].is_static);
+}
+
+#[test]
+fn parse_errors_are_reported() {
+    let err = compile_ui_tree("<div><span></div>", &TranspileOptions::default()).unwrap_err();
+    assert_eq!(err.code, ErrorCode::MismatchedClosingTag);
+}
+
+#[cfg(feature = "ast-json")]
+#[test]
+fn tree_serializes_to_json() {
+    let json = relay_hook_transpiler::compile_ui_tree_json(
+        "export default () => <p id=\"x\" onClick={go}>Hi {name}</p>;",
+        &TranspileOptions::default(),
+    )
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let root = &value["roots"][0];
+    assert_eq!(root["is_static"], false);
+    assert_eq!(root["node"]["type"], "Element");
+    assert_eq!(root["node"]["props"][0]["value"], serde_json::json!({ "type": "String", "value": "x" }));
+    assert_eq!(root["node"]["props"][1]["value"], serde_json::json!({ "type": "Dynamic", "slot": 0 }));
+    assert_eq!(root["node"]["children"][1], serde_json::json!({ "type": "Dynamic", "slot": 1 }));
+    assert_eq!(root["slots"][1]["source"], "name");
+}